@@ -62,7 +62,28 @@ fn main() {
 
     loop {
         for msg in rx.try_iter() {
-            client.send_chat(msg)
+            if msg == "/friends" {
+                for friend in client.friends() {
+                    match &friend.alias {
+                        Some(alias) => println!("{} (online as {})", friend.account, alias),
+                        None => println!("{} (offline)", friend.account),
+                    }
+                }
+                for account in client.friend_requests() {
+                    println!("{} wants to be your friend", account);
+                }
+            } else if msg.starts_with('@') {
+                // `@name message` whispers to a player, by their alias or account.
+                let mut parts = msg[1..].splitn(2, ' ');
+                match (parts.next(), parts.next()) {
+                    (Some(alias), Some(message)) if !alias.is_empty() => {
+                        client.whisper(alias.to_owned(), message.trim().to_owned())
+                    }
+                    _ => println!("Usage: @<alias> <message>"),
+                }
+            } else {
+                client.send_chat(msg)
+            }
         }
 
         let events = match client.tick(comp::Controller::default(), clock.get_last_delta()) {
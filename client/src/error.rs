@@ -5,9 +5,14 @@ pub enum Error {
     Network(PostError),
     ServerWentMad,
     ServerTimeout,
-    ServerShutdown,
+    /// The server is stopping, with its reason.
+    ServerShutdown(String),
     TooManyPlayers,
     InvalidAuth,
+    /// The account or address is banned, with the server's explanation.
+    Banned(String),
+    /// A moderator removed us from the server, with their reason.
+    Kicked(String),
     //TODO: InvalidAlias,
     Other(String),
 }
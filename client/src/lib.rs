@@ -9,7 +9,10 @@ pub use specs::{join::Join, saveload::Marker, Entity as EcsEntity, ReadStorage};
 
 use common::{
     comp,
-    msg::{ClientMsg, ClientState, RequestStateError, ServerError, ServerInfo, ServerMsg},
+    msg::{
+        ClientMsg, ClientState, FriendInfo, FriendRequest, GroupInfo, GroupRequest,
+        RequestStateError, ServerError, ServerInfo, ServerMsg, WorldMap,
+    },
     net::PostBox,
     state::{State, Uid},
     terrain::{block::Block, chonk::ChonkMetrics, TerrainChunk, TerrainChunkSize},
@@ -54,6 +57,19 @@ pub struct Client {
     loaded_distance: Option<u32>,
 
     pending_chunks: HashMap<Vec2<i32>, Instant>,
+
+    world_map: Option<WorldMap>,
+
+    group: Option<GroupInfo>,
+    /// The uid and alias of the player who last invited us to their group.
+    group_invite: Option<(u64, String)>,
+
+    friends: Vec<FriendInfo>,
+    /// The accounts that asked to be our friend.
+    friend_requests: Vec<String>,
+
+    /// The last partly typed command we asked to complete, and the ways to complete it.
+    completions: Option<(String, Vec<String>)>,
 }
 
 impl Client {
@@ -79,7 +95,12 @@ impl Client {
                     );
                 }
 
-                let state = State::from_state_package(ecs_state);
+                let mut state = State::from_state_package(ecs_state);
+                // Simulate in the same steps as the server so that predictions agree
+                state.set_tick_rate(server_info.physics_tick_rate);
+                state
+                    .ecs_mut()
+                    .add_resource(server_info.energy_config.clone());
                 let entity = state
                     .ecs()
                     .entity_from_uid(entity_uid)
@@ -89,6 +110,10 @@ impl Client {
             Some(ServerMsg::Error(ServerError::TooManyPlayers)) => {
                 return Err(Error::TooManyPlayers)
             }
+            Some(ServerMsg::Error(ServerError::Banned(reason))) => {
+                return Err(Error::Banned(reason))
+            }
+            Some(ServerMsg::Shutdown(reason)) => return Err(Error::ServerShutdown(reason)),
             _ => return Err(Error::ServerWentMad),
         };
 
@@ -123,6 +148,16 @@ impl Client {
             loaded_distance: None,
 
             pending_chunks: HashMap::new(),
+
+            world_map: None,
+
+            group: None,
+            group_invite: None,
+
+            friends: Vec::new(),
+            friend_requests: Vec::new(),
+
+            completions: None,
         })
     }
 
@@ -143,6 +178,11 @@ impl Client {
                     break Err(Error::InvalidAuth)
                 }
                 Some(ServerMsg::StateAnswer(Ok(ClientState::Registered))) => break Ok(()),
+                Some(ServerMsg::Error(ServerError::Banned(reason))) => {
+                    break Err(Error::Banned(reason))
+                }
+                Some(ServerMsg::Shutdown(reason)) => break Err(Error::ServerShutdown(reason)),
+                Some(ServerMsg::WorldMap(world_map)) => self.world_map = Some(world_map),
                 _ => {}
             }
         }
@@ -224,6 +264,13 @@ impl Client {
         self.postbox.send_message(ClientMsg::chat(msg))
     }
 
+    /// Send a chat message on a channel: `Say` reaches players nearby, `Group` and `Faction`
+    /// reach the members of ours. Any other type is sent to everyone, like `send_chat`.
+    pub fn send_chat_on(&mut self, chat_type: ChatType, message: String) {
+        self.postbox
+            .send_message(ClientMsg::ChatMsg { chat_type, message })
+    }
+
     /// Remove all cached terrain
     #[allow(dead_code)]
     pub fn clear_terrain(&mut self) {
@@ -231,6 +278,104 @@ impl Client {
         self.pending_chunks.clear();
     }
 
+    /// Invite a player to our group, forming one if we aren't in a group yet.
+    pub fn invite_to_group(&mut self, uid: u64) {
+        self.postbox
+            .send_message(ClientMsg::Group(GroupRequest::Invite(uid)));
+    }
+
+    pub fn accept_group_invite(&mut self) {
+        self.group_invite = None;
+        self.postbox
+            .send_message(ClientMsg::Group(GroupRequest::Accept));
+    }
+
+    pub fn decline_group_invite(&mut self) {
+        self.group_invite = None;
+        self.postbox
+            .send_message(ClientMsg::Group(GroupRequest::Decline));
+    }
+
+    /// Remove a member from our group. Only works if we lead it.
+    pub fn kick_from_group(&mut self, uid: u64) {
+        self.postbox
+            .send_message(ClientMsg::Group(GroupRequest::Kick(uid)));
+    }
+
+    pub fn leave_group(&mut self) {
+        self.postbox
+            .send_message(ClientMsg::Group(GroupRequest::Leave));
+    }
+
+    /// Make another member the leader of our group. Only works if we lead it.
+    pub fn promote_in_group(&mut self, uid: u64) {
+        self.postbox
+            .send_message(ClientMsg::Group(GroupRequest::Promote(uid)));
+    }
+
+    /// The group we're in, if any.
+    pub fn group(&self) -> Option<&GroupInfo> {
+        self.group.as_ref()
+    }
+
+    /// The uid and alias of the player whose group invite we haven't answered yet.
+    pub fn group_invite(&self) -> Option<(u64, &str)> {
+        self.group_invite
+            .as_ref()
+            .map(|(uid, alias)| (*uid, alias.as_str()))
+    }
+
+    /// Ask a player to be our friend. They don't need to be online.
+    pub fn send_friend_request(&mut self, alias: String) {
+        self.postbox
+            .send_message(ClientMsg::Friend(FriendRequest::Add(alias)));
+    }
+
+    pub fn accept_friend_request(&mut self, alias: String) {
+        self.postbox
+            .send_message(ClientMsg::Friend(FriendRequest::Accept(alias)));
+    }
+
+    pub fn decline_friend_request(&mut self, alias: String) {
+        self.postbox
+            .send_message(ClientMsg::Friend(FriendRequest::Decline(alias)));
+    }
+
+    pub fn remove_friend(&mut self, alias: String) {
+        self.postbox
+            .send_message(ClientMsg::Friend(FriendRequest::Remove(alias)));
+    }
+
+    /// Send a private message to a player, however far away they are.
+    pub fn whisper(&mut self, alias: String, message: String) {
+        self.postbox
+            .send_message(ClientMsg::Whisper { alias, message });
+    }
+
+    /// Ask the server how a partly typed chat command, like `/tp al`, could be completed. The
+    /// answer is available from `completions` once it arrives.
+    pub fn request_completions(&mut self, line: String) {
+        self.postbox.send_message(ClientMsg::CompleteCommand(line));
+    }
+
+    /// The ways to complete `line`, each as the whole line, if the server answered for it.
+    pub fn completions(&self, line: &str) -> Option<&[String]> {
+        self.completions
+            .as_ref()
+            .filter(|(completed, _)| completed == line)
+            .map(|(_, completions)| completions.as_slice())
+    }
+
+    /// Our friends and whether they are online.
+    pub fn friends(&self) -> &[FriendInfo] {
+        &self.friends
+    }
+
+    /// The accounts waiting for us to accept or decline their friend request.
+    pub fn friend_requests(&self) -> &[String] {
+        &self.friend_requests
+    }
+
     pub fn place_block(&mut self, pos: Vec3<i32>, block: Block) {
         self.postbox.send_message(ClientMsg::PlaceBlock(pos, block));
     }
@@ -410,9 +555,11 @@ impl Client {
                     ServerMsg::Error(e) => match e {
                         ServerError::TooManyPlayers => return Err(Error::ServerWentMad),
                         ServerError::InvalidAuth => return Err(Error::InvalidAuth),
+                        ServerError::Banned(reason) => return Err(Error::Banned(reason)),
+                        ServerError::Kicked(reason) => return Err(Error::Kicked(reason)),
                         //TODO: ServerError::InvalidAlias => return Err(Error::InvalidAlias),
                     },
-                    ServerMsg::Shutdown => return Err(Error::ServerShutdown),
+                    ServerMsg::Shutdown(reason) => return Err(Error::ServerShutdown(reason)),
                     ServerMsg::InitialSync { .. } => return Err(Error::ServerWentMad),
                     ServerMsg::Ping => self.postbox.send_message(ClientMsg::Pong),
                     ServerMsg::Pong => {
@@ -462,6 +609,18 @@ impl Client {
                     ServerMsg::TerrainBlockUpdates(mut blocks) => blocks
                         .drain()
                         .for_each(|(pos, block)| self.state.set_block(pos, block)),
+                    ServerMsg::WorldMap(world_map) => self.world_map = Some(world_map),
+                    ServerMsg::GroupInvite { inviter, alias } => {
+                        self.group_invite = Some((inviter, alias))
+                    }
+                    ServerMsg::GroupUpdate(group) => self.group = group,
+                    ServerMsg::Friends { friends, requests } => {
+                        self.friends = friends;
+                        self.friend_requests = requests;
+                    }
+                    ServerMsg::CommandCompletions { line, completions } => {
+                        self.completions = Some((line, completions))
+                    }
                     ServerMsg::StateAnswer(Ok(state)) => {
                         self.client_state = state;
                     }
@@ -492,6 +651,11 @@ impl Client {
         Ok(frontend_events)
     }
 
+    /// Get the overview of the world sent by the server, if it has arrived yet.
+    pub fn world_map(&self) -> Option<&WorldMap> {
+        self.world_map.as_ref()
+    }
+
     /// Get the player's entity.
     #[allow(dead_code)]
     pub fn entity(&self) -> EcsEntity {
@@ -23,6 +23,8 @@ pub enum Error {
     InvalidType,
     /// Asset does not exist.
     NotFound(String),
+    /// Asset exists but its contents could not be parsed.
+    ParseError(String),
 }
 
 impl From<Arc<dyn Any + 'static + Sync + Send>> for Error {
@@ -43,9 +45,9 @@ lazy_static! {
         RwLock::new(HashMap::new());
 }
 
-// TODO: Remove this function. It's only used in world/ in a really ugly way.To do this properly
-// assets should have all their necessary data in one file. A ron file could be used to combine
-// voxel data with positioning data for example.
+// TODO: Remove this function. It's only used for the dungeon structures in world/ now. Those should
+// be described by RON manifests like the natural structures, which keep their positioning data
+// next to the voxel model specifier.
 /// Function used to load assets from the filesystem or the cache. Permits manipulating the loaded asset with a mapping function.
 /// Example usage:
 /// ```no_run
@@ -4,7 +4,7 @@ use log::warn;
 use notify::{event::Flag, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher as _};
 use std::{
     collections::HashMap,
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc, Mutex, Weak,
@@ -23,6 +23,10 @@ lazy_static! {
 // This will need to be adjusted when specifier mapping to asset location becomes more dynamic
 struct Watcher {
     watching: HashMap<PathBuf, (Handler, Vec<Weak<AtomicBool>>)>,
+    /// The directories of the watched files, with how many of them are in each. Files are
+    /// watched through their directory so that a file replaced by renaming another over it, as
+    /// many editors do when saving, is still noticed.
+    dirs: HashMap<PathBuf, usize>,
     watcher: RecommendedWatcher,
     event_rx: Receiver<Result<Event, notify::Error>>,
 }
@@ -31,6 +35,7 @@ impl Watcher {
         let (event_tx, event_rx) = unbounded();
         Watcher {
             watching: HashMap::new(),
+            dirs: HashMap::new(),
             watcher: notify::Watcher::new(event_tx, Duration::from_secs(2))
                 .expect("Failed to create notify::Watcher"),
             event_rx,
@@ -47,55 +52,77 @@ impl Watcher {
                 }
             }
             None => {
-                if let Err(err) = self.watcher.watch(path.clone(), RecursiveMode::Recursive) {
-                    warn!("Could not start watching {:#?} due to: {}", &path, err);
-                    return;
+                let dir = match path.parent() {
+                    Some(dir) => dir.to_owned(),
+                    None => {
+                        warn!("Could not start watching {:#?}, it has no directory", &path);
+                        return;
+                    }
+                };
+                if !self.dirs.contains_key(&dir) {
+                    if let Err(err) = self.watcher.watch(dir.clone(), RecursiveMode::NonRecursive) {
+                        warn!("Could not start watching {:#?} due to: {}", &path, err);
+                        return;
+                    }
                 }
+                *self.dirs.entry(dir).or_insert(0) += 1;
                 self.watching.insert(path, (handler, vec![signal]));
             }
         }
     }
+    fn unwatch(&mut self, path: &Path) {
+        self.watching.remove(path);
+        let dir = match path.parent() {
+            Some(dir) => dir,
+            None => return,
+        };
+        let files = match self.dirs.get_mut(dir) {
+            Some(files) => {
+                *files -= 1;
+                *files
+            }
+            None => return,
+        };
+        if files == 0 {
+            self.dirs.remove(dir);
+            if let Err(err) = self.watcher.unwatch(dir) {
+                warn!("Error unwatching: {}", err);
+            }
+        }
+    }
     fn handle_event(&mut self, event: Event) {
         // Skip notice events
         if let Some(Flag::Notice) = event.flag() {
             return;
         }
-        if let Event {
-            kind: EventKind::Modify(_),
-            paths,
-            ..
-        } = event
-        {
-            for path in paths {
-                match self.watching.get_mut(&path) {
-                    Some((reloader, ref mut signals)) => {
-                        if !signals.is_empty() {
-                            // Reload this file
-                            reloader();
+        match event.kind {
+            // Renames are modifications of the name, and the new name is among the paths.
+            EventKind::Create(_) | EventKind::Modify(_) => {}
+            _ => return,
+        }
+        for path in event.paths {
+            // Other files in the same directories aren't watched.
+            let no_signals = match self.watching.get_mut(&path) {
+                Some((reloader, ref mut signals)) => {
+                    if !signals.is_empty() {
+                        // Reload this file
+                        reloader();
 
-                            signals.retain(|signal| match signal.upgrade() {
-                                Some(signal) => {
-                                    signal.store(true, Ordering::Release);
-                                    true
-                                }
-                                None => false,
-                            });
-                        }
-                        // If there is no one to signal stop watching this path
-                        if signals.is_empty() {
-                            if let Err(err) = self.watcher.unwatch(&path) {
-                                warn!("Error unwatching: {}", err);
+                        signals.retain(|signal| match signal.upgrade() {
+                            Some(signal) => {
+                                signal.store(true, Ordering::Release);
+                                true
                             }
-                            self.watching.remove(&path);
-                        }
-                    }
-                    None => {
-                        warn!("Watching {:#?} but there are no signals for this path. The path will be unwatched.", path);
-                        if let Err(err) = self.watcher.unwatch(&path) {
-                            warn!("Error unwatching: {}", err);
-                        }
+                            None => false,
+                        });
                     }
+                    signals.is_empty()
                 }
+                None => false,
+            };
+            // If there is no one to signal stop watching this path
+            if no_signals {
+                self.unwatch(&path);
             }
         }
     }
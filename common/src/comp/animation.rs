@@ -13,6 +13,7 @@ pub enum Animation {
     Crun,
     Cidle,
     Cjump,
+    Swim,
 }
 
 #[derive(Copy, Clone, Debug, Serialize, Deserialize)]
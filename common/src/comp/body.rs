@@ -21,6 +21,43 @@ impl Body {
             _ => false,
         }
     }
+
+    /// The half-width of the body's collision box, in blocks, before `Scale` is applied.
+    pub fn radius(&self) -> f32 {
+        match self {
+            Body::Humanoid(body) => body.radius(),
+            Body::Quadruped(_) => 0.6,
+            Body::QuadrupedMedium(_) => 0.8,
+            Body::Object(body) => body.radius(),
+        }
+    }
+
+    /// The height of the body's collision box, in blocks, before `Scale` is applied.
+    pub fn height(&self) -> f32 {
+        match self {
+            Body::Humanoid(body) => body.height(),
+            Body::Quadruped(_) => 1.0,
+            Body::QuadrupedMedium(_) => 1.4,
+            Body::Object(body) => body.height(),
+        }
+    }
+
+    /// The mass of the body, in kilograms, before `Scale` is applied. Heavier bodies push lighter
+    /// ones aside when they collide.
+    pub fn mass(&self) -> f32 {
+        match self {
+            Body::Humanoid(body) => match body.race {
+                humanoid::Race::Orc => 90.0,
+                humanoid::Race::Dwarf => 75.0,
+                humanoid::Race::Danari => 45.0,
+                _ => 60.0,
+            },
+            Body::Quadruped(_) => 30.0,
+            Body::QuadrupedMedium(_) => 80.0,
+            // Objects are about as dense as wood
+            Body::Object(body) => 4.0 * body.radius().powf(2.0) * body.height() * 500.0,
+        }
+    }
 }
 
 impl Component for Body {
@@ -26,6 +26,25 @@ impl Body {
             shoulder: *(&ALL_SHOULDERS).choose(&mut rng).unwrap(),
         }
     }
+
+    pub fn radius(&self) -> f32 {
+        match self.race {
+            Race::Orc => 0.4,
+            Race::Dwarf => 0.35,
+            _ => 0.3,
+        }
+    }
+
+    pub fn height(&self) -> f32 {
+        match (self.race, self.body_type) {
+            (Race::Orc, BodyType::Male) => 1.8,
+            (Race::Orc, BodyType::Female) => 1.6,
+            (Race::Elf, _) => 1.6,
+            (Race::Dwarf, _) => 1.2,
+            (Race::Danari, _) => 1.2,
+            _ => 1.5,
+        }
+    }
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -56,6 +56,53 @@ impl Body {
         let mut rng = thread_rng();
         *(&ALL_OBJECTS).choose(&mut rng).unwrap()
     }
+
+    pub fn radius(&self) -> f32 {
+        match self {
+            Body::Bomb | Body::PotionBlue | Body::PotionGreen | Body::PotionRed | Body::Pouch => {
+                0.2
+            }
+            Body::Pumpkin
+            | Body::Pumpkin2
+            | Body::Pumpkin3
+            | Body::Pumpkin4
+            | Body::Pumpkin5
+            | Body::Campfire
+            | Body::LanternGround
+            | Body::LanternGroundOpen
+            | Body::LanternStanding
+            | Body::LanternStanding2
+            | Body::Chair
+            | Body::Chair2
+            | Body::Chair3 => 0.4,
+            Body::Tent | Body::BedBlue | Body::Bedroll => 1.2,
+            Body::Carpet
+            | Body::CarpetHumanRound
+            | Body::CarpetHumanSquare
+            | Body::CarpetHumanSquare2
+            | Body::CarpetHumanSquircle => 1.0,
+            _ => 0.6,
+        }
+    }
+
+    pub fn height(&self) -> f32 {
+        match self {
+            Body::Bomb | Body::PotionBlue | Body::PotionGreen | Body::PotionRed | Body::Pouch => {
+                0.4
+            }
+            Body::Carpet
+            | Body::CarpetHumanRound
+            | Body::CarpetHumanSquare
+            | Body::CarpetHumanSquare2
+            | Body::CarpetHumanSquircle
+            | Body::Bedroll => 0.1,
+            Body::Scarecrow | Body::LanternStanding | Body::LanternStanding2 | Body::DoorSpooky => {
+                2.0
+            }
+            Body::Tent => 2.5,
+            _ => 1.0,
+        }
+    }
 }
 
 const ALL_OBJECTS: [Body; 46] = [
@@ -9,7 +9,7 @@ pub enum MovementState {
     Jump,
     Glide,
     Roll { time_left: Duration },
-    //Swim,
+    Swim,
 }
 
 impl MovementState {
@@ -12,6 +12,8 @@ pub struct Controller {
     pub roll: bool,
     pub glide: bool,
     pub respawn: bool,
+    /// The block being mined, if any.
+    pub mine: Option<Vec3<i32>>,
 }
 
 impl Component for Controller {
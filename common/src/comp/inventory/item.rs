@@ -1,3 +1,4 @@
+use crate::terrain::BlockKind;
 use specs::{Component, FlaggedStorage};
 use specs_idvs::IDVStorage;
 
@@ -24,6 +25,17 @@ impl Tool {
             Tool::Staff => "staff",
         }
     }
+
+    /// How many times faster than by hand the tool mines a block.
+    pub fn mining_speed(&self, block: BlockKind) -> f32 {
+        match (self, block) {
+            (Tool::Axe, BlockKind::Wood) => 4.0,
+            (Tool::Hammer, BlockKind::Wood) => 1.5,
+            (Tool::Hammer, _) => 4.0,
+            (Tool::Axe, _) => 1.5,
+            _ => 1.0,
+        }
+    }
 }
 
 pub const ALL_TOOLS: [Tool; 7] = [
@@ -70,6 +82,33 @@ impl Armor {
     }
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Ingredient {
+    Wood,
+    Stone,
+    Granite,
+    Sandstone,
+    Coal,
+    CopperOre,
+    IronOre,
+    GoldOre,
+}
+
+impl Ingredient {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Ingredient::Wood => "wood",
+            Ingredient::Stone => "stone",
+            Ingredient::Granite => "granite",
+            Ingredient::Sandstone => "sandstone",
+            Ingredient::Coal => "coal",
+            Ingredient::CopperOre => "copper ore",
+            Ingredient::IronOre => "iron ore",
+            Ingredient::GoldOre => "gold ore",
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ConsumptionEffect {
     Health(i32),
@@ -90,7 +129,9 @@ pub enum Item {
     Consumable {
         effect: ConsumptionEffect,
     },
-    Ingredient,
+    Ingredient {
+        kind: Ingredient,
+    },
 }
 
 impl Item {
@@ -99,7 +140,7 @@ impl Item {
             Item::Tool { kind, .. } => kind.name(),
             Item::Armor { kind, .. } => kind.name(),
             Item::Consumable { .. } => "<consumable>",
-            Item::Ingredient => "<ingredient>",
+            Item::Ingredient { kind } => kind.name(),
         }
     }
 
@@ -108,7 +149,7 @@ impl Item {
             Item::Tool { .. } => "tool",
             Item::Armor { .. } => "armour",
             Item::Consumable { .. } => "consumable",
-            Item::Ingredient => "ingredient",
+            Item::Ingredient { .. } => "ingredient",
         }
     }
 
@@ -2,7 +2,7 @@
 pub mod item;
 
 // Reexports
-pub use self::item::{Item, Tool};
+pub use self::item::{Ingredient, Item, Tool};
 
 use specs::{Component, HashMapStorage, NullStorage};
 use specs_idvs::IDVStorage;
@@ -21,6 +21,14 @@ impl Inventory {
         self.slots.len()
     }
 
+    /// The tool in the first slot, which is the one held in hand.
+    pub fn wielded_tool(&self) -> Option<Tool> {
+        match self.get(0) {
+            Some(Item::Tool { kind, .. }) => Some(kind),
+            _ => None,
+        }
+    }
+
     pub fn insert(&mut self, item: Item) -> Option<Item> {
         match self.slots.iter_mut().find(|slot| slot.is_none()) {
             Some(slot) => {
@@ -0,0 +1,14 @@
+use specs::{Component, HashMapStorage};
+use vek::*;
+
+/// Progress towards breaking a resource block.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Mining {
+    pub pos: Vec3<i32>,
+    /// Fraction of the block that has been mined, from 0 to 1.
+    pub progress: f32,
+}
+
+impl Component for Mining {
+    type Storage = HashMapStorage<Self>;
+}
@@ -1,4 +1,3 @@
-mod admin;
 mod agent;
 mod animation;
 mod body;
@@ -7,13 +6,14 @@ mod controller;
 mod inputs;
 mod inventory;
 mod last;
+mod mining;
 mod phys;
 mod player;
+mod social;
 mod stats;
 mod visual;
 
 // Reexports
-pub use admin::Admin;
 pub use agent::Agent;
 pub use animation::{Animation, AnimationInfo};
 pub use body::{humanoid, object, quadruped, quadruped_medium, Body};
@@ -22,7 +22,9 @@ pub use controller::Controller;
 pub use inputs::CanBuild;
 pub use inventory::{item, Inventory, InventoryUpdate, Item};
 pub use last::Last;
-pub use phys::{ForceUpdate, Ori, PhysicsState, Pos, Scale, Vel};
+pub use mining::Mining;
+pub use phys::{ForceUpdate, NoEntityCollision, Ori, PhysicsState, Pos, Scale, Vel};
 pub use player::Player;
-pub use stats::{Exp, HealthSource, Level, Stats};
+pub use social::{Faction, Group};
+pub use stats::{EnergyConfig, EnergySource, Exp, HealthSource, Level, Stats};
 pub use visual::LightEmitter;
@@ -38,6 +38,7 @@ impl Component for Scale {
 #[derive(Copy, Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
 pub struct PhysicsState {
     pub on_ground: bool,
+    pub in_fluid: bool,
 }
 
 impl Component for PhysicsState {
@@ -51,3 +52,13 @@ pub struct ForceUpdate;
 impl Component for ForceUpdate {
     type Storage = NullStorage<Self>;
 }
+
+// NoEntityCollision
+/// Entities with this component are not pushed by and do not push other entities (e.g: dropped
+/// items and projectiles). They still collide with terrain.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct NoEntityCollision;
+
+impl Component for NoEntityCollision {
+    type Storage = FlaggedStorage<Self, NullStorage<Self>>;
+}
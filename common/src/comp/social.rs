@@ -0,0 +1,24 @@
+use crate::state::Uid;
+use specs::Component;
+use specs_idvs::IDVStorage;
+
+/// The faction a player has joined. Faction chat reaches everyone in the same faction.
+/// Players found a faction by creating it, and join an existing one by accepting an invite from
+/// a member.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Faction(pub String);
+
+impl Component for Faction {
+    type Storage = IDVStorage<Self>;
+}
+
+/// Membership of a group, which is identified by its leader. Group chat reaches every member,
+/// including the leader.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Group {
+    pub leader: Uid,
+}
+
+impl Component for Group {
+    type Storage = IDVStorage<Self>;
+}
@@ -12,9 +12,14 @@ pub enum HealthSource {
     LevelUp,
     Unknown,
 }
-#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub enum EnergySource {
     CastSpell,
+    Roll,
+    Glide,
+    Attack,
+    Regenerate,
+    Revive,
     LevelUp,
     Unknown,
 }
@@ -31,6 +36,9 @@ pub struct Energy {
     current: u32,
     maximum: u32,
     pub last_change: Option<(i32, f64, EnergySource)>,
+    // Fractional change not yet applied to `current`
+    #[serde(skip)]
+    partial: f32,
 }
 
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
@@ -90,6 +98,27 @@ impl Energy {
         self.last_change = Some((amount, 0.0, cause));
     }
 
+    /// Change energy by a fractional amount, such as a per-second rate multiplied by delta time.
+    /// Whole points are applied as they accumulate across calls.
+    pub fn change_by_partial(&mut self, amount: f32, cause: EnergySource) {
+        self.partial += amount;
+        let whole = self.partial.trunc();
+        if whole != 0.0 {
+            self.partial -= whole;
+            self.change_by(whole as i32, cause);
+        }
+    }
+
+    /// Attempt to spend the given amount of energy, returning whether there was enough.
+    pub fn try_spend(&mut self, amount: u32, cause: EnergySource) -> bool {
+        if self.current >= amount {
+            self.change_by(-(amount as i32), cause);
+            true
+        } else {
+            false
+        }
+    }
+
     pub fn set_maximum(&mut self, amount: u32) {
         self.maximum = amount;
         self.current = self.current.min(self.maximum);
@@ -156,6 +185,8 @@ impl Stats {
     pub fn revive(&mut self) {
         self.health
             .set_to(self.health.maximum(), HealthSource::Revive);
+        self.energy
+            .set_to(self.energy.maximum(), EnergySource::Revive);
         self.is_dead = false;
     }
 }
@@ -178,6 +209,7 @@ impl Stats {
                 current: 200,
                 maximum: 200,
                 last_change: None,
+                partial: 0.0,
             },
             is_dead: false,
         }
@@ -208,3 +240,67 @@ pub struct Dying {
 impl Component for Dying {
     type Storage = IDVStorage<Self>;
 }
+
+/// A resource describing how much energy actions cost and how quickly it is regenerated.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EnergyConfig {
+    /// Energy spent to start a roll.
+    pub roll_cost: u32,
+    /// Energy spent to start an attack.
+    pub attack_cost: u32,
+    /// Energy spent per second while gliding.
+    pub glide_cost: f32,
+    /// Energy regenerated per second.
+    pub regen_rate: f32,
+    /// Seconds after spending energy before it starts to regenerate.
+    pub regen_delay: f64,
+}
+
+impl Default for EnergyConfig {
+    fn default() -> Self {
+        Self {
+            roll_cost: 40,
+            attack_cost: 15,
+            glide_cost: 8.0,
+            regen_rate: 25.0,
+            regen_delay: 1.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partial_energy_changes_accumulate() {
+        let mut stats = Stats::new("Test".to_owned()).with_max_energy(100);
+        stats.energy.set_to(50, EnergySource::Unknown);
+
+        for _ in 0..10 {
+            stats
+                .energy
+                .change_by_partial(0.25, EnergySource::Regenerate);
+        }
+        assert_eq!(stats.energy.current(), 52);
+
+        for _ in 0..10 {
+            stats.energy.change_by_partial(-0.5, EnergySource::Glide);
+        }
+        assert_eq!(stats.energy.current(), 48);
+        assert_eq!(
+            stats.energy.last_change.map(|c| c.2),
+            Some(EnergySource::Glide)
+        );
+    }
+
+    #[test]
+    fn spending_is_refused_without_enough_energy() {
+        let mut stats = Stats::new("Test".to_owned()).with_max_energy(30);
+
+        assert!(stats.energy.try_spend(20, EnergySource::Roll));
+        assert!(!stats.energy.try_spend(20, EnergySource::Roll));
+        assert_eq!(stats.energy.current(), 10);
+    }
+}
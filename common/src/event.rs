@@ -20,6 +20,11 @@ pub enum ServerEvent {
     },
     Respawn(EcsEntity),
     Shoot(EcsEntity),
+    /// An entity has finished mining a block.
+    Mine {
+        entity: EcsEntity,
+        pos: Vec3<i32>,
+    },
 }
 
 pub struct EventBus<E> {
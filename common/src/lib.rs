@@ -53,7 +53,7 @@ pub mod volumes;
 /// ```
 pub mod net;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ChatType {
     Broadcast,
     Chat,
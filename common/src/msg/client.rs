@@ -35,9 +35,45 @@ pub enum ClientMsg {
     TerrainChunkRequest {
         key: Vec2<i32>,
     },
+    Group(GroupRequest),
+    Friend(FriendRequest),
+    /// A private message to a player, wherever they are. `alias` may also name their account.
+    Whisper {
+        alias: String,
+        message: String,
+    },
+    /// Ask for the ways to complete a partly typed chat command.
+    CompleteCommand(String),
     Disconnect,
 }
 
+/// Ways of managing the group a player is in. Players are referred to by their uid.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum GroupRequest {
+    /// Invite a player, forming a group led by us if we aren't in one.
+    Invite(u64),
+    /// Join the group we were last invited to.
+    Accept,
+    Decline,
+    /// Remove a member. Only the leader can do this.
+    Kick(u64),
+    Leave,
+    /// Hand the lead over to another member.
+    Promote(u64),
+}
+
+/// Ways of managing our friends list. Accounts are referred to by the name they registered with,
+/// since friends might be offline, or by the alias of a player who is online.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum FriendRequest {
+    /// Ask an account to be our friend.
+    Add(String),
+    /// Accept a request from an account.
+    Accept(String),
+    Decline(String),
+    Remove(String),
+}
+
 impl ClientMsg {
     pub fn chat(message: String) -> ClientMsg {
         ClientMsg::ChatMsg {
@@ -51,6 +87,24 @@ impl ClientMsg {
             message,
         }
     }
+    pub fn say(message: String) -> ClientMsg {
+        ClientMsg::ChatMsg {
+            chat_type: ChatType::Say,
+            message,
+        }
+    }
+    pub fn group(message: String) -> ClientMsg {
+        ClientMsg::ChatMsg {
+            chat_type: ChatType::Group,
+            message,
+        }
+    }
+    pub fn faction(message: String) -> ClientMsg {
+        ClientMsg::ChatMsg {
+            chat_type: ChatType::Faction,
+            message,
+        }
+    }
     pub fn game(message: String) -> ClientMsg {
         ClientMsg::ChatMsg {
             chat_type: ChatType::GameUpdate,
@@ -27,6 +27,7 @@ sphynx::sum_type! {
         LightEmitter(comp::LightEmitter),
         Item(comp::Item),
         Scale(comp::Scale),
+        NoEntityCollision(comp::NoEntityCollision),
     }
 }
 // Automatically derive From<T> for EcsCompPhantom
@@ -44,6 +45,7 @@ sphynx::sum_type! {
         LightEmitter(PhantomData<comp::LightEmitter>),
         Item(PhantomData<comp::Item>),
         Scale(PhantomData<comp::Scale>),
+        NoEntityCollision(PhantomData<comp::NoEntityCollision>),
     }
 }
 impl sphynx::CompPacket for EcsCompPacket {
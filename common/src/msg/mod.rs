@@ -3,9 +3,12 @@ pub mod ecs_packet;
 pub mod server;
 
 // Reexports
-pub use self::client::ClientMsg;
+pub use self::client::{ClientMsg, FriendRequest, GroupRequest};
 pub use self::ecs_packet::{EcsCompPacket, EcsResPacket};
-pub use self::server::{RequestStateError, ServerError, ServerInfo, ServerMsg};
+pub use self::server::{
+    FriendInfo, GroupInfo, MapLocation, RequestStateError, ServerError, ServerInfo, ServerMsg,
+    WorldMap,
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum ClientState {
@@ -20,6 +20,52 @@ pub struct ServerInfo {
     pub name: String,
     pub description: String,
     pub git_hash: String,
+    pub physics_tick_rate: u32,
+    pub energy_config: comp::EnergyConfig,
+}
+
+/// A low resolution overview of the whole world, used to draw maps.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorldMap {
+    /// Width and height of the map, in pixels.
+    pub dimensions: Vec2<u32>,
+    /// Width and height of the area covered by the map, in blocks.
+    pub world_size: Vec2<u32>,
+    /// Colour of each pixel, row by row.  The first row is the northern (highest y) edge of the
+    /// world.
+    pub colors: Vec<Rgb<u8>>,
+    /// Altitude of each pixel, in the same order as `colors`, scaled so that 0 is the lowest
+    /// point in the world and 255 is the highest.
+    pub altitudes: Vec<u8>,
+    pub locations: Vec<MapLocation>,
+}
+
+impl WorldMap {
+    /// Largest width or height, in pixels, of the map sent to clients. The whole map is sent
+    /// in one message, so this has to stay well within the message size limit.
+    pub const MAX_SIZE: u32 = 256;
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupInfo {
+    pub leader: u64,
+    /// The uid and alias of every member, including the leader.
+    pub members: Vec<(u64, String)>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FriendInfo {
+    /// The account the friend registered with, which friend requests refer to them by.
+    pub account: String,
+    /// The alias the friend is playing under, if they're online.
+    pub alias: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MapLocation {
+    pub name: String,
+    /// Position of the centre of the location, in blocks.
+    pub pos: Vec2<i32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -61,15 +107,38 @@ pub enum ServerMsg {
         chunk: Box<TerrainChunk>,
     },
     TerrainBlockUpdates(HashMap<Vec3<i32>, Block>),
+    WorldMap(WorldMap),
+    /// A player invited us to their group.
+    GroupInvite {
+        inviter: u64,
+        alias: String,
+    },
+    /// The group we're in, sent whenever it changes. `None` once we're no longer in one.
+    GroupUpdate(Option<GroupInfo>),
+    /// Our friends and the accounts waiting for us to accept them, sent whenever they change.
+    Friends {
+        friends: Vec<FriendInfo>,
+        requests: Vec<String>,
+    },
+    /// The ways to complete a partly typed chat command, each as the whole line.
+    CommandCompletions {
+        line: String,
+        completions: Vec<String>,
+    },
     Error(ServerError),
     Disconnect,
-    Shutdown,
+    /// The server is stopping, with the reason to show the player.
+    Shutdown(String),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ServerError {
     TooManyPlayers,
     InvalidAuth,
+    /// The account or address is banned, with a message explaining why and for how long.
+    Banned(String),
+    /// A moderator removed the player from the server, with the reason they gave.
+    Kicked(String),
     //TODO: InvalidAlias,
 }
 
@@ -86,6 +155,24 @@ impl ServerMsg {
             message,
         }
     }
+    pub fn say(message: String) -> ServerMsg {
+        ServerMsg::ChatMsg {
+            chat_type: ChatType::Say,
+            message,
+        }
+    }
+    pub fn group(message: String) -> ServerMsg {
+        ServerMsg::ChatMsg {
+            chat_type: ChatType::Group,
+            message,
+        }
+    }
+    pub fn faction(message: String) -> ServerMsg {
+        ServerMsg::ChatMsg {
+            chat_type: ChatType::Faction,
+            message,
+        }
+    }
     pub fn game(message: String) -> ServerMsg {
         ServerMsg::ChatMsg {
             chat_type: ChatType::GameUpdate,
@@ -8,7 +8,7 @@ use std::{
     marker::PhantomData,
     net::{Shutdown, SocketAddr, TcpListener, TcpStream},
     sync::{
-        atomic::{AtomicBool, Ordering},
+        atomic::{AtomicBool, AtomicU64, Ordering},
         Arc,
     },
     thread,
@@ -45,9 +45,27 @@ pub trait PostMsg = Serialize + DeserializeOwned + 'static + Send;
 
 const MAX_MSG_SIZE: usize = 1 << 20;
 
+/// Bytes moved through a set of connections, counted by their worker threads.
+#[derive(Debug, Default)]
+pub struct Traffic {
+    sent: AtomicU64,
+    received: AtomicU64,
+}
+
+impl Traffic {
+    pub fn bytes_sent(&self) -> u64 {
+        self.sent.load(Ordering::Relaxed)
+    }
+
+    pub fn bytes_received(&self) -> u64 {
+        self.received.load(Ordering::Relaxed)
+    }
+}
+
 pub struct PostOffice<S: PostMsg, R: PostMsg> {
     listener: TcpListener,
     error: Option<Error>,
+    traffic: Arc<Traffic>,
     phantom: PhantomData<(S, R)>,
 }
 
@@ -59,6 +77,7 @@ impl<S: PostMsg, R: PostMsg> PostOffice<S, R> {
         Ok(Self {
             listener,
             error: None,
+            traffic: Arc::new(Traffic::default()),
             phantom: PhantomData,
         })
     }
@@ -67,6 +86,11 @@ impl<S: PostMsg, R: PostMsg> PostOffice<S, R> {
         self.error.clone()
     }
 
+    /// Bytes sent and received by all the postboxes this office has handed out.
+    pub fn traffic(&self) -> &Traffic {
+        &self.traffic
+    }
+
     pub fn new_postboxes(&mut self) -> impl ExactSizeIterator<Item = PostBox<S, R>> {
         let mut new = Vec::new();
 
@@ -76,7 +100,9 @@ impl<S: PostMsg, R: PostMsg> PostOffice<S, R> {
 
         loop {
             match self.listener.accept() {
-                Ok((stream, _sock)) => new.push(PostBox::from_stream(stream).unwrap()),
+                Ok((stream, _sock)) => {
+                    new.push(PostBox::from_stream(stream, self.traffic.clone()).unwrap())
+                }
                 Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
                 Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
                 Err(e) => {
@@ -96,15 +122,20 @@ pub struct PostBox<S: PostMsg, R: PostMsg> {
     worker: Option<thread::JoinHandle<()>>,
     running: Arc<AtomicBool>,
     error: Option<Error>,
+    peer_addr: Option<SocketAddr>,
 }
 
 impl<S: PostMsg, R: PostMsg> PostBox<S, R> {
     pub fn to<A: Into<SocketAddr>>(addr: A) -> Result<Self, Error> {
-        Self::from_stream(TcpStream::connect(addr.into())?)
+        Self::from_stream(
+            TcpStream::connect(addr.into())?,
+            Arc::new(Traffic::default()),
+        )
     }
 
-    fn from_stream(stream: TcpStream) -> Result<Self, Error> {
+    fn from_stream(stream: TcpStream, traffic: Arc<Traffic>) -> Result<Self, Error> {
         stream.set_nonblocking(true)?;
+        let peer_addr = stream.peer_addr().ok();
 
         let running = Arc::new(AtomicBool::new(true));
         let worker_running = running.clone();
@@ -112,7 +143,8 @@ impl<S: PostMsg, R: PostMsg> PostBox<S, R> {
         let (send_tx, send_rx) = channel::unbounded();
         let (recv_tx, recv_rx) = channel::unbounded();
 
-        let worker = thread::spawn(move || Self::worker(stream, send_rx, recv_tx, worker_running));
+        let worker =
+            thread::spawn(move || Self::worker(stream, send_rx, recv_tx, worker_running, traffic));
 
         Ok(Self {
             send_tx,
@@ -120,6 +152,7 @@ impl<S: PostMsg, R: PostMsg> PostBox<S, R> {
             worker: Some(worker),
             running,
             error: None,
+            peer_addr,
         })
     }
 
@@ -127,6 +160,11 @@ impl<S: PostMsg, R: PostMsg> PostBox<S, R> {
         self.error.clone()
     }
 
+    /// The address of the other end of the connection.
+    pub fn peer_addr(&self) -> Option<SocketAddr> {
+        self.peer_addr
+    }
+
     pub fn send_message(&mut self, msg: S) {
         let _ = self.send_tx.send(msg);
     }
@@ -175,6 +213,7 @@ impl<S: PostMsg, R: PostMsg> PostBox<S, R> {
         send_rx: channel::Receiver<S>,
         recv_tx: channel::Sender<Result<R, Error>>,
         running: Arc<AtomicBool>,
+        traffic: Arc<Traffic>,
     ) {
         let mut outgoing_chunks = VecDeque::new();
         let mut incoming_buf = Vec::new();
@@ -229,8 +268,11 @@ impl<S: PostMsg, R: PostMsg> PostBox<S, R> {
                 for _ in 0..1000 {
                     match outgoing_chunks.pop_front() {
                         Some(mut chunk) => match stream.write(&chunk) {
-                            Ok(n) if n == chunk.len() => {}
+                            Ok(n) if n == chunk.len() => {
+                                traffic.sent.fetch_add(n as u64, Ordering::Relaxed);
+                            }
                             Ok(n) => {
+                                traffic.sent.fetch_add(n as u64, Ordering::Relaxed);
                                 outgoing_chunks.push_front(chunk.split_off(n));
                                 break;
                             }
@@ -254,7 +296,10 @@ impl<S: PostMsg, R: PostMsg> PostBox<S, R> {
                     let mut buf = [0; 4096];
 
                     match stream.read(&mut buf) {
-                        Ok(n) => incoming_buf.extend_from_slice(&buf[0..n]),
+                        Ok(n) => {
+                            traffic.received.fetch_add(n as u64, Ordering::Relaxed);
+                            incoming_buf.extend_from_slice(&buf[0..n]);
+                        }
                         Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
                         Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
                         // Worker error
@@ -374,6 +419,39 @@ mod tests {
     }
     */
 
+    #[test]
+    fn world_map_fits_in_message() {
+        use crate::msg::{MapLocation, WorldMap};
+        use vek::*;
+
+        // Noise can't be compressed, so this is larger than any real map.
+        let mut seed = 0x2545_f491u32;
+        let mut noise = || {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            seed as u8
+        };
+        let pixels = (WorldMap::MAX_SIZE * WorldMap::MAX_SIZE) as usize;
+        let map = WorldMap {
+            dimensions: Vec2::broadcast(WorldMap::MAX_SIZE),
+            world_size: Vec2::broadcast(1 << 15),
+            colors: (0..pixels)
+                .map(|_| Rgb::new(noise(), noise(), noise()))
+                .collect(),
+            altitudes: (0..pixels).map(|_| noise()).collect(),
+            locations: (0..500)
+                .map(|i| MapLocation {
+                    name: format!("Location with a long name {}", i),
+                    pos: Vec2::broadcast(i),
+                })
+                .collect(),
+        };
+
+        let msg_bytes = lz4_compress::compress(&bincode::serialize(&map).unwrap());
+        assert!(msg_bytes.len() < MAX_MSG_SIZE / 2);
+    }
+
     #[test]
     fn send_recv() {
         let (mut postoffice, sock) = create_postoffice::<(), i32>(2).unwrap();
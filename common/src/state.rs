@@ -33,15 +33,19 @@ pub struct TimeOfDay(pub f64);
 #[derive(Copy, Clone, Debug, Default, Serialize, Deserialize)]
 pub struct Time(pub f64);
 
-/// A resource that stores the time since the previous tick.
+/// A resource that stores the time simulated by a single step of the ECS systems. This is always
+/// the fixed step length, regardless of how much real time has passed between frames.
 #[derive(Default)]
 pub struct DeltaTime(pub f32);
 
-/// At what point should we stop speeding up physics to compensate for lag? If we speed physics up
-/// too fast, we'd skip important physics events like collisions. This constant determines the
-/// upper limit. If delta time exceeds this value, the game's physics will begin to produce time
-/// lag. Ideally, we'd avoid such a situation.
-const MAX_DELTA_TIME: f32 = 1.0;
+/// How many fixed simulation steps are run per second by default.
+pub const DEFAULT_TICK_RATE: u32 = 60;
+
+/// At what point should we stop catching up on simulation steps to compensate for lag? If we run
+/// too many steps at once, a slow frame results in an even slower next frame. If more than this
+/// many steps are owed in a single call to `State::tick`, the remainder is dropped and the game's
+/// physics will begin to produce time lag. Ideally, we'd avoid such a situation.
+const MAX_STEPS_PER_TICK: u32 = 30;
 const HUMANOID_JUMP_ACCEL: f32 = 18.0;
 
 #[derive(Default)]
@@ -81,6 +85,12 @@ pub struct State {
     ecs: sphynx::World<EcsCompPacket, EcsResPacket>,
     // Avoid lifetime annotation by storing a thread pool instead of the whole dispatcher
     thread_pool: Arc<ThreadPool>,
+    // The length of a single simulation step
+    step_dt: Duration,
+    // Time that has passed but has not yet been simulated
+    accumulator: Duration,
+    // The real time passed to the most recent call to `tick`
+    frame_dt: Duration,
 }
 
 impl Default for State {
@@ -89,6 +99,9 @@ impl Default for State {
         Self {
             ecs: sphynx::World::new(specs::World::new(), Self::setup_sphynx_world),
             thread_pool: Arc::new(ThreadPoolBuilder::new().build().unwrap()),
+            step_dt: Duration::from_secs(1) / DEFAULT_TICK_RATE,
+            accumulator: Duration::default(),
+            frame_dt: Duration::default(),
         }
     }
 }
@@ -105,6 +118,9 @@ impl State {
                 state_package,
             ),
             thread_pool: Arc::new(ThreadPoolBuilder::new().build().unwrap()),
+            step_dt: Duration::from_secs(1) / DEFAULT_TICK_RATE,
+            accumulator: Duration::default(),
+            frame_dt: Duration::default(),
         }
     }
 
@@ -119,6 +135,7 @@ impl State {
         ecs.register_synced::<comp::LightEmitter>();
         ecs.register_synced::<comp::Item>();
         ecs.register_synced::<comp::Scale>();
+        ecs.register_synced::<comp::NoEntityCollision>();
 
         // Register components send from clients -> server
         ecs.register::<comp::Controller>();
@@ -136,16 +153,20 @@ impl State {
         // Register client-local components
         ecs.register::<comp::AnimationInfo>();
 
+        // Register components simulated separately by the server and clients
+        ecs.register::<comp::Mining>();
+
         // Register server-local components
         ecs.register::<comp::Last<comp::Pos>>();
         ecs.register::<comp::Last<comp::Vel>>();
         ecs.register::<comp::Last<comp::Ori>>();
         ecs.register::<comp::Last<comp::CharacterState>>();
         ecs.register::<comp::Agent>();
+        ecs.register::<comp::Faction>();
+        ecs.register::<comp::Group>();
         ecs.register::<comp::ForceUpdate>();
         ecs.register::<comp::InventoryUpdate>();
         ecs.register::<comp::Inventory>();
-        ecs.register::<comp::Admin>();
 
         // Register synced resources used by the ECS.
         ecs.add_resource_synced(TimeOfDay(0.0));
@@ -153,6 +174,7 @@ impl State {
         // Register unsynced resources used by the ECS.
         ecs.add_resource(Time(0.0));
         ecs.add_resource(DeltaTime(0.0));
+        ecs.add_resource(comp::EnergyConfig::default());
         ecs.add_resource(TerrainMap::new().unwrap());
         ecs.add_resource(BlockChange::default());
         ecs.add_resource(TerrainChanges::default());
@@ -215,10 +237,29 @@ impl State {
     }
 
     /// Get the current delta time.
+    ///
+    /// This is the length of a single simulation step. Use `get_frame_delta_time` for timings that
+    /// should follow the frame rate, such as rendering.
     pub fn get_delta_time(&self) -> f32 {
         self.ecs.read_resource::<DeltaTime>().0
     }
 
+    /// Get the real time that passed during the most recent call to `tick`.
+    pub fn get_frame_delta_time(&self) -> f32 {
+        self.frame_dt.as_secs_f32()
+    }
+
+    /// Get the number of simulation steps run per second.
+    pub fn tick_rate(&self) -> u32 {
+        (Duration::from_secs(1).as_nanos() / self.step_dt.as_nanos()) as u32
+    }
+
+    /// Set the number of simulation steps run per second. Simulations will only produce identical
+    /// results for identical inputs if they share a tick rate.
+    pub fn set_tick_rate(&mut self, tick_rate: u32) {
+        self.step_dt = Duration::from_secs(1) / tick_rate.max(1);
+    }
+
     /// Get a reference to this state's terrain.
     pub fn terrain(&self) -> Fetch<TerrainMap> {
         self.ecs.read_resource()
@@ -283,23 +324,25 @@ impl State {
     }
 
     /// Execute a single tick, simulating the game state by the given duration.
+    ///
+    /// The game state is advanced in fixed steps of `1 / tick_rate` seconds, so the simulation
+    /// does not depend on how the given duration is divided between calls. Any time left over is
+    /// carried into the next call.
     pub fn tick(&mut self, dt: Duration) {
-        // Change the time accordingly.
-        self.ecs.write_resource::<TimeOfDay>().0 += dt.as_secs_f64() * DAY_CYCLE_FACTOR;
-        self.ecs.write_resource::<Time>().0 += dt.as_secs_f64();
-
-        // Update delta time.
-        // Beyond a delta time of MAX_DELTA_TIME, start lagging to avoid skipping important physics events.
-        self.ecs.write_resource::<DeltaTime>().0 = dt.as_secs_f32().min(MAX_DELTA_TIME);
-
-        // Run systems to update the world.
-        // Create and run a dispatcher for ecs systems.
-        let mut dispatch_builder = DispatcherBuilder::new().with_pool(self.thread_pool.clone());
-        sys::add_local_systems(&mut dispatch_builder);
-        // This dispatches all the systems in parallel.
-        dispatch_builder.build().dispatch(&self.ecs.res);
-
-        self.ecs.maintain();
+        self.frame_dt = dt;
+        self.accumulator += dt;
+
+        let mut steps = 0;
+        while self.accumulator >= self.step_dt {
+            // Beyond MAX_STEPS_PER_TICK, start lagging to avoid falling ever further behind.
+            if steps == MAX_STEPS_PER_TICK {
+                self.accumulator = Duration::default();
+                break;
+            }
+            self.accumulator -= self.step_dt;
+            self.step();
+            steps += 1;
+        }
 
         // Apply terrain changes
         let mut terrain = self.ecs.write_resource::<TerrainMap>();
@@ -314,6 +357,25 @@ impl State {
             &mut self.ecs.write_resource::<BlockChange>().blocks,
             Default::default(),
         );
+    }
+
+    /// Run the ECS systems for a single fixed step.
+    fn step(&mut self) {
+        // Change the time accordingly.
+        self.ecs.write_resource::<TimeOfDay>().0 += self.step_dt.as_secs_f64() * DAY_CYCLE_FACTOR;
+        self.ecs.write_resource::<Time>().0 += self.step_dt.as_secs_f64();
+
+        // Update delta time.
+        self.ecs.write_resource::<DeltaTime>().0 = self.step_dt.as_secs_f32();
+
+        // Run systems to update the world.
+        // Create and run a dispatcher for ecs systems.
+        let mut dispatch_builder = DispatcherBuilder::new().with_pool(self.thread_pool.clone());
+        sys::add_local_systems(&mut dispatch_builder);
+        // This dispatches all the systems in parallel.
+        dispatch_builder.build().dispatch(&self.ecs.res);
+
+        self.ecs.maintain();
 
         // Process local events
         let events = self.ecs.read_resource::<EventBus<LocalEvent>>().recv_all();
@@ -344,3 +406,91 @@ impl State {
         self.ecs.write_resource::<TerrainChanges>().clear();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terrain::{BlockKind, TerrainChunkMeta};
+    use specs::{Builder, Entity as EcsEntity};
+
+    // Create a state with flat ground and a single character walking and jumping across it
+    fn create_state() -> (State, EcsEntity) {
+        let mut state = State::default();
+        for x in -1..2 {
+            for y in -1..2 {
+                state.insert_chunk(
+                    Vec2::new(x, y),
+                    TerrainChunk::new(
+                        0,
+                        Block::new(BlockKind::Dense, Rgb::new(128, 128, 128)),
+                        Block::empty(),
+                        TerrainChunkMeta::void(),
+                    ),
+                );
+            }
+        }
+
+        let entity = state
+            .ecs_mut()
+            .create_entity_synced()
+            .with(comp::Pos(Vec3::new(16.0, 16.0, 4.0)))
+            .with(comp::Vel(Vec3::zero()))
+            .with(comp::Ori(Vec3::unit_y()))
+            .with(comp::Body::Humanoid(comp::humanoid::Body::random()))
+            .with(comp::Stats::new("Runner".to_owned()))
+            .with(comp::CharacterState::default())
+            .with(comp::Controller {
+                move_dir: Vec2::new(0.6, 0.8),
+                jump: true,
+                ..comp::Controller::default()
+            })
+            .build();
+
+        (state, entity)
+    }
+
+    #[test]
+    fn steps_are_independent_of_frame_rate() {
+        let (mut server, server_entity) = create_state();
+        let (mut client, client_entity) = create_state();
+        let body = server.read_component_cloned::<comp::Body>(server_entity);
+        client.write_component(client_entity, body.unwrap());
+
+        // A 30 TPS server and a 144 FPS client
+        for _ in 0..60 {
+            server.tick(Duration::from_secs(1) / 30);
+            server.cleanup();
+        }
+        while client.get_time() < server.get_time() {
+            client.tick(Duration::from_secs(1) / 144);
+            client.cleanup();
+        }
+
+        assert_eq!(client.get_time(), server.get_time());
+        assert_eq!(
+            client.read_component_cloned::<comp::Pos>(client_entity),
+            server.read_component_cloned::<comp::Pos>(server_entity),
+        );
+        assert_eq!(
+            client.read_component_cloned::<comp::Vel>(client_entity),
+            server.read_component_cloned::<comp::Vel>(server_entity),
+        );
+        assert_eq!(
+            client.read_component_cloned::<comp::CharacterState>(client_entity),
+            server.read_component_cloned::<comp::CharacterState>(server_entity),
+        );
+    }
+
+    #[test]
+    fn tick_rate_sets_step_length() {
+        let mut state = State::default();
+        assert_eq!(state.tick_rate(), DEFAULT_TICK_RATE);
+
+        state.set_tick_rate(20);
+        state.tick(Duration::from_millis(120));
+        assert_eq!(state.tick_rate(), 20);
+        assert_eq!(state.get_delta_time(), 0.05);
+        // Two steps have run, with the remaining 20ms carried over
+        assert_eq!(state.get_time(), 0.1);
+    }
+}
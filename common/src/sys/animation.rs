@@ -40,6 +40,7 @@ impl<'a> System<'a> for Sys {
                 (true, Run, Wield { .. }) => Animation::Crun,
                 (false, Jump, Wield { .. }) => Animation::Cjump,
                 (_, Glide, Idle) => Animation::Gliding,
+                (_, Swim, _) => Animation::Swim,
                 (_, _, Attack { .. }) => Animation::Attack,
                 (_, _, Block { .. }) => Animation::Block,
                 // Impossible animation (Caused by missing animations or syncing delays)
@@ -1,6 +1,7 @@
 use crate::{
     comp::{
-        ActionState::*, CharacterState, Controller, ForceUpdate, HealthSource, Ori, Pos, Stats, Vel,
+        ActionState::*, Body, CharacterState, Controller, ForceUpdate, HealthSource, Ori, Pos,
+        Scale, Stats, Vel,
     },
     state::{DeltaTime, Uid},
 };
@@ -18,6 +19,8 @@ const BASE_DMG: i32 = 10;
 const BLOCK_EFFICIENCY: f32 = 0.9;
 
 const ATTACK_RANGE: f32 = 4.0;
+// How far to either side of the target's collision box a swing can still connect
+const ATTACK_WIDTH: f32 = 0.7;
 const BLOCK_ANGLE: f32 = 180.0;
 
 const KNOCKBACK_XY: f32 = 2.0;
@@ -32,6 +35,8 @@ impl<'a> System<'a> for Sys {
         Read<'a, DeltaTime>,
         ReadStorage<'a, Pos>,
         ReadStorage<'a, Ori>,
+        ReadStorage<'a, Scale>,
+        ReadStorage<'a, Body>,
         ReadStorage<'a, Controller>,
         WriteStorage<'a, Vel>,
         WriteStorage<'a, CharacterState>,
@@ -47,6 +52,8 @@ impl<'a> System<'a> for Sys {
             dt,
             positions,
             orientations,
+            scales,
+            bodies,
             controllers,
             mut velocities,
             mut character_states,
@@ -55,8 +62,16 @@ impl<'a> System<'a> for Sys {
         ): Self::SystemData,
     ) {
         // Attacks
-        for (entity, uid, pos, ori, _) in
-            (&entities, &uids, &positions, &orientations, &controllers).join()
+        for (entity, uid, pos, ori, scale_maybe, body, _) in (
+            &entities,
+            &uids,
+            &positions,
+            &orientations,
+            scales.maybe(),
+            &bodies,
+            &controllers,
+        )
+            .join()
         {
             let (deal_damage, should_end) = if let Some(Attack { time_left, applied }) =
                 &mut character_states.get_mut(entity).map(|c| &mut c.action)
@@ -80,17 +95,26 @@ impl<'a> System<'a> for Sys {
                 if let Some(Attack { time_left, applied }) =
                     &character_states.get(entity).map(|c| c.action)
                 {
+                    let height = body.height() * scale_maybe.map(|s| s.0).unwrap_or(1.0);
+
                     // Go through all other entities
-                    for (b, pos_b, ori_b, character_b, mut vel_b, stat_b) in (
-                        &entities,
-                        &positions,
-                        &orientations,
-                        &character_states,
-                        &mut velocities,
-                        &mut stats,
-                    )
-                        .join()
+                    for (b, pos_b, ori_b, scale_b_maybe, body_b, character_b, mut vel_b, stat_b) in
+                        (
+                            &entities,
+                            &positions,
+                            &orientations,
+                            scales.maybe(),
+                            &bodies,
+                            &character_states,
+                            &mut velocities,
+                            &mut stats,
+                        )
+                            .join()
                     {
+                        let scale_b = scale_b_maybe.map(|s| s.0).unwrap_or(1.0);
+                        let rad_b = body_b.radius() * scale_b;
+                        let height_b = body_b.height() * scale_b;
+
                         // 2D versions
                         let pos2 = Vec2::from(pos.0);
                         let pos_b2: Vec2<f32> = Vec2::from(pos_b.0);
@@ -99,9 +123,12 @@ impl<'a> System<'a> for Sys {
                         // Check if it is a hit
                         if entity != b
                             && !stat_b.is_dead
-                            && pos.0.distance_squared(pos_b.0) < ATTACK_RANGE.powi(2)
-                            // TODO: Use size instead of 1.0
-                            && ori2.angle_between(pos_b2 - pos2) < (1.0 / pos2.distance(pos_b2)).atan()
+                            && pos2.distance_squared(pos_b2) < (ATTACK_RANGE + rad_b).powi(2)
+                            // The swing must overlap the target vertically...
+                            && pos.0.z < pos_b.0.z + height_b
+                            && pos_b.0.z < pos.0.z + height
+                            // ...and be aimed close enough to its collision box
+                            && ori2.angle_between(pos_b2 - pos2) < ((rad_b + ATTACK_WIDTH) / pos2.distance(pos_b2)).atan()
                         {
                             let dmg = if character_b.action.is_block()
                                 && ori_b.0.angle_between(pos.0 - pos_b.0).to_degrees()
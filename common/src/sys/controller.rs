@@ -4,10 +4,11 @@ use super::{
 };
 use crate::{
     comp::{
-        ActionState::*, Body, CharacterState, Controller, MovementState::*, PhysicsState, Stats,
-        Vel,
+        ActionState::*, Body, CharacterState, Controller, EnergyConfig, EnergySource,
+        MovementState::*, PhysicsState, Stats, Vel,
     },
     event::{EventBus, LocalEvent, ServerEvent},
+    state::DeltaTime,
 };
 use specs::{Entities, Join, Read, ReadStorage, System, WriteStorage};
 use std::time::Duration;
@@ -17,10 +18,12 @@ pub struct Sys;
 impl<'a> System<'a> for Sys {
     type SystemData = (
         Entities<'a>,
+        Read<'a, DeltaTime>,
+        Read<'a, EnergyConfig>,
         Read<'a, EventBus<ServerEvent>>,
         Read<'a, EventBus<LocalEvent>>,
         WriteStorage<'a, Controller>,
-        ReadStorage<'a, Stats>,
+        WriteStorage<'a, Stats>,
         ReadStorage<'a, Body>,
         ReadStorage<'a, Vel>,
         ReadStorage<'a, PhysicsState>,
@@ -31,10 +34,12 @@ impl<'a> System<'a> for Sys {
         &mut self,
         (
             entities,
+            dt,
+            energy_config,
             server_bus,
             local_bus,
             mut controllers,
-            stats,
+            mut stats,
             bodies,
             velocities,
             physics_states,
@@ -47,7 +52,7 @@ impl<'a> System<'a> for Sys {
         for (entity, controller, stats, body, vel, physics, mut character) in (
             &entities,
             &mut controllers,
-            &stats,
+            &mut stats,
             &bodies,
             &velocities,
             &physics_states,
@@ -89,12 +94,21 @@ impl<'a> System<'a> for Sys {
                 && (character.action == Idle || character.action.is_wield())
                 && character.movement == Jump
                 && body.is_humanoid()
+                && stats.energy.current() > 0
             {
                 character.movement = Glide;
-            } else if !controller.glide && character.movement == Glide {
+            } else if (!controller.glide || stats.energy.current() == 0)
+                && character.movement == Glide
+            {
                 character.movement = Jump;
             }
 
+            if character.movement == Glide {
+                stats
+                    .energy
+                    .change_by_partial(-energy_config.glide_cost * dt.0, EnergySource::Glide);
+            }
+
             // Wield
             if controller.attack
                 && character.action == Idle
@@ -113,7 +127,11 @@ impl<'a> System<'a> for Sys {
             {
                 // TODO: Check if wield ability exists
                 if let Wield { time_left } = character.action {
-                    if time_left == Duration::default() {
+                    if time_left == Duration::default()
+                        && stats
+                            .energy
+                            .try_spend(energy_config.attack_cost, EnergySource::Attack)
+                    {
                         character.action = Attack {
                             time_left: ATTACK_DURATION,
                             applied: false,
@@ -139,6 +157,9 @@ impl<'a> System<'a> for Sys {
                 && (character.action == Idle || character.action.is_wield())
                 && character.movement == Run
                 && physics.on_ground
+                && stats
+                    .energy
+                    .try_spend(energy_config.roll_cost, EnergySource::Roll)
             {
                 character.movement = Roll {
                     time_left: ROLL_DURATION,
@@ -0,0 +1,147 @@
+use crate::{
+    comp::{item::Tool, CanBuild, Controller, Inventory, Mining, Pos, Stats},
+    event::{EventBus, ServerEvent},
+    state::DeltaTime,
+    terrain::{BlockKind, TerrainMap},
+    vol::ReadVol,
+};
+use specs::{Entities, Join, Read, ReadExpect, ReadStorage, System, WriteStorage};
+
+/// How far away a block can be mined from.
+const MINE_RANGE: f32 = 5.0;
+/// How quickly blocks are mined without a tool, relative to an ordinary tool.
+const HAND_SPEED: f32 = 0.5;
+
+/// This system is responsible for mining the blocks that entities are working on
+pub struct Sys;
+impl<'a> System<'a> for Sys {
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, DeltaTime>,
+        Read<'a, EventBus<ServerEvent>>,
+        ReadExpect<'a, TerrainMap>,
+        ReadStorage<'a, Pos>,
+        ReadStorage<'a, Controller>,
+        ReadStorage<'a, Stats>,
+        ReadStorage<'a, Inventory>,
+        ReadStorage<'a, CanBuild>,
+        WriteStorage<'a, Mining>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            dt,
+            server_bus,
+            terrain,
+            positions,
+            controllers,
+            stats,
+            inventories,
+            can_build,
+            mut minings,
+        ): Self::SystemData,
+    ) {
+        let mut server_emitter = server_bus.emitter();
+
+        for (entity, pos, controller, stats, inventory, _) in (
+            &entities,
+            &positions,
+            &controllers,
+            &stats,
+            inventories.maybe(),
+            // Builders break blocks instantly instead
+            !&can_build,
+        )
+            .join()
+        {
+            let target = controller
+                .mine
+                .filter(|_| !stats.is_dead)
+                .filter(|target| {
+                    pos.0.distance_squared(target.map(|e| e as f32 + 0.5)) < MINE_RANGE.powi(2)
+                })
+                .and_then(|target| {
+                    let kind = terrain.get(target).ok()?.kind();
+                    Some((target, kind, kind.mine_time()?))
+                });
+
+            let (target, kind, mine_time) = match target {
+                Some(target) => target,
+                None => {
+                    minings.remove(entity);
+                    continue;
+                }
+            };
+
+            let speed = mining_speed(inventory.and_then(|inv| inv.wielded_tool()), kind);
+            let progress = match minings.get(entity) {
+                Some(mining) if mining.pos == target => mining.progress,
+                // Switching to a different block starts again from scratch
+                _ => 0.0,
+            } + dt.0 * speed / mine_time;
+
+            if progress >= 1.0 {
+                server_emitter.emit(ServerEvent::Mine {
+                    entity,
+                    pos: target,
+                });
+                minings.remove(entity);
+            } else {
+                let _ = minings.insert(
+                    entity,
+                    Mining {
+                        pos: target,
+                        progress,
+                    },
+                );
+            }
+        }
+    }
+}
+
+fn mining_speed(tool: Option<Tool>, kind: BlockKind) -> f32 {
+    match tool {
+        Some(tool) => tool.mining_speed(kind),
+        // Ore is too hard to dig out with bare hands
+        None if kind.is_ore() => 0.0,
+        None => HAND_SPEED,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Seconds taken to mine a block of the given kind.
+    fn time_to_mine(tool: Option<Tool>, kind: BlockKind) -> Option<f32> {
+        let speed = mining_speed(tool, kind);
+        kind.mine_time()
+            .filter(|_| speed > 0.0)
+            .map(|time| time / speed)
+    }
+
+    #[test]
+    fn tools_speed_up_mining() {
+        let stone = time_to_mine(None, BlockKind::Stone).unwrap();
+        // Bare hands are slower than any tool
+        assert!(stone > BlockKind::Stone.mine_time().unwrap());
+        assert!(time_to_mine(Some(Tool::Sword), BlockKind::Stone).unwrap() < stone);
+        assert!(
+            time_to_mine(Some(Tool::Hammer), BlockKind::Stone)
+                < time_to_mine(Some(Tool::Axe), BlockKind::Stone)
+        );
+        assert!(
+            time_to_mine(Some(Tool::Axe), BlockKind::Wood)
+                < time_to_mine(Some(Tool::Hammer), BlockKind::Wood)
+        );
+    }
+
+    #[test]
+    fn ore_needs_a_tool() {
+        assert_eq!(time_to_mine(None, BlockKind::Iron), None);
+        assert!(time_to_mine(Some(Tool::Hammer), BlockKind::Iron).is_some());
+        assert_eq!(time_to_mine(Some(Tool::Hammer), BlockKind::Dense), None);
+    }
+}
@@ -3,6 +3,7 @@ pub mod animation;
 mod cleanup;
 pub mod combat;
 pub mod controller;
+mod mining;
 pub mod movement;
 pub mod phys;
 mod stats;
@@ -16,6 +17,7 @@ const CONTROLLER_SYS: &str = "controller_sys";
 const PHYS_SYS: &str = "phys_sys";
 const MOVEMENT_SYS: &str = "movement_sys";
 const COMBAT_SYS: &str = "combat_sys";
+const MINING_SYS: &str = "mining_sys";
 const ANIMATION_SYS: &str = "animation_sys";
 const STATS_SYS: &str = "stats_sys";
 const CLEANUP_SYS: &str = "cleanup_sys";
@@ -26,6 +28,7 @@ pub fn add_local_systems(dispatch_builder: &mut DispatcherBuilder) {
     dispatch_builder.add(phys::Sys, PHYS_SYS, &[CONTROLLER_SYS]);
     dispatch_builder.add(movement::Sys, MOVEMENT_SYS, &[PHYS_SYS]);
     dispatch_builder.add(combat::Sys, COMBAT_SYS, &[CONTROLLER_SYS]);
+    dispatch_builder.add(mining::Sys, MINING_SYS, &[CONTROLLER_SYS]);
     dispatch_builder.add(animation::Sys, ANIMATION_SYS, &[MOVEMENT_SYS]);
     dispatch_builder.add(stats::Sys, STATS_SYS, &[COMBAT_SYS]);
     dispatch_builder.add(cleanup::Sys, CLEANUP_SYS, &[STATS_SYS, ANIMATION_SYS]);
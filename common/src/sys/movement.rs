@@ -24,6 +24,10 @@ const GLIDE_ACCEL: f32 = 15.0;
 const GLIDE_SPEED: f32 = 45.0;
 // Gravity is 9.81 * 4, so this makes gravity equal to .15
 const GLIDE_ANTIGRAV: f32 = 9.81 * 3.95;
+const SWIM_ACCEL: f32 = 20.0;
+const SWIM_SPEED: f32 = 40.0;
+const SWIM_UP_ACCEL: f32 = 15.0;
+const SWIM_UP_SPEED: f32 = 5.0;
 
 pub const MOVEMENT_THRESHOLD_VEL: f32 = 3.0;
 
@@ -98,6 +102,9 @@ impl<'a> System<'a> for Sys {
                         {
                             HUMANOID_AIR_ACCEL
                         }
+                        (false, Swim) if vel.0.magnitude_squared() < SWIM_SPEED.powf(2.0) => {
+                            SWIM_ACCEL
+                        }
                         _ => 0.0,
                     };
             }
@@ -119,7 +126,11 @@ impl<'a> System<'a> for Sys {
                 ori.0 = vek::ops::Slerp::slerp(
                     ori.0,
                     ori_dir.into(),
-                    if physics.on_ground { 12.0 } else { 2.0 } * dt.0,
+                    if physics.on_ground || physics.in_fluid {
+                        12.0
+                    } else {
+                        2.0
+                    } * dt.0,
                 );
             }
 
@@ -133,6 +144,19 @@ impl<'a> System<'a> for Sys {
                 vel.0.z += dt.0 * lift * Vec2::<f32>::from(vel.0 * 0.15).magnitude().min(1.0);
             }
 
+            // Swim
+            if character.movement == Swim {
+                character.action = Idle;
+                // Dive or surface in the direction the character is looking
+                if vel.0.magnitude_squared() < SWIM_SPEED.powf(2.0) {
+                    vel.0.z +=
+                        dt.0 * SWIM_ACCEL * controller.look_dir.z * controller.move_dir.magnitude();
+                }
+                if controller.jump {
+                    vel.0.z = (vel.0.z + dt.0 * SWIM_UP_ACCEL).min(SWIM_UP_SPEED.max(vel.0.z));
+                }
+            }
+
             // Roll
             if let Roll { time_left } = &mut character.movement {
                 character.action = Idle;
@@ -145,10 +169,20 @@ impl<'a> System<'a> for Sys {
                 }
             }
 
-            if physics.on_ground && (character.movement == Jump || character.movement == Glide) {
+            if physics.on_ground
+                && (character.movement == Jump
+                    || character.movement == Glide
+                    || character.movement == Swim)
+            {
                 character.movement = Stand;
             }
 
+            if !physics.on_ground && physics.in_fluid && character.movement != Swim {
+                character.movement = Swim;
+            } else if !physics.in_fluid && character.movement == Swim {
+                character.movement = Jump;
+            }
+
             if !physics.on_ground
                 && (character.movement == Stand
                     || character.movement.is_roll()
@@ -1,18 +1,27 @@
 use {
     crate::{
-        comp::{Body, MovementState::*, Ori, PhysicsState, Pos, Scale, Stats, Vel},
+        comp::{
+            Body, MovementState::*, NoEntityCollision, Ori, PhysicsState, Pos, Scale, Stats, Vel,
+        },
         event::{EventBus, LocalEvent},
         state::DeltaTime,
         terrain::TerrainMap,
         vol::{ReadVol, Vox},
     },
-    specs::{Entities, Join, Read, ReadExpect, ReadStorage, System, WriteStorage},
+    hashbrown::{HashMap, HashSet},
+    specs::{Entities, Entity, Join, Read, ReadExpect, ReadStorage, System, WriteStorage},
     vek::*,
 };
 
 const GRAVITY: f32 = 9.81 * 4.0;
 const FRIC_GROUND: f32 = 0.15;
 const FRIC_AIR: f32 = 0.015;
+const FRIC_FLUID: f32 = 0.08;
+// A fully submerged body is pushed upwards with this multiple of gravity, so bodies float with
+// roughly 1 / BUOYANCY of their height below the surface
+const BUOYANCY: f32 = 1.25;
+// Width of the grid columns used to find nearby bodies when colliding entities with each other
+const COLLISION_CELL_SIZE: f32 = 4.0;
 
 // Integrates forces, calculates the new velocity based off of the old velocity
 // dt = delta time
@@ -27,6 +36,36 @@ fn integrate_forces(dt: f32, mut lv: Vec3<f32>, grav: f32, damp: f32) -> Vec3<f3
     lv * linear_damp
 }
 
+// The collision box of an entity, used to resolve collisions between entities
+struct Collider {
+    entity: Entity,
+    pos: Vec3<f32>,
+    radius: f32,
+    height: f32,
+    mass: f32,
+}
+
+// Returns the fraction (0.0 to 1.0) of a body of the given height standing at `pos` that lies
+// below the surface of a fluid
+fn submersion(terrain: &TerrainMap, pos: Vec3<f32>, height: f32) -> f32 {
+    let feet = pos.map(|e| e.floor() as i32);
+    (0..=height.ceil() as i32)
+        .map(|k| feet + Vec3::unit_z() * k)
+        .take_while(|block_pos| {
+            terrain
+                .get(*block_pos)
+                .map(|vox| vox.is_fluid())
+                .unwrap_or(false)
+        })
+        .last()
+        .map(|surface| {
+            ((surface.z as f32 + 1.0 - pos.z) / height)
+                .max(0.0)
+                .min(1.0)
+        })
+        .unwrap_or(0.0)
+}
+
 /// This system applies forces and calculates new positions and velocities.
 pub struct Sys;
 impl<'a> System<'a> for Sys {
@@ -37,6 +76,7 @@ impl<'a> System<'a> for Sys {
         Read<'a, EventBus<LocalEvent>>,
         ReadStorage<'a, Scale>,
         ReadStorage<'a, Body>,
+        ReadStorage<'a, NoEntityCollision>,
         WriteStorage<'a, PhysicsState>,
         WriteStorage<'a, Pos>,
         WriteStorage<'a, Vel>,
@@ -52,6 +92,7 @@ impl<'a> System<'a> for Sys {
             event_bus,
             scales,
             bodies,
+            no_entity_collisions,
             mut physics_states,
             mut positions,
             mut velocities,
@@ -60,8 +101,91 @@ impl<'a> System<'a> for Sys {
     ) {
         let mut event_emitter = event_bus.emitter();
 
+        // Resolve collisions between entities. This is done before bodies are moved, so that the
+        // pushes are collided with the terrain along with the rest of each body's movement.
+        // Broad phase: bucket bodies into a grid of columns so that only nearby pairs are tested
+        let colliders = (
+            &entities,
+            &positions,
+            scales.maybe(),
+            &bodies,
+            !&no_entity_collisions,
+        )
+            .join()
+            .map(|(entity, pos, scale, body, _)| {
+                let scale = scale.map(|s| s.0).unwrap_or(1.0);
+                Collider {
+                    entity,
+                    pos: pos.0,
+                    radius: body.radius() * scale,
+                    height: body.height() * scale,
+                    mass: body.mass() * scale.powf(3.0),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut grid = HashMap::<Vec2<i32>, Vec<usize>>::new();
+        for (i, collider) in colliders.iter().enumerate() {
+            let min = (Vec2::from(collider.pos) - collider.radius)
+                .map(|e| (e / COLLISION_CELL_SIZE).floor() as i32);
+            let max = (Vec2::from(collider.pos) + collider.radius)
+                .map(|e| (e / COLLISION_CELL_SIZE).floor() as i32);
+            for x in min.x..=max.x {
+                for y in min.y..=max.y {
+                    grid.entry(Vec2::new(x, y)).or_default().push(i);
+                }
+            }
+        }
+
+        let mut pairs = HashSet::new();
+        for cell in grid.values() {
+            for (n, i) in cell.iter().enumerate() {
+                for j in &cell[n + 1..] {
+                    pairs.insert((*i.min(j), *i.max(j)));
+                }
+            }
+        }
+
+        // Narrow phase: separate overlapping pairs, moving the lighter body further
+        let mut displacements = vec![Vec3::zero(); colliders.len()];
+        let mut supported = vec![false; colliders.len()];
+        for (i, j) in pairs {
+            let (a, b) = (&colliders[i], &colliders[j]);
+
+            let diff = Vec2::<f32>::from(a.pos - b.pos);
+            let horizontal_overlap = a.radius + b.radius - diff.magnitude();
+            let vertical_overlap =
+                (a.pos.z + a.height).min(b.pos.z + b.height) - a.pos.z.max(b.pos.z);
+
+            if horizontal_overlap <= 0.0 || vertical_overlap <= 0.0 {
+                continue;
+            }
+
+            if vertical_overlap < horizontal_overlap {
+                // One body is resting on top of the other, so the upper one is lifted clear
+                let upper = if a.pos.z > b.pos.z { i } else { j };
+                displacements[upper].z = displacements[upper].z.max(vertical_overlap);
+                supported[upper] = true;
+            } else {
+                // The bodies are side by side, so they are pushed apart in proportion to mass
+                let dir = Vec3::from(diff.try_normalized().unwrap_or(Vec2::unit_x()));
+                let total_mass = (a.mass + b.mass).max(0.001);
+                displacements[i] += dir * horizontal_overlap * b.mass / total_mass;
+                displacements[j] -= dir * horizontal_overlap * a.mass / total_mass;
+            }
+        }
+
+        let pushes = colliders
+            .iter()
+            .zip(displacements)
+            .zip(supported)
+            .map(|((collider, displacement), supported)| {
+                (collider.entity, (displacement, supported))
+            })
+            .collect::<HashMap<_, _>>();
+
         // Apply movement inputs
-        for (entity, scale, b, mut pos, mut vel, mut ori) in (
+        for (entity, scale, body, mut pos, mut vel, mut ori) in (
             &entities,
             scales.maybe(),
             &bodies,
@@ -73,6 +197,22 @@ impl<'a> System<'a> for Sys {
         {
             let mut physics_state = physics_states.get(entity).cloned().unwrap_or_default();
             let scale = scale.map(|s| s.0).unwrap_or(1.0);
+            let (push, supported) = pushes
+                .get(&entity)
+                .copied()
+                .unwrap_or((Vec3::zero(), false));
+
+            // Basic collision with terrain
+            let player_rad = body.radius() * scale; // half-width of the body's AABB
+            let player_height = body.height() * scale;
+
+            // Probe distances
+            let hdist = player_rad.ceil() as i32;
+            let vdist = player_height.ceil() as i32;
+
+            // Determine how much of the body is submerged in fluid
+            let submersion = submersion(&terrain, pos.0, player_height);
+            physics_state.in_fluid = submersion > 0.0;
 
             // Integrate forces
             // Friction is assumed to be a constant dependent on location
@@ -80,17 +220,14 @@ impl<'a> System<'a> for Sys {
                 * if physics_state.on_ground {
                     FRIC_GROUND
                 } else {
-                    FRIC_AIR
+                    FRIC_AIR * (1.0 - submersion) + FRIC_FLUID * submersion
                 };
-            vel.0 = integrate_forces(dt.0, vel.0, GRAVITY, friction);
-
-            // Basic collision with terrain
-            let player_rad = 0.3 * scale; // half-width of the player's AABB
-            let player_height = 1.5 * scale;
-
-            // Probe distances
-            let hdist = player_rad.ceil() as i32;
-            let vdist = player_height.ceil() as i32;
+            let gravity = GRAVITY * (1.0 - BUOYANCY * submersion);
+            vel.0 = integrate_forces(dt.0, vel.0, gravity, friction);
+            // Bodies resting on top of others don't sink into them
+            if supported {
+                vel.0.z = vel.0.z.max(0.0);
+            }
             // Neighbouring blocks iterator
             let near_iter = (-hdist..=hdist)
                 .map(move |i| (-hdist..=hdist).map(move |j| (0..=vdist).map(move |k| (i, j, k))))
@@ -135,7 +272,7 @@ impl<'a> System<'a> for Sys {
                 .get_key(terrain.pos_key(pos.0.map(|e| e.floor() as i32)))
                 .is_some()
             {
-                vel.0 * dt.0
+                vel.0 * dt.0 + push
             } else {
                 Vec3::zero()
             };
@@ -257,7 +394,7 @@ impl<'a> System<'a> for Sys {
                 }
             }
 
-            if on_ground {
+            if on_ground || supported {
                 physics_state.on_ground = true;
             // If the space below us is free, then "snap" to the ground
             } else if collision_with(pos.0 - Vec3::unit_z() * 1.05, near_iter.clone())
@@ -271,27 +408,222 @@ impl<'a> System<'a> for Sys {
 
             let _ = physics_states.insert(entity, physics_state);
         }
+    }
+}
 
-        // Apply pushback
-        for (pos, scale, mut vel, _) in
-            (&positions, scales.maybe(), &mut velocities, &bodies).join()
-        {
-            let scale = scale.map(|s| s.0).unwrap_or(1.0);
-            for (pos_other, scale_other, _) in (&positions, scales.maybe(), &bodies).join() {
-                let scale_other = scale_other.map(|s| s.0).unwrap_or(1.0);
-                let diff = Vec2::<f32>::from(pos.0 - pos_other.0);
-
-                let collision_dist = 0.95 * (scale + scale_other);
-
-                if diff.magnitude_squared() > 0.0
-                    && diff.magnitude_squared() < collision_dist.powf(2.0)
-                    && pos.0.z + 1.6 * scale > pos_other.0.z
-                    && pos.0.z < pos_other.0.z + 1.6 * scale_other
-                {
-                    vel.0 +=
-                        Vec3::from(diff.normalized()) * (collision_dist - diff.magnitude()) * 1.0;
+#[cfg(test)]
+mod tests {
+    use crate::{
+        comp,
+        state::State,
+        terrain::{Block, BlockKind, TerrainChunk, TerrainChunkMeta},
+        vol::WriteVol,
+    };
+    use specs::{Builder, Entity};
+    use std::time::Duration;
+    use vek::*;
+
+    const TICK: Duration = Duration::from_millis(33);
+
+    // Creates a state with a single chunk of solid ground below `floor`, filled with water up to
+    // `surface` and air above that
+    fn water_state(floor: i32, surface: i32) -> State {
+        let mut state = State::default();
+        let mut chunk = TerrainChunk::new(
+            floor,
+            Block::new(BlockKind::Dense, Rgb::new(128, 128, 128)),
+            Block::empty(),
+            TerrainChunkMeta::void(),
+        );
+        for x in 0..32 {
+            for y in 0..32 {
+                for z in floor..surface {
+                    chunk
+                        .set(
+                            Vec3::new(x, y, z),
+                            Block::new(BlockKind::Water, Rgb::new(100, 150, 255)),
+                        )
+                        .unwrap();
                 }
             }
         }
+        state.insert_chunk(Vec2::zero(), chunk);
+        state
+    }
+
+    // A human, so that collision dimensions are the same every run
+    fn human() -> comp::Body {
+        let mut body = comp::humanoid::Body::random();
+        body.race = comp::humanoid::Race::Human;
+        body.body_type = comp::humanoid::BodyType::Male;
+        comp::Body::Humanoid(body)
+    }
+
+    fn create_body(state: &mut State, pos: Vec3<f32>) -> Entity {
+        state
+            .ecs_mut()
+            .create_entity_synced()
+            .with(comp::Pos(pos))
+            .with(comp::Vel(Vec3::zero()))
+            .with(comp::Ori(Vec3::unit_y()))
+            .with(human())
+            .build()
+    }
+
+    fn pos_of(state: &State, entity: Entity) -> Vec3<f32> {
+        state.read_component_cloned::<comp::Pos>(entity).unwrap().0
+    }
+
+    fn run_for(state: &mut State, secs: f32) {
+        for _ in 0..(secs / TICK.as_secs_f32()) as usize {
+            state.tick(TICK);
+            state.cleanup();
+        }
+    }
+
+    #[test]
+    fn rests_on_ground_without_water() {
+        let mut state = water_state(0, 0);
+        let entity = create_body(&mut state, Vec3::new(16.5, 16.5, 10.0));
+
+        run_for(&mut state, 3.0);
+
+        let pos = state.read_component_cloned::<comp::Pos>(entity).unwrap();
+        let physics = state
+            .read_component_cloned::<comp::PhysicsState>(entity)
+            .unwrap();
+        assert!(physics.on_ground);
+        assert!(!physics.in_fluid);
+        assert!(pos.0.z.abs() < 0.1);
+    }
+
+    #[test]
+    fn floats_at_surface() {
+        let mut state = water_state(0, 20);
+        let entity = create_body(&mut state, Vec3::new(16.5, 16.5, 15.0));
+
+        run_for(&mut state, 8.0);
+
+        let pos = state.read_component_cloned::<comp::Pos>(entity).unwrap();
+        let physics = state
+            .read_component_cloned::<comp::PhysicsState>(entity)
+            .unwrap();
+        assert!(physics.in_fluid);
+        assert!(!physics.on_ground);
+        // Feet below the surface, head above it
+        assert!(pos.0.z < 20.0 && pos.0.z + human().height() > 20.0);
+    }
+
+    #[test]
+    fn drag_slows_falls_into_water() {
+        let mut state = water_state(0, 40);
+        let entity = create_body(&mut state, Vec3::new(16.5, 16.5, 45.0));
+        state.write_component(entity, comp::Vel(Vec3::new(0.0, 0.0, -40.0)));
+
+        run_for(&mut state, 1.0);
+
+        let vel = state.read_component_cloned::<comp::Vel>(entity).unwrap();
+        let physics = state
+            .read_component_cloned::<comp::PhysicsState>(entity)
+            .unwrap();
+        assert!(physics.in_fluid);
+        assert!(vel.0.z > -10.0);
+    }
+
+    #[test]
+    fn characters_swim_in_water() {
+        let mut state = water_state(0, 20);
+        let entity = create_body(&mut state, Vec3::new(16.5, 16.5, 18.0));
+        state.write_component(entity, comp::Stats::new("Swimmer".to_owned()));
+        state.write_component(entity, comp::Controller::default());
+        state.write_component(entity, comp::CharacterState::default());
+
+        run_for(&mut state, 1.0);
+
+        let character = state
+            .read_component_cloned::<comp::CharacterState>(entity)
+            .unwrap();
+        assert_eq!(character.movement, comp::MovementState::Swim);
+    }
+
+    #[test]
+    fn bodies_stack_on_each_other() {
+        let mut state = water_state(0, 0);
+        let lower = create_body(&mut state, Vec3::new(16.5, 16.5, 0.0));
+        let upper = create_body(&mut state, Vec3::new(16.5, 16.5, 3.0));
+
+        run_for(&mut state, 2.0);
+
+        let physics = state
+            .read_component_cloned::<comp::PhysicsState>(upper)
+            .unwrap();
+        assert!(physics.on_ground);
+        assert!(pos_of(&state, lower).z.abs() < 0.1);
+        assert!((pos_of(&state, upper).z - human().height()).abs() < 0.25);
+    }
+
+    #[test]
+    fn heavier_bodies_push_lighter_ones() {
+        let mut state = water_state(0, 0);
+        let light = create_body(&mut state, Vec3::new(16.0, 16.5, 0.0));
+        let heavy = create_body(&mut state, Vec3::new(16.4, 16.5, 0.0));
+        state.write_component(heavy, comp::Scale(2.0));
+
+        state.tick(TICK);
+
+        let light_moved = (pos_of(&state, light) - Vec3::new(16.0, 16.5, 0.0)).magnitude();
+        let heavy_moved = (pos_of(&state, heavy) - Vec3::new(16.4, 16.5, 0.0)).magnitude();
+        assert!(light_moved > heavy_moved * 4.0);
+        // Pushed apart along the line between them
+        assert!(pos_of(&state, light).x < 16.0);
+        assert!(pos_of(&state, heavy).x > 16.4);
+    }
+
+    #[test]
+    fn bodies_separate_after_pushing() {
+        let mut state = water_state(0, 0);
+        let a = create_body(&mut state, Vec3::new(16.0, 16.5, 0.0));
+        let b = create_body(&mut state, Vec3::new(16.2, 16.5, 0.0));
+
+        run_for(&mut state, 0.5);
+
+        let dist = Vec2::<f32>::from(pos_of(&state, a) - pos_of(&state, b)).magnitude();
+        assert!(dist >= human().radius() * 2.0 - 0.01);
+    }
+
+    #[test]
+    fn bodies_are_not_pushed_into_walls() {
+        let stone = Block::new(BlockKind::Dense, Rgb::new(128, 128, 128));
+        let mut state = State::default();
+        let mut chunk = TerrainChunk::new(0, stone, Block::empty(), TerrainChunkMeta::void());
+        // A wall along x = 16, too high to climb
+        for y in 0..32 {
+            for z in 0..8 {
+                chunk.set(Vec3::new(16, y, z), stone).unwrap();
+            }
+        }
+        state.insert_chunk(Vec2::zero(), chunk);
+        // The body nearest the wall is pushed towards it
+        let a = create_body(&mut state, Vec3::new(15.6, 16.5, 0.0));
+        let b = create_body(&mut state, Vec3::new(15.3, 16.5, 0.0));
+
+        run_for(&mut state, 1.0);
+
+        assert!(pos_of(&state, a).x + human().radius() <= 16.01);
+        let dist = Vec2::<f32>::from(pos_of(&state, a) - pos_of(&state, b)).magnitude();
+        assert!(dist >= human().radius() * 2.0 - 0.01);
+    }
+
+    #[test]
+    fn opted_out_bodies_pass_through() {
+        let mut state = water_state(0, 0);
+        let a = create_body(&mut state, Vec3::new(16.0, 16.5, 0.0));
+        let b = create_body(&mut state, Vec3::new(16.2, 16.5, 0.0));
+        state.write_component(b, comp::NoEntityCollision);
+
+        state.tick(TICK);
+
+        assert!((pos_of(&state, a).x - 16.0).abs() < 0.01);
+        assert!((pos_of(&state, b).x - 16.2).abs() < 0.01);
     }
 }
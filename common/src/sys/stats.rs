@@ -1,5 +1,5 @@
 use crate::{
-    comp::{HealthSource, Stats},
+    comp::{EnergyConfig, EnergySource, HealthSource, Stats},
     event::{EventBus, ServerEvent},
     state::DeltaTime,
 };
@@ -12,11 +12,12 @@ impl<'a> System<'a> for Sys {
     type SystemData = (
         Entities<'a>,
         Read<'a, DeltaTime>,
+        Read<'a, EnergyConfig>,
         Read<'a, EventBus<ServerEvent>>,
         WriteStorage<'a, Stats>,
     );
 
-    fn run(&mut self, (entities, dt, event_bus, mut stats): Self::SystemData) {
+    fn run(&mut self, (entities, dt, energy_config, event_bus, mut stats): Self::SystemData) {
         let mut event_emitter = event_bus.emitter();
 
         for (entity, mut stat) in (&entities, &mut stats).join() {
@@ -39,6 +40,21 @@ impl<'a> System<'a> for Sys {
                 change.1 += f64::from(dt.0);
             }
 
+            if let Some(change) = &mut stat.energy.last_change {
+                change.1 += f64::from(dt.0);
+            }
+
+            // Regenerate energy once the entity has stopped spending it for a while
+            let recently_spent = stat
+                .energy
+                .last_change
+                .map(|(amount, time, _)| amount < 0 && time < energy_config.regen_delay)
+                .unwrap_or(false);
+            if !stat.is_dead && !recently_spent && stat.energy.current() < stat.energy.maximum() {
+                stat.energy
+                    .change_by_partial(energy_config.regen_rate * dt.0, EnergySource::Regenerate);
+            }
+
             if stat.exp.current() >= stat.exp.maximum() {
                 stat.exp.change_by(-(stat.exp.maximum() as i64));
                 stat.exp.change_maximum_by(25);
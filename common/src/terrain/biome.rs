@@ -1,13 +1,58 @@
 use serde_derive::{Deserialize, Serialize};
 
-#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum BiomeKind {
     Void,
-    Grassland,
     Ocean,
-    Mountain,
-    Snowlands,
+    Lake,
+    /// Low land bordering the ocean.
+    Beach,
+    Grassland,
+    /// Hot, dry grassland with scattered trees.
+    Savannah,
     Desert,
+    /// Hot and very humid.
+    Jungle,
+    /// Warm, very humid land close to sea level.
+    Mangrove,
+    /// Flat, wet land close to sea level.
     Swamp,
     Forest,
+    /// Cold forest.
+    Taiga,
+    Snowlands,
+    /// Cold and too dry for snow or forests.
+    Tundra,
+    Mountain,
+}
+
+impl BiomeKind {
+    /// Whether the biome is mostly covered by water.
+    pub fn is_water(&self) -> bool {
+        match self {
+            BiomeKind::Ocean | BiomeKind::Lake => true,
+            _ => false,
+        }
+    }
+
+    /// The typical air temperature of the biome, in degrees Celsius.
+    pub fn temperature(&self) -> f32 {
+        match self {
+            BiomeKind::Void => 0.0,
+            BiomeKind::Ocean => 12.0,
+            BiomeKind::Lake => 14.0,
+            BiomeKind::Beach => 22.0,
+            BiomeKind::Grassland => 16.0,
+            BiomeKind::Savannah => 28.0,
+            BiomeKind::Desert => 38.0,
+            BiomeKind::Jungle => 30.0,
+            BiomeKind::Mangrove => 27.0,
+            BiomeKind::Swamp => 20.0,
+            BiomeKind::Forest => 14.0,
+            BiomeKind::Taiga => 2.0,
+            BiomeKind::Snowlands => -12.0,
+            BiomeKind::Tundra => -5.0,
+            BiomeKind::Mountain => 4.0,
+        }
+    }
 }
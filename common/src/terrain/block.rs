@@ -1,4 +1,4 @@
-use crate::vol::Vox;
+use crate::{comp::item::Ingredient, vol::Vox};
 use serde_derive::{Deserialize, Serialize};
 use std::ops::Deref;
 use vek::*;
@@ -23,6 +23,15 @@ pub enum BlockKind {
     MediumGrass,
     ShortGrass,
     Apple,
+    Wood,
+    Granite,
+    Sandstone,
+    Coal,
+    Copper,
+    Iron,
+    Gold,
+    /// Natural stone. Unlike `Dense` blocks, which make up buildings and dungeons, it can be mined.
+    Stone,
 }
 
 impl BlockKind {
@@ -94,6 +103,44 @@ impl BlockKind {
             _ => true,
         }
     }
+
+    /// Seconds it takes to mine the block with a tool that has no advantage against it, or `None`
+    /// if the block can't be mined.
+    pub fn mine_time(&self) -> Option<f32> {
+        match self {
+            BlockKind::Wood => Some(2.0),
+            BlockKind::Sandstone => Some(2.0),
+            BlockKind::Stone => Some(3.0),
+            BlockKind::Granite => Some(4.0),
+            BlockKind::Coal => Some(3.0),
+            BlockKind::Copper => Some(4.0),
+            BlockKind::Iron => Some(5.0),
+            BlockKind::Gold => Some(6.0),
+            _ => None,
+        }
+    }
+
+    /// The ingredient collected by mining the block.
+    pub fn mined_ingredient(&self) -> Option<Ingredient> {
+        match self {
+            BlockKind::Wood => Some(Ingredient::Wood),
+            BlockKind::Sandstone => Some(Ingredient::Sandstone),
+            BlockKind::Stone => Some(Ingredient::Stone),
+            BlockKind::Granite => Some(Ingredient::Granite),
+            BlockKind::Coal => Some(Ingredient::Coal),
+            BlockKind::Copper => Some(Ingredient::CopperOre),
+            BlockKind::Iron => Some(Ingredient::IronOre),
+            BlockKind::Gold => Some(Ingredient::GoldOre),
+            _ => None,
+        }
+    }
+
+    pub fn is_ore(&self) -> bool {
+        match self {
+            BlockKind::Coal | BlockKind::Copper | BlockKind::Iron | BlockKind::Gold => true,
+            _ => false,
+        }
+    }
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -154,4 +201,45 @@ mod tests {
         assert_eq!(std::mem::size_of::<BlockKind>(), 1);
         assert_eq!(std::mem::size_of::<Block>(), 4);
     }
+
+    #[test]
+    fn mined_blocks_yield_ingredients() {
+        let kinds = [
+            BlockKind::Air,
+            BlockKind::Normal,
+            BlockKind::Dense,
+            BlockKind::Water,
+            BlockKind::LongGrass,
+            BlockKind::Apple,
+            BlockKind::Wood,
+            BlockKind::Granite,
+            BlockKind::Sandstone,
+            BlockKind::Coal,
+            BlockKind::Copper,
+            BlockKind::Iron,
+            BlockKind::Gold,
+            BlockKind::Stone,
+        ];
+        for kind in kinds.iter() {
+            assert_eq!(
+                kind.mine_time().is_some(),
+                kind.mined_ingredient().is_some(),
+                "{:?}",
+                kind
+            );
+            if let Some(time) = kind.mine_time() {
+                assert!(time > 0.0 && kind.is_solid(), "{:?}", kind);
+            }
+        }
+
+        assert_eq!(BlockKind::Stone.mined_ingredient(), Some(Ingredient::Stone));
+        assert_eq!(
+            BlockKind::Iron.mined_ingredient(),
+            Some(Ingredient::IronOre)
+        );
+        // Buildings and dungeons can't be dug through
+        assert_eq!(BlockKind::Dense.mine_time(), None);
+        // Deeper ore takes longer to mine
+        assert!(BlockKind::Gold.mine_time() > BlockKind::Coal.mine_time());
+    }
 }
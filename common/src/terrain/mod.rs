@@ -32,17 +32,23 @@ impl VolSize for TerrainChunkSize {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TerrainChunkMeta {
     name: Option<String>,
+    settlement: Option<String>,
     biome: BiomeKind,
 }
 
 impl TerrainChunkMeta {
-    pub fn new(name: Option<String>, biome: BiomeKind) -> Self {
-        Self { name, biome }
+    pub fn new(name: Option<String>, settlement: Option<String>, biome: BiomeKind) -> Self {
+        Self {
+            name,
+            settlement,
+            biome,
+        }
     }
 
     pub fn void() -> Self {
         Self {
             name: None,
+            settlement: None,
             biome: BiomeKind::Void,
         }
     }
@@ -54,6 +60,11 @@ impl TerrainChunkMeta {
             .unwrap_or("Wilderness")
     }
 
+    /// The name of the settlement this chunk is part of, if any.
+    pub fn settlement(&self) -> Option<&str> {
+        self.settlement.as_ref().map(|s| s.as_str())
+    }
+
     pub fn biome(&self) -> BiomeKind {
         self.biome
     }
@@ -53,6 +53,14 @@ impl Structure {
         self
     }
 
+    /// Centre the structure on the middle of its footprint, with the bottom `floor` layers sunk
+    /// below the ground it is placed on.
+    pub fn with_footprint_center(mut self, floor: i32) -> Self {
+        let size = self.vol.get_size().map(|e| e as i32);
+        self.center = Vec3::new(size.x / 2, size.y / 2, floor);
+        self
+    }
+
     pub fn with_default_kind(mut self, kind: BlockKind) -> Self {
         self.default_kind = kind;
         self
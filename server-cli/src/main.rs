@@ -2,9 +2,16 @@
 
 use common::clock::Clock;
 use heaptrack::track_mem;
-use log::info;
-use server::{Event, Input, Server, ServerSettings};
-use std::time::Duration;
+use log::{info, warn};
+use server::{ConsoleCommand, Event, Input, Server, ServerSettings, DEFAULT_SHUTDOWN_GRACE};
+use std::{
+    env,
+    io::{self, BufRead},
+    path::PathBuf,
+    sync::mpsc,
+    thread,
+    time::Duration,
+};
 
 track_mem!();
 
@@ -19,15 +26,43 @@ fn main() {
     // Set up an fps clock
     let mut clock = Clock::start();
 
-    // Load settings
-    let settings = ServerSettings::load();
+    // Load settings, from the path given as the first argument if there is one.
+    let settings_path = env::args_os()
+        .nth(1)
+        .map(PathBuf::from)
+        .unwrap_or_else(ServerSettings::default_path);
+    info!("Using settings from {}", settings_path.display());
+    let settings = ServerSettings::load(&settings_path);
 
     // Create server
     let mut server = Server::new(settings).expect("Failed to create server instance!");
+    server.watch_settings(settings_path);
 
-    loop {
+    // Read console commands on another thread so that waiting for input doesn't block ticks.
+    let console = spawn_console();
+    info!("Type 'help' for a list of console commands.");
+
+    // Stop gracefully on SIGINT or SIGTERM. A second signal skips the rest of the countdown.
+    let signals = spawn_signal_handler();
+
+    'running: loop {
+        for () in signals.try_iter() {
+            if server.is_shutting_down() {
+                server.shutdown(Duration::from_secs(0), "The server was stopped".to_owned());
+            } else {
+                info!("Stopping, send the signal again to stop immediately.");
+                server.shutdown(
+                    DEFAULT_SHUTDOWN_GRACE,
+                    "The server is shutting down".to_owned(),
+                );
+            }
+        }
+
+        let input = Input {
+            console_commands: console.try_iter().collect(),
+        };
         let events = server
-            .tick(Input::default(), clock.get_last_delta())
+            .tick(input, clock.get_last_delta())
             .expect("Failed to tick server");
 
         for event in events {
@@ -35,6 +70,8 @@ fn main() {
                 Event::ClientConnected { entity: _ } => info!("Client connected!"),
                 Event::ClientDisconnected { entity: _ } => info!("Client disconnected!"),
                 Event::Chat { entity: _, msg } => info!("[Client] {}", msg),
+                Event::ConsoleOutput { msg } => println!("{}", msg),
+                Event::Shutdown => break 'running,
             }
         }
 
@@ -44,4 +81,49 @@ fn main() {
         // Wait for the next tick.
         clock.tick(Duration::from_millis(1000 / TPS));
     }
+
+    info!("Shutting down server-cli...");
+}
+
+fn spawn_signal_handler() -> mpsc::Receiver<()> {
+    let (tx, rx) = mpsc::channel();
+
+    if let Err(err) = ctrlc::set_handler(move || {
+        let _ = tx.send(());
+    }) {
+        warn!("Failed to set the signal handler: {}", err);
+    }
+
+    rx
+}
+
+fn spawn_console() -> mpsc::Receiver<ConsoleCommand> {
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        let stdin = io::stdin();
+        for line in stdin.lock().lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(err) => {
+                    warn!("Failed to read from the console: {}", err);
+                    break;
+                }
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            match ConsoleCommand::parse(&line) {
+                Ok(command) => {
+                    if tx.send(command).is_err() {
+                        break;
+                    }
+                }
+                Err(err) => println!("{}", err),
+            }
+        }
+    });
+
+    rx
 }
@@ -12,6 +12,11 @@ impl AuthProvider {
         }
     }
 
+    /// Whether anybody registered with `username` since the server started.
+    pub fn exists(&self, username: &str) -> bool {
+        self.accounts.contains_key(username)
+    }
+
     pub fn query(&mut self, username: String, password: String) -> bool {
         let pwd = password.clone();
         if self.accounts.entry(username.clone()).or_insert_with(|| {
@@ -0,0 +1,264 @@
+//! Bans and mutes, which are kept in their own RON file so that they survive restarts.
+
+use chrono::{NaiveDateTime, Utc};
+use serde_derive::{Deserialize, Serialize};
+use std::{fmt, fs, io::prelude::*, net::IpAddr, path::Path, time::Duration};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BanTarget {
+    Account(String),
+    Ip(IpAddr),
+}
+
+impl fmt::Display for BanTarget {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BanTarget::Account(alias) => write!(f, "{}", alias),
+            BanTarget::Ip(ip) => write!(f, "{}", ip),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Ban {
+    pub target: BanTarget,
+    pub reason: String,
+    /// Unix time, in seconds, at which the ban ends. Permanent bans have none.
+    pub until: Option<i64>,
+}
+
+impl Ban {
+    pub fn is_active(&self) -> bool {
+        is_active(self.until)
+    }
+
+    /// The message shown to the banned player.
+    pub fn message(&self) -> String {
+        format!(
+            "You are banned {}: {}",
+            describe_until(self.until),
+            self.reason
+        )
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Mute {
+    pub alias: String,
+    pub reason: String,
+    /// Unix time, in seconds, at which the mute ends. Permanent mutes have none.
+    pub until: Option<i64>,
+}
+
+impl Mute {
+    pub fn is_active(&self) -> bool {
+        is_active(self.until)
+    }
+
+    /// The message shown to the muted player when they try to chat.
+    pub fn message(&self) -> String {
+        format!(
+            "You are muted {}: {}",
+            describe_until(self.until),
+            self.reason
+        )
+    }
+}
+
+fn until(duration: Option<Duration>) -> Option<i64> {
+    duration.map(|duration| Utc::now().timestamp() + duration.as_secs() as i64)
+}
+
+fn is_active(until: Option<i64>) -> bool {
+    until.map_or(true, |until| until > Utc::now().timestamp())
+}
+
+/// Describe when a ban or mute ends, e.g. "until 2019-08-01 12:00 UTC".
+pub fn describe_until(until: Option<i64>) -> String {
+    match until {
+        Some(until) => format!(
+            "until {}",
+            NaiveDateTime::from_timestamp(until, 0).format("%Y-%m-%d %H:%M UTC")
+        ),
+        None => "permanently".to_owned(),
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BanList {
+    pub bans: Vec<Ban>,
+    pub mutes: Vec<Mute>,
+}
+
+impl BanList {
+    pub fn load(path: &Path) -> Self {
+        match fs::File::open(path) {
+            Ok(file) => match ron::de::from_reader(file) {
+                Ok(banlist) => banlist,
+                Err(e) => {
+                    log::warn!("Failed to parse the ban list! Starting with no bans. {}", e);
+                    Self::default()
+                }
+            },
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let mut file = fs::File::create(path)?;
+
+        let s: &str = &ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()).unwrap();
+        file.write_all(s.as_bytes())
+    }
+
+    /// Find an active ban on the given account or address.
+    pub fn find_ban(&self, alias: Option<&str>, ip: Option<IpAddr>) -> Option<&Ban> {
+        self.bans
+            .iter()
+            .filter(|ban| ban.is_active())
+            .find(|ban| match &ban.target {
+                BanTarget::Account(banned) => Some(banned.as_str()) == alias,
+                BanTarget::Ip(banned) => Some(*banned) == ip,
+            })
+    }
+
+    /// Ban a target, replacing any earlier ban on it. Bans without a duration are permanent.
+    pub fn ban(&mut self, target: BanTarget, reason: String, duration: Option<Duration>) -> &Ban {
+        self.bans.retain(|ban| ban.target != target);
+        self.bans.push(Ban {
+            target,
+            reason,
+            until: until(duration),
+        });
+        self.bans.last().unwrap()
+    }
+
+    /// Lift the ban on a target, returning whether it was banned.
+    pub fn unban(&mut self, target: &BanTarget) -> bool {
+        let len = self.bans.len();
+        self.bans.retain(|ban| &ban.target != target);
+        self.bans.len() != len
+    }
+
+    pub fn find_mute(&self, alias: &str) -> Option<&Mute> {
+        self.mutes
+            .iter()
+            .find(|mute| mute.alias == alias && mute.is_active())
+    }
+
+    /// Mute an account, replacing any earlier mute. Mutes without a duration are permanent.
+    pub fn mute(&mut self, alias: String, reason: String, duration: Option<Duration>) -> &Mute {
+        self.mutes.retain(|mute| mute.alias != alias);
+        self.mutes.push(Mute {
+            alias,
+            reason,
+            until: until(duration),
+        });
+        self.mutes.last().unwrap()
+    }
+
+    /// Lift the mute on an account, returning whether it was muted.
+    pub fn unmute(&mut self, alias: &str) -> bool {
+        let len = self.mutes.len();
+        self.mutes.retain(|mute| mute.alias != alias);
+        self.mutes.len() != len
+    }
+
+    /// Forget bans and mutes that have run out.
+    pub fn remove_expired(&mut self) {
+        self.bans.retain(Ban::is_active);
+        self.mutes.retain(Mute::is_active);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ban_until(target: BanTarget, until: Option<i64>) -> Ban {
+        Ban {
+            target,
+            reason: "Testing".to_owned(),
+            until,
+        }
+    }
+
+    #[test]
+    fn bans_run_out() {
+        let now = Utc::now().timestamp();
+        let mut banlist = BanList::default();
+        banlist.bans = vec![
+            ban_until(BanTarget::Account("expired".to_owned()), Some(now - 10)),
+            ban_until(BanTarget::Account("temporary".to_owned()), Some(now + 3600)),
+            ban_until(BanTarget::Account("permanent".to_owned()), None),
+        ];
+        assert!(!banlist.bans[0].is_active());
+        assert!(banlist.bans[1].is_active());
+        assert!(banlist.bans[2].is_active());
+        assert!(banlist.find_ban(Some("expired"), None).is_none());
+
+        banlist.remove_expired();
+        let remaining = banlist
+            .bans
+            .iter()
+            .map(|ban| ban.target.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(remaining, vec!["temporary", "permanent"]);
+    }
+
+    #[test]
+    fn bans_match_account_or_address() {
+        let ip = "10.0.0.1".parse::<IpAddr>().unwrap();
+        let other_ip = "10.0.0.2".parse::<IpAddr>().unwrap();
+        let mut banlist = BanList::default();
+        banlist.ban(
+            BanTarget::Account("Griefer".to_owned()),
+            "Griefing".to_owned(),
+            None,
+        );
+        banlist.ban(BanTarget::Ip(ip), "Spamming".to_owned(), None);
+
+        let reason = |alias, ip| banlist.find_ban(alias, ip).map(|ban| ban.reason.as_str());
+        assert_eq!(reason(Some("Griefer"), Some(other_ip)), Some("Griefing"));
+        assert_eq!(reason(Some("Someone"), Some(ip)), Some("Spamming"));
+        // Before registering, only the address is known
+        assert_eq!(reason(None, Some(ip)), Some("Spamming"));
+        assert_eq!(reason(Some("Someone"), Some(other_ip)), None);
+        assert_eq!(reason(None, None), None);
+    }
+
+    #[test]
+    fn bans_replace_earlier_ones() {
+        let target = BanTarget::Account("Griefer".to_owned());
+        let mut banlist = BanList::default();
+        banlist.ban(target.clone(), "First".to_owned(), None);
+        banlist.ban(
+            target.clone(),
+            "Second".to_owned(),
+            Some(Duration::from_secs(60)),
+        );
+
+        assert_eq!(banlist.bans.len(), 1);
+        let ban = banlist.find_ban(Some("Griefer"), None).unwrap();
+        assert_eq!(ban.reason, "Second");
+        assert!(ban.until.is_some());
+
+        assert!(banlist.unban(&target));
+        assert!(!banlist.unban(&target));
+        assert!(banlist.find_ban(Some("Griefer"), None).is_none());
+    }
+
+    #[test]
+    fn mutes_replace_earlier_ones() {
+        let mut banlist = BanList::default();
+        banlist.mute("Loud".to_owned(), "First".to_owned(), None);
+        banlist.mute("Loud".to_owned(), "Second".to_owned(), None);
+
+        assert_eq!(banlist.mutes.len(), 1);
+        assert_eq!(banlist.find_mute("Loud").unwrap().reason, "Second");
+        assert!(banlist.find_mute("Quiet").is_none());
+        assert!(banlist.unmute("Loud"));
+        assert!(banlist.find_mute("Loud").is_none());
+    }
+}
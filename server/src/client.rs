@@ -4,17 +4,24 @@ use common::{
 };
 use hashbrown::HashMap;
 use specs::Entity as EcsEntity;
+use std::net::IpAddr;
 
 pub struct Client {
     pub client_state: ClientState,
     pub postbox: PostBox<ServerMsg, ClientMsg>,
     pub last_ping: f64,
+    /// The account the client registered with. Unlike the alias in `comp::Player`, it can't be
+    /// changed in game, so roles, bans, mutes and friends are looked up by it.
+    pub account: Option<String>,
 }
 
 impl Client {
     pub fn notify(&mut self, msg: ServerMsg) {
         self.postbox.send_message(msg);
     }
+    pub fn ip(&self) -> Option<IpAddr> {
+        self.postbox.peer_addr().map(|addr| addr.ip())
+    }
     pub fn allow_state(&mut self, new_state: ClientState) {
         self.client_state = new_state;
         self.postbox
@@ -57,6 +64,22 @@ impl Clients {
         self.clients.get_mut(entity)
     }
 
+    /// The client that registered with `account`, if it's online.
+    pub fn entity_with_account(&self, account: &str) -> Option<EcsEntity> {
+        self.clients
+            .iter()
+            .find(|(_, client)| client.account.as_ref().map(String::as_str) == Some(account))
+            .map(|(entity, _)| *entity)
+    }
+
+    pub fn entities_with_ip(&self, ip: IpAddr) -> Vec<EcsEntity> {
+        self.clients
+            .iter()
+            .filter(|(_, client)| client.ip() == Some(ip))
+            .map(|(entity, _)| *entity)
+            .collect()
+    }
+
     pub fn remove_if<F: FnMut(EcsEntity, &mut Client) -> bool>(&mut self, mut f: F) {
         self.clients.retain(|entity, client| !f(*entity, client));
     }
@@ -1,35 +1,184 @@
 //! # Implementing new commands.
 //! To implement a new command, add an instance of `ChatCommand` to `CHAT_COMMANDS`
-//! and provide a handler function.
+//! and provide a handler function. The arguments are described by `ArgumentSpec`s, which are
+//! used to check them before the handler runs, to write the help text and to complete them in
+//! the chat box.
 
-use crate::Server;
+use crate::{
+    banlist::{self, BanTarget},
+    faction_members, friends, group,
+    roles::Permission,
+    Server,
+};
 use chrono::{NaiveTime, Timelike};
 use common::{
     comp,
     event::{EventBus, ServerEvent},
-    msg::ServerMsg,
+    msg::{FriendRequest, ServerMsg},
     npc::{get_npc_name, NpcKind},
     state::TimeOfDay,
     terrain::TerrainChunkSize,
     vol::VolSize,
+    ChatType,
 };
 use rand::Rng;
 use specs::{Builder, Entity as EcsEntity, Join};
+use std::{net::IpAddr, ops::RangeInclusive, time::Duration};
 use vek::*;
 
 use lazy_static::lazy_static;
 use scan_fmt::{scan_fmt, scan_fmt_some};
 
+/// Enums with more choices than this show a label instead of their choices in usage strings.
+const MAX_LISTED_CHOICES: usize = 6;
+/// The integers that can be parsed as an `i32`.
+const I32_RANGE: RangeInclusive<i64> = (i32::min_value() as i64)..=(i32::max_value() as i64);
+
+/// Whether an argument can be left out.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Requirement {
+    Required,
+    Optional,
+}
+
+/// The kind of an argument a command takes, along with a label for the help text.
+pub enum ArgumentSpec {
+    /// The alias of a player. Completed with the players online, but not checked against them
+    /// since some commands take accounts that are offline.
+    PlayerName(Requirement),
+    /// A whole number in the given range, which should fit the type the handler parses it as.
+    Integer(&'static str, RangeInclusive<i64>, Requirement),
+    Float(&'static str, Requirement),
+    /// One of a fixed set of words.
+    Enum(&'static str, Vec<String>, Requirement),
+    /// Three numbers giving a position, or an offset, in blocks.
+    Position(Requirement),
+    /// A single word that isn't checked, such as a name or a duration.
+    Any(&'static str, Requirement),
+    /// The rest of the line, spaces included. Must be the last argument.
+    Message(&'static str, Requirement),
+}
+
+impl ArgumentSpec {
+    pub fn requirement(&self) -> Requirement {
+        match self {
+            ArgumentSpec::PlayerName(requirement) | ArgumentSpec::Position(requirement) => {
+                *requirement
+            }
+            ArgumentSpec::Integer(_, _, requirement)
+            | ArgumentSpec::Float(_, requirement)
+            | ArgumentSpec::Enum(_, _, requirement)
+            | ArgumentSpec::Any(_, requirement)
+            | ArgumentSpec::Message(_, requirement) => *requirement,
+        }
+    }
+
+    /// How the argument is shown in usage strings, e.g. `<player>` or `[amount]`.
+    pub fn usage(&self) -> String {
+        let name = match self {
+            ArgumentSpec::PlayerName(_) => "player".to_owned(),
+            ArgumentSpec::Position(_) => "x> <y> <z".to_owned(),
+            ArgumentSpec::Enum(label, choices, _) => {
+                if choices.len() <= MAX_LISTED_CHOICES {
+                    choices.join("|")
+                } else {
+                    label.to_string()
+                }
+            }
+            ArgumentSpec::Integer(label, _, _)
+            | ArgumentSpec::Float(label, _)
+            | ArgumentSpec::Any(label, _)
+            | ArgumentSpec::Message(label, _) => label.to_string(),
+        };
+        match self.requirement() {
+            Requirement::Required => format!("<{}>", name),
+            Requirement::Optional => format!("[<{}>]", name),
+        }
+    }
+
+    /// How many words the argument takes, not counting messages, which take all that are left.
+    fn words(&self) -> usize {
+        match self {
+            ArgumentSpec::Position(_) => 3,
+            _ => 1,
+        }
+    }
+
+    /// Check a word given for this argument, explaining what's wrong with it if it doesn't fit.
+    fn check(&self, word: &str, keyword: &str) -> Result<(), String> {
+        match self {
+            ArgumentSpec::Integer(label, range, _) => match word.parse::<i64>() {
+                Ok(n) if range.contains(&n) => Ok(()),
+                _ => Err(format!(
+                    "'{}' is not a whole number from {} to {}, expected <{}>.",
+                    word,
+                    range.start(),
+                    range.end(),
+                    label
+                )),
+            },
+            ArgumentSpec::Float(label, _) => word
+                .parse::<f32>()
+                .map(|_| ())
+                .map_err(|_| format!("'{}' is not a number, expected <{}>.", word, label)),
+            ArgumentSpec::Position(_) => word
+                .parse::<f32>()
+                .map(|_| ())
+                .map_err(|_| format!("'{}' is not a coordinate.", word)),
+            ArgumentSpec::Enum(label, choices, _) => {
+                if choices.iter().any(|choice| choice == word) {
+                    Ok(())
+                } else if choices.len() <= MAX_LISTED_CHOICES {
+                    Err(format!(
+                        "'{}' is not a valid {}, expected one of: {}.",
+                        word,
+                        label,
+                        choices.join(", ")
+                    ))
+                } else {
+                    Err(format!(
+                        "'{}' is not a valid {}. Type '/help {}' to list them.",
+                        word, label, keyword
+                    ))
+                }
+            }
+            ArgumentSpec::PlayerName(_) | ArgumentSpec::Any(..) | ArgumentSpec::Message(..) => {
+                Ok(())
+            }
+        }
+    }
+
+    /// The words that could be given for this argument, starting with `prefix`. `aliases` are
+    /// those of the players online.
+    fn completions(&self, aliases: &[String], prefix: &str) -> Vec<String> {
+        match self {
+            ArgumentSpec::PlayerName(_) => aliases
+                .iter()
+                .filter(|alias| alias.starts_with(prefix))
+                .cloned()
+                .collect(),
+            ArgumentSpec::Enum(_, choices, _) => choices
+                .iter()
+                .filter(|choice| choice.starts_with(prefix))
+                .cloned()
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
 /// Struct representing a command that a user can run from server chat.
 pub struct ChatCommand {
     /// The keyword used to invoke the command, omitting the leading '/'.
     pub keyword: &'static str,
-    /// A format string for parsing arguments.
-    arg_fmt: &'static str,
-    /// A message that explains how the command is used.
-    help_string: &'static str,
-    /// A boolean that is used to check whether the command requires administrator permissions or not.
-    needs_admin: bool,
+    /// The arguments the command takes, in order.
+    args: Vec<ArgumentSpec>,
+    /// A format string for parsing the arguments with `scan_fmt!`, made from `args`.
+    arg_fmt: String,
+    /// What the command does, shown after its usage in the help text.
+    description: &'static str,
+    /// The permission needed to run the command, if any.
+    permission: Option<Permission>,
     /// Handler function called when the command is executed.
     /// # Arguments
     /// * `&mut Server` - the `Server` instance executing the command.
@@ -37,6 +186,8 @@ pub struct ChatCommand {
     /// * `String` - a `String` containing the part of the command after the keyword.
     /// * `&ChatCommand` - the command to execute with the above arguments.
     /// Handler functions must parse arguments from the the given `String` (`scan_fmt!` is included for this purpose).
+    /// The arguments have already been checked against `args`, so parsing them with `arg_fmt`
+    /// only fails for optional arguments that were left out.
     handler: fn(&mut Server, EcsEntity, String, &ChatCommand),
 }
 
@@ -44,37 +195,177 @@ impl ChatCommand {
     /// Creates a new chat command.
     pub fn new(
         keyword: &'static str,
-        arg_fmt: &'static str,
-        help_string: &'static str,
-        needs_admin: bool,
+        args: Vec<ArgumentSpec>,
+        description: &'static str,
+        permission: Option<Permission>,
         handler: fn(&mut Server, EcsEntity, String, &ChatCommand),
     ) -> Self {
+        let arg_fmt = args
+            .iter()
+            .filter_map(|arg| match arg {
+                ArgumentSpec::Integer(..) => Some("{d}"),
+                ArgumentSpec::Position(_) => Some("{} {} {}"),
+                ArgumentSpec::Message(..) => None,
+                _ => Some("{}"),
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
         Self {
             keyword,
+            args,
             arg_fmt,
-            help_string,
-            needs_admin,
+            description,
+            permission,
             handler,
         }
     }
-    /// Calls the contained handler function, passing `&self` as the last argument.
-    pub fn execute(&self, server: &mut Server, entity: EcsEntity, args: String) {
-        if self.needs_admin {
-            if !server.entity_is_admin(entity) {
-                server.clients.notify(
-                    entity,
-                    ServerMsg::private(String::from(
-                        "Unavailable command '/{}'.\nType '/help' for available commands",
-                    )),
-                );
-                return;
-            } else {
-                (self.handler)(server, entity, args, self);
+
+    /// How the command is typed, e.g. `/tp <player>`.
+    pub fn usage(&self) -> String {
+        let mut usage = format!("/{}", self.keyword);
+        for arg in &self.args {
+            usage.push(' ');
+            usage.push_str(&arg.usage());
+        }
+        usage
+    }
+
+    /// The usage of the command followed by what it does.
+    pub fn help_string(&self) -> String {
+        format!("{} : {}", self.usage(), self.description)
+    }
+
+    /// The help string along with the choices of arguments too long to show in it.
+    fn detailed_help(&self) -> String {
+        let mut help = self.help_string();
+        for arg in &self.args {
+            if let ArgumentSpec::Enum(label, choices, _) = arg {
+                if choices.len() > MAX_LISTED_CHOICES {
+                    help += &format!("\n<{}> is one of: {}", label, choices.join(", "));
+                }
             }
-        } else {
-            (self.handler)(server, entity, args, self);
+        }
+        help
+    }
+
+    /// Check the arguments against `args`, explaining the first problem found.
+    fn check_args(&self, args: &str) -> Result<(), String> {
+        let mut words = args.split_whitespace();
+        for arg in &self.args {
+            if let ArgumentSpec::Message(..) = arg {
+                return match words.next() {
+                    None if arg.requirement() == Requirement::Required => {
+                        Err(format!("Missing {}.", arg.usage()))
+                    }
+                    _ => Ok(()),
+                };
+            }
+            for i in 0..arg.words() {
+                match words.next() {
+                    Some(word) => arg.check(word, self.keyword)?,
+                    // Arguments of several words can only be left out entirely.
+                    None if arg.requirement() == Requirement::Required || i > 0 => {
+                        return Err(format!("Missing {}.", arg.usage()))
+                    }
+                    None => break,
+                }
+            }
+        }
+        match words.next() {
+            Some(word) => Err(format!("Unexpected argument '{}'.", word)),
+            None => Ok(()),
         }
     }
+
+    /// Whether `entity` is allowed to run the command.
+    fn is_available(&self, server: &Server, entity: EcsEntity) -> bool {
+        self.permission.map_or(true, |permission| {
+            server.entity_has_permission(entity, permission)
+        })
+    }
+
+    /// Checks the arguments and calls the contained handler function, passing `&self` as the
+    /// last argument.
+    pub fn execute(&self, server: &mut Server, entity: EcsEntity, args: String) {
+        if !self.is_available(server, entity) {
+            server.notify_client(
+                entity,
+                ServerMsg::private(format!(
+                    "Unavailable command '/{}'.\nType '/help' for available commands",
+                    self.keyword
+                )),
+            );
+            return;
+        }
+        if let Err(err) = self.check_args(&args) {
+            server.notify_client(
+                entity,
+                ServerMsg::private(format!("{}\n{}", err, self.help_string())),
+            );
+            return;
+        }
+        (self.handler)(server, entity, args, self);
+    }
+}
+
+/// Complete the last word of a partly typed command, returning each way of doing so as the
+/// whole line.
+pub fn complete(server: &Server, entity: EcsEntity, line: &str) -> Vec<String> {
+    let aliases = server
+        .state
+        .ecs()
+        .read_storage::<comp::Player>()
+        .join()
+        .map(|player| player.alias.clone())
+        .collect::<Vec<_>>();
+    complete_line(line, &aliases, |cmd| cmd.is_available(server, entity))
+}
+
+/// Complete `line` with the commands that are `available` and the `aliases` of the players
+/// online.
+fn complete_line(
+    line: &str,
+    aliases: &[String],
+    available: impl Fn(&ChatCommand) -> bool,
+) -> Vec<String> {
+    if !line.starts_with('/') {
+        return Vec::new();
+    }
+    let cmd = &line[1..];
+    // The word being typed is empty if the line ends with a space.
+    let mut words = cmd.split(' ').collect::<Vec<_>>();
+    let prefix = words.pop().unwrap_or("");
+    let start = &line[..line.len() - prefix.len()];
+
+    let completions = match words.split_first() {
+        None => CHAT_COMMANDS
+            .iter()
+            .filter(|cmd| cmd.keyword.starts_with(prefix) && available(*cmd))
+            .map(|cmd| cmd.keyword.to_owned())
+            .collect(),
+        Some((keyword, typed)) => {
+            let typed = typed.iter().filter(|word| !word.is_empty()).count();
+            let arg = CHAT_COMMANDS
+                .iter()
+                .find(|cmd| cmd.keyword == *keyword && available(*cmd))
+                .and_then(|cmd| {
+                    // Find the argument the word belongs to.
+                    let mut words = 0;
+                    cmd.args.iter().find(|arg| {
+                        words += arg.words();
+                        words > typed
+                    })
+                });
+            match arg {
+                Some(ArgumentSpec::Message(..)) | None => Vec::new(),
+                Some(arg) => arg.completions(aliases, prefix),
+            }
+        }
+    };
+    completions
+        .into_iter()
+        .map(|completion| format!("{}{}", start, completion))
+        .collect()
 }
 
 lazy_static! {
@@ -82,137 +373,341 @@ lazy_static! {
     pub static ref CHAT_COMMANDS: Vec<ChatCommand> = vec![
         ChatCommand::new(
             "jump",
-            "{d} {d} {d}",
-            "/jump <dx> <dy> <dz> : Offset your current position",
-            true,
+            vec![ArgumentSpec::Position(Requirement::Required)],
+            "Offset your current position",
+            Some(Permission::Teleport),
             handle_jump,
         ),
         ChatCommand::new(
             "goto",
-            "{d} {d} {d}",
-            "/goto <x> <y> <z> : Teleport to a position",
-            true,
+            vec![ArgumentSpec::Position(Requirement::Required)],
+            "Teleport to a position",
+            Some(Permission::Teleport),
             handle_goto,
         ),
         ChatCommand::new(
             "alias",
-            "{}",
-            "/alias <name> : Change your alias",
-            false,
+            vec![ArgumentSpec::Any("name", Requirement::Required)],
+            "Change your alias",
+            None,
             handle_alias,
         ),
         ChatCommand::new(
             "tp",
-            "{}",
-            "/tp <alias> : Teleport to another player",
-            true,
+            vec![ArgumentSpec::PlayerName(Requirement::Required)],
+            "Teleport to another player",
+            Some(Permission::Teleport),
             handle_tp,
         ),
         ChatCommand::new(
             "kill",
-            "{}",
-            "/kill : Kill yourself",
-            false,
+            vec![],
+            "Kill yourself",
+            None,
             handle_kill,
         ),
         ChatCommand::new(
             "time",
-            "{} {s}",
-            "/time <XY:XY> or [Time of day] : Set the time of day",
-            true,
+            vec![ArgumentSpec::Any("time", Requirement::Optional)],
+            "Set the time of day, given as HH:MM, HH:MM:SS, seconds since midnight or one of night, dawn, day and dusk. Tells the time if none is given",
+            Some(Permission::Time),
             handle_time,
         ),
         ChatCommand::new(
             "spawn",
-            "{} {} {d}",
-            "/spawn <alignment> <entity> [amount] : Spawn a test entity",
-            true,
+            vec![
+                ArgumentSpec::Enum(
+                    "alignment",
+                    choices(&["hostile", "friendly"]),
+                    Requirement::Required,
+                ),
+                ArgumentSpec::Enum(
+                    "entity",
+                    choices(&["humanoid", "wolf", "pig"]),
+                    Requirement::Required,
+                ),
+                ArgumentSpec::Integer("amount", 1..=10, Requirement::Optional),
+            ],
+            "Spawn a test entity",
+            Some(Permission::Spawn),
             handle_spawn,
         ),
         ChatCommand::new(
              "players",
-             "{}",
-             "/players : Lists players currently online",
-             false,
+             vec![],
+             "Lists players currently online",
+             None,
              handle_players,
          ),
         ChatCommand::new(
-            "help", "", "/help: Display this message", false, handle_help),
+            "help",
+            vec![ArgumentSpec::Any("command", Requirement::Optional)],
+            "Display this message, or the details of a command",
+            None,
+            handle_help,
+        ),
         ChatCommand::new(
             "health",
-            "{}",
-            "/health : Set your current health",
-            true,
+            vec![ArgumentSpec::Integer(
+                "hp",
+                0..=i64::from(u32::max_value()),
+                Requirement::Required,
+            )],
+            "Set your current health",
+            Some(Permission::Cheat),
             handle_health,
         ),
         ChatCommand::new(
             "build",
-            "",
-            "/build : Toggles build mode on and off",
-            true,
+            vec![],
+            "Toggles build mode on and off",
+            Some(Permission::Build),
             handle_build,
         ),
         ChatCommand::new(
             "tell",
-            "{}",
-            "/tell <alias> <message>: Send a message to another player",
-            false,
+            vec![
+                ArgumentSpec::PlayerName(Requirement::Required),
+                ArgumentSpec::Message("message", Requirement::Optional),
+            ],
+            "Send a message to another player",
+            None,
             handle_tell,
         ),
+        ChatCommand::new(
+            "faction",
+            vec![
+                ArgumentSpec::Enum(
+                    "action",
+                    choices(&["create", "invite", "accept", "leave"]),
+                    Requirement::Required,
+                ),
+                ArgumentSpec::Message("name", Requirement::Optional),
+            ],
+            "Manage your faction, whose members can hear your faction chat ('!f <message>'). Create one with a name nobody online is using, or join one by accepting an invite from a member. Creating needs a faction name and inviting needs a player",
+            None,
+            handle_faction,
+        ),
+        ChatCommand::new(
+            "group",
+            vec![
+                ArgumentSpec::Enum(
+                    "action",
+                    choices(&["invite", "kick", "leader", "accept", "decline", "leave"]),
+                    Requirement::Required,
+                ),
+                ArgumentSpec::PlayerName(Requirement::Optional),
+            ],
+            "Manage your group. Inviting, kicking and handing over the lead need a player",
+            None,
+            handle_group,
+        ),
+        ChatCommand::new(
+            "friend",
+            vec![
+                ArgumentSpec::Enum(
+                    "action",
+                    choices(&["add", "accept", "decline", "remove"]),
+                    Requirement::Required,
+                ),
+                ArgumentSpec::PlayerName(Requirement::Required),
+            ],
+            "Manage your friends list",
+            None,
+            handle_friend,
+        ),
         ChatCommand::new(
             "killnpcs",
-            "{}",
-            "/killnpcs : Kill the NPCs",
-            true,
+            vec![],
+            "Kill the NPCs",
+            Some(Permission::Spawn),
             handle_killnpcs,
         ),
         ChatCommand::new(
             "object",
-            "{}",
-            "/object [Name]: Spawn an object",
-            true,
+            vec![ArgumentSpec::Enum(
+                "object",
+                OBJECTS.iter().map(|(name, _)| name.to_string()).collect(),
+                Requirement::Required,
+            )],
+            "Spawn an object",
+            Some(Permission::Spawn),
             handle_object,
         ),
         ChatCommand::new(
             "light",
-            "{} {} {} {} {} {} {}",
-            "/light <opt:  <<cr> <cg> <cb>> <<ox> <oy> <oz>> <<strength>>>: Spawn entity with light",
-            true,
+            vec![
+                ArgumentSpec::Float("r", Requirement::Optional),
+                ArgumentSpec::Float("g", Requirement::Optional),
+                ArgumentSpec::Float("b", Requirement::Optional),
+                ArgumentSpec::Position(Requirement::Optional),
+                ArgumentSpec::Float("strength", Requirement::Optional),
+            ],
+            "Spawn entity with light, with an optional colour, offset and strength",
+            Some(Permission::Spawn),
             handle_light,
         ),
         ChatCommand::new(
             "lantern",
-            "{}",
-            "/lantern : adds/remove light near player",
-            false,
+            vec![ArgumentSpec::Float("strength", Requirement::Optional)],
+            "adds/remove light near player",
+            None,
             handle_lantern,
         ),
         ChatCommand::new(
             "explosion",
-            "{}",
-            "/explosion <radius> : Explodes the ground around you",
-            false,
+            vec![ArgumentSpec::Float("radius", Requirement::Optional)],
+            "Explodes the ground around you",
+            None,
             handle_explosion,
         ),
         ChatCommand::new(
-            "adminify",
-            "{}",
-            "/adminify <playername> : Temporarily gives a player admin permissions or removes them",
-            true,
-            handle_adminify,
+            "role",
+            vec![
+                ArgumentSpec::Enum(
+                    "action",
+                    choices(&["grant", "revoke", "show"]),
+                    Requirement::Required,
+                ),
+                ArgumentSpec::PlayerName(Requirement::Required),
+                ArgumentSpec::Any("role", Requirement::Optional),
+            ],
+            "Give a role to an account or take it away. 'show' lists the roles of an account",
+            Some(Permission::ManageRoles),
+            handle_role,
         ),
         ChatCommand::new(
              "debug_column",
-             "{} {}",
-             "/debug_column <x> <y> : Prints some debug information about a column",
-             false,
+             vec![
+                 ArgumentSpec::Integer("x", I32_RANGE, Requirement::Required),
+                 ArgumentSpec::Integer("y", I32_RANGE, Requirement::Required),
+             ],
+             "Prints some debug information about a column",
+             None,
              handle_debug_column,
          ),
+        ChatCommand::new(
+            "kick",
+            vec![
+                ArgumentSpec::PlayerName(Requirement::Required),
+                ArgumentSpec::Message("reason", Requirement::Optional),
+            ],
+            "Disconnect a player",
+            Some(Permission::Moderate),
+            handle_kick,
+        ),
+        ChatCommand::new(
+            "ban",
+            vec![
+                ArgumentSpec::PlayerName(Requirement::Required),
+                ArgumentSpec::Any("duration", Requirement::Optional),
+                ArgumentSpec::Message("reason", Requirement::Optional),
+            ],
+            "Ban an account, e.g. '/ban name 3d griefing'. Bans without a duration are permanent",
+            Some(Permission::Ban),
+            handle_ban,
+        ),
+        ChatCommand::new(
+            "banip",
+            vec![
+                ArgumentSpec::Any("alias or ip", Requirement::Required),
+                ArgumentSpec::Any("duration", Requirement::Optional),
+                ArgumentSpec::Message("reason", Requirement::Optional),
+            ],
+            "Ban the address of a player or an IP address",
+            Some(Permission::Ban),
+            handle_banip,
+        ),
+        ChatCommand::new(
+            "unban",
+            vec![ArgumentSpec::Any("alias or ip", Requirement::Required)],
+            "Lift a ban",
+            Some(Permission::Ban),
+            handle_unban,
+        ),
+        ChatCommand::new(
+            "mute",
+            vec![
+                ArgumentSpec::PlayerName(Requirement::Required),
+                ArgumentSpec::Any("duration", Requirement::Optional),
+                ArgumentSpec::Message("reason", Requirement::Optional),
+            ],
+            "Stop a player from chatting",
+            Some(Permission::Moderate),
+            handle_mute,
+        ),
+        ChatCommand::new(
+            "unmute",
+            vec![ArgumentSpec::PlayerName(Requirement::Required)],
+            "Allow a player to chat again",
+            Some(Permission::Moderate),
+            handle_unmute,
+        ),
     ];
 }
 
+fn choices(choices: &[&str]) -> Vec<String> {
+    choices.iter().map(|choice| choice.to_string()).collect()
+}
+
+/// The objects that can be spawned with '/object', by name.
+const OBJECTS: &[(&str, comp::object::Body)] = &[
+    ("scarecrow", comp::object::Body::Scarecrow),
+    ("cauldron", comp::object::Body::Cauldron),
+    ("chest_vines", comp::object::Body::ChestVines),
+    ("chest", comp::object::Body::Chest),
+    ("chest_dark", comp::object::Body::ChestDark),
+    ("chest_demon", comp::object::Body::ChestDemon),
+    ("chest_gold", comp::object::Body::ChestGold),
+    ("chest_light", comp::object::Body::ChestLight),
+    ("chest_open", comp::object::Body::ChestOpen),
+    ("chest_skull", comp::object::Body::ChestSkull),
+    ("pumpkin", comp::object::Body::Pumpkin),
+    ("pumpkin_2", comp::object::Body::Pumpkin2),
+    ("pumpkin_3", comp::object::Body::Pumpkin3),
+    ("pumpkin_4", comp::object::Body::Pumpkin4),
+    ("pumpkin_5", comp::object::Body::Pumpkin5),
+    ("campfire", comp::object::Body::Campfire),
+    ("lantern_ground", comp::object::Body::LanternGround),
+    ("lantern_ground_open", comp::object::Body::LanternGroundOpen),
+    ("lantern_2", comp::object::Body::LanternStanding2),
+    ("lantern", comp::object::Body::LanternStanding),
+    ("potion_blue", comp::object::Body::PotionBlue),
+    ("potion_green", comp::object::Body::PotionGreen),
+    ("potion_red", comp::object::Body::PotionRed),
+    ("crate", comp::object::Body::Crate),
+    ("tent", comp::object::Body::Tent),
+    ("bomb", comp::object::Body::Bomb),
+    ("window_spooky", comp::object::Body::WindowSpooky),
+    ("door_spooky", comp::object::Body::DoorSpooky),
+    ("carpet", comp::object::Body::Carpet),
+    ("table_human", comp::object::Body::Table),
+    ("table_human_2", comp::object::Body::Table2),
+    ("table_human_3", comp::object::Body::Table3),
+    ("drawer", comp::object::Body::Drawer),
+    ("bed_human_blue", comp::object::Body::BedBlue),
+    ("anvil", comp::object::Body::Anvil),
+    ("gravestone", comp::object::Body::Gravestone),
+    ("gravestone_2", comp::object::Body::Gravestone2),
+    ("chair", comp::object::Body::Chair),
+    ("chair_2", comp::object::Body::Chair2),
+    ("chair_3", comp::object::Body::Chair3),
+    ("bench_human", comp::object::Body::Bench),
+    ("bedroll", comp::object::Body::Bedroll),
+    ("carpet_human_round", comp::object::Body::CarpetHumanRound),
+    ("carpet_human_square", comp::object::Body::CarpetHumanSquare),
+    (
+        "carpet_human_square_2",
+        comp::object::Body::CarpetHumanSquare2,
+    ),
+    (
+        "carpet_human_squircle",
+        comp::object::Body::CarpetHumanSquircle,
+    ),
+];
+
 fn handle_jump(server: &mut Server, entity: EcsEntity, args: String, action: &ChatCommand) {
-    if let Ok((x, y, z)) = scan_fmt!(&args, action.arg_fmt, f32, f32, f32) {
+    if let Ok((x, y, z)) = scan_fmt!(&args, &action.arg_fmt, f32, f32, f32) {
         match server.state.read_component_cloned::<comp::Pos>(entity) {
             Some(current_pos) => {
                 server
@@ -220,7 +715,7 @@ fn handle_jump(server: &mut Server, entity: EcsEntity, args: String, action: &Ch
                     .write_component(entity, comp::Pos(current_pos.0 + Vec3::new(x, y, z)));
                 server.state.write_component(entity, comp::ForceUpdate);
             }
-            None => server.clients.notify(
+            None => server.notify_client(
                 entity,
                 ServerMsg::private(String::from("You have no position.")),
             ),
@@ -229,7 +724,7 @@ fn handle_jump(server: &mut Server, entity: EcsEntity, args: String, action: &Ch
 }
 
 fn handle_goto(server: &mut Server, entity: EcsEntity, args: String, action: &ChatCommand) {
-    if let Ok((x, y, z)) = scan_fmt!(&args, action.arg_fmt, f32, f32, f32) {
+    if let Ok((x, y, z)) = scan_fmt!(&args, &action.arg_fmt, f32, f32, f32) {
         if server
             .state
             .read_component_cloned::<comp::Pos>(entity)
@@ -240,15 +735,13 @@ fn handle_goto(server: &mut Server, entity: EcsEntity, args: String, action: &Ch
                 .write_component(entity, comp::Pos(Vec3::new(x, y, z)));
             server.state.write_component(entity, comp::ForceUpdate);
         } else {
-            server.clients.notify(
+            server.notify_client(
                 entity,
                 ServerMsg::private(String::from("You have no position.")),
             );
         }
     } else {
-        server
-            .clients
-            .notify(entity, ServerMsg::private(String::from(action.help_string)));
+        server.notify_client(entity, ServerMsg::private(action.help_string()));
     }
 }
 
@@ -262,24 +755,17 @@ fn handle_kill(server: &mut Server, entity: EcsEntity, _args: String, _action: &
 }
 
 fn handle_time(server: &mut Server, entity: EcsEntity, args: String, action: &ChatCommand) {
-    let time = scan_fmt_some!(&args, action.arg_fmt, String);
-    let new_time = match time.as_ref().map(|s| s.as_str()) {
-        Some("night") => NaiveTime::from_hms(0, 0, 0),
-        Some("dawn") => NaiveTime::from_hms(5, 0, 0),
-        Some("day") => NaiveTime::from_hms(12, 0, 0),
-        Some("dusk") => NaiveTime::from_hms(17, 0, 0),
-        Some(n) => match n.parse() {
-            Ok(n) => n,
-            Err(_) => match NaiveTime::parse_from_str(n, "%H:%M") {
-                Ok(time) => time,
-                Err(_) => {
-                    server.clients.notify(
-                        entity,
-                        ServerMsg::private(format!("'{}' is not a valid time.", n)),
-                    );
-                    return;
-                }
-            },
+    let time = scan_fmt_some!(&args, &action.arg_fmt, String);
+    let new_time = match time {
+        Some(n) => match parse_time(&n) {
+            Some(time) => time,
+            None => {
+                server.notify_client(
+                    entity,
+                    ServerMsg::private(format!("'{}' is not a valid time.", n)),
+                );
+                return;
+            }
         },
         None => {
             let time_in_seconds = server.state.ecs_mut().read_resource::<TimeOfDay>().0;
@@ -293,7 +779,7 @@ fn handle_time(server: &mut Server, entity: EcsEntity, args: String, action: &Ch
                 Some(time) => format!("It is {}", time.format("%H:%M").to_string()),
                 None => String::from("Unknown Time"),
             };
-            server.clients.notify(entity, ServerMsg::private(msg));
+            server.notify_client(entity, ServerMsg::private(msg));
             return;
         }
     };
@@ -301,7 +787,7 @@ fn handle_time(server: &mut Server, entity: EcsEntity, args: String, action: &Ch
     server.state.ecs_mut().write_resource::<TimeOfDay>().0 =
         new_time.num_seconds_from_midnight() as f64;
 
-    server.clients.notify(
+    server.notify_client(
         entity,
         ServerMsg::private(format!(
             "Time changed to: {}",
@@ -310,8 +796,24 @@ fn handle_time(server: &mut Server, entity: EcsEntity, args: String, action: &Ch
     );
 }
 
+/// Parse a time of day given to `/time`.
+fn parse_time(s: &str) -> Option<NaiveTime> {
+    match s {
+        "night" => Some(NaiveTime::from_hms(0, 0, 0)),
+        "dawn" => Some(NaiveTime::from_hms(5, 0, 0)),
+        "day" => Some(NaiveTime::from_hms(12, 0, 0)),
+        "dusk" => Some(NaiveTime::from_hms(17, 0, 0)),
+        _ => s
+            .parse::<u32>()
+            .ok()
+            .and_then(|secs| NaiveTime::from_num_seconds_from_midnight_opt(secs, 0))
+            .or_else(|| NaiveTime::parse_from_str(s, "%H:%M:%S").ok())
+            .or_else(|| NaiveTime::parse_from_str(s, "%H:%M").ok()),
+    }
+}
+
 fn handle_health(server: &mut Server, entity: EcsEntity, args: String, action: &ChatCommand) {
-    if let Ok(hp) = scan_fmt!(&args, action.arg_fmt, u32) {
+    if let Ok(hp) = scan_fmt!(&args, &action.arg_fmt, u32) {
         if let Some(stats) = server
             .state
             .ecs_mut()
@@ -320,13 +822,13 @@ fn handle_health(server: &mut Server, entity: EcsEntity, args: String, action: &
         {
             stats.health.set_to(hp, comp::HealthSource::Command);
         } else {
-            server.clients.notify(
+            server.notify_client(
                 entity,
                 ServerMsg::private(String::from("You have no health.")),
             );
         }
     } else {
-        server.clients.notify(
+        server.notify_client(
             entity,
             ServerMsg::private(String::from("You must specify health amount!")),
         );
@@ -334,22 +836,40 @@ fn handle_health(server: &mut Server, entity: EcsEntity, args: String, action: &
 }
 
 fn handle_alias(server: &mut Server, entity: EcsEntity, args: String, action: &ChatCommand) {
-    if let Ok(alias) = scan_fmt!(&args, action.arg_fmt, String) {
-        server
-            .state
-            .ecs_mut()
-            .write_storage::<comp::Player>()
-            .get_mut(entity)
-            .map(|player| player.alias = alias);
+    if let Ok(alias) = scan_fmt!(&args, &action.arg_fmt, String) {
+        // Other players' names, and those of accounts, can't be taken so nobody can pass for
+        // somebody else.
+        let own_account = server.account_of(entity).map(str::to_owned);
+        let taken = server
+            .player_by_alias(&alias)
+            .map_or(false, |player| player != entity)
+            || (own_account.as_ref() != Some(&alias)
+                && (server.accounts.exists(&alias) || server.player_by_account(&alias).is_some()));
+        if !comp::Player::new(alias.clone(), None).is_valid() {
+            server.notify_client(
+                entity,
+                ServerMsg::private(format!("'{}' is not a valid alias.", alias)),
+            );
+        } else if taken {
+            server.notify_client(
+                entity,
+                ServerMsg::private(format!("The alias '{}' is taken.", alias)),
+            );
+        } else {
+            server
+                .state
+                .ecs_mut()
+                .write_storage::<comp::Player>()
+                .get_mut(entity)
+                .map(|player| player.alias = alias);
+        }
     } else {
-        server
-            .clients
-            .notify(entity, ServerMsg::private(String::from(action.help_string)));
+        server.notify_client(entity, ServerMsg::private(action.help_string()));
     }
 }
 
 fn handle_tp(server: &mut Server, entity: EcsEntity, args: String, action: &ChatCommand) {
-    if let Ok(alias) = scan_fmt!(&args, action.arg_fmt, String) {
+    if let Ok(alias) = scan_fmt!(&args, &action.arg_fmt, String) {
         let ecs = server.state.ecs();
         let opt_player = (&ecs.entities(), &ecs.read_storage::<comp::Player>())
             .join()
@@ -362,36 +882,30 @@ fn handle_tp(server: &mut Server, entity: EcsEntity, args: String, action: &Chat
                         server.state.write_component(entity, pos);
                         server.state.write_component(entity, comp::ForceUpdate);
                     }
-                    None => server.clients.notify(
+                    None => server.notify_client(
                         entity,
                         ServerMsg::private(format!("Unable to teleport to player '{}'!", alias)),
                     ),
                 },
                 None => {
-                    server.clients.notify(
+                    server.notify_client(
                         entity,
                         ServerMsg::private(format!("Player '{}' not found!", alias)),
                     );
-                    server
-                        .clients
-                        .notify(entity, ServerMsg::private(String::from(action.help_string)));
+                    server.notify_client(entity, ServerMsg::private(action.help_string()));
                 }
             },
             None => {
-                server
-                    .clients
-                    .notify(entity, ServerMsg::private(format!("You have no position!")));
+                server.notify_client(entity, ServerMsg::private(format!("You have no position!")));
             }
         }
     } else {
-        server
-            .clients
-            .notify(entity, ServerMsg::private(String::from(action.help_string)));
+        server.notify_client(entity, ServerMsg::private(action.help_string()));
     }
 }
 
 fn handle_spawn(server: &mut Server, entity: EcsEntity, args: String, action: &ChatCommand) {
-    match scan_fmt_some!(&args, action.arg_fmt, String, NpcKind, String) {
+    match scan_fmt_some!(&args, &action.arg_fmt, String, NpcKind, String) {
         (Some(opt_align), Some(id), opt_amount) => {
             if let Some(agent) = alignment_to_agent(&opt_align, entity) {
                 let amount = opt_amount
@@ -416,12 +930,12 @@ fn handle_spawn(server: &mut Server, entity: EcsEntity, args: String, action: &C
                                 .with(agent)
                                 .build();
                         }
-                        server.clients.notify(
+                        server.notify_client(
                             entity,
                             ServerMsg::private(format!("Spawned {} entities", amount).to_owned()),
                         );
                     }
-                    None => server.clients.notify(
+                    None => server.notify_client(
                         entity,
                         ServerMsg::private("You have no position!".to_owned()),
                     ),
@@ -429,35 +943,32 @@ fn handle_spawn(server: &mut Server, entity: EcsEntity, args: String, action: &C
             }
         }
         _ => {
-            server
-                .clients
-                .notify(entity, ServerMsg::private(String::from(action.help_string)));
+            server.notify_client(entity, ServerMsg::private(action.help_string()));
         }
     }
 }
 
 fn handle_players(server: &mut Server, entity: EcsEntity, _args: String, _action: &ChatCommand) {
-    let ecs = server.state.ecs();
-    let players = ecs.read_storage::<comp::Player>();
-    let count = players.join().count();
-    let header_message: String = format!("{} online players: \n", count);
-    if count > 0 {
-        let mut player_iter = players.join();
-        let first = player_iter.next().unwrap().alias.to_owned();
-        let player_list = player_iter.fold(first, |mut s, p| {
-            s += ",\n";
-            s += &p.alias;
-            s
-        });
+    let msg = {
+        let ecs = server.state.ecs();
+        let players = ecs.read_storage::<comp::Player>();
+        let count = players.join().count();
+        let header_message: String = format!("{} online players: \n", count);
+        if count > 0 {
+            let mut player_iter = players.join();
+            let first = player_iter.next().unwrap().alias.to_owned();
+            let player_list = player_iter.fold(first, |mut s, p| {
+                s += ",\n";
+                s += &p.alias;
+                s
+            });
 
-        server
-            .clients
-            .notify(entity, ServerMsg::private(header_message + &player_list));
-    } else {
-        server
-            .clients
-            .notify(entity, ServerMsg::private(header_message));
-    }
+            header_message + &player_list
+        } else {
+            header_message
+        }
+    };
+    server.notify_client(entity, ServerMsg::private(msg));
 }
 
 fn handle_build(server: &mut Server, entity: EcsEntity, _args: String, _action: &ChatCommand) {
@@ -472,7 +983,7 @@ fn handle_build(server: &mut Server, entity: EcsEntity, _args: String, _action:
             .ecs()
             .write_storage::<comp::CanBuild>()
             .remove(entity);
-        server.clients.notify(
+        server.notify_client(
             entity,
             ServerMsg::private(String::from("Toggled off build mode!")),
         );
@@ -482,19 +993,32 @@ fn handle_build(server: &mut Server, entity: EcsEntity, _args: String, _action:
             .ecs()
             .write_storage::<comp::CanBuild>()
             .insert(entity, comp::CanBuild);
-        server.clients.notify(
+        server.notify_client(
             entity,
             ServerMsg::private(String::from("Toggled on build mode!")),
         );
     }
 }
 
-// TODO: Don't display commands that the player cannot use.
-fn handle_help(server: &mut Server, entity: EcsEntity, _args: String, _action: &ChatCommand) {
-    for cmd in CHAT_COMMANDS.iter() {
-        server
-            .clients
-            .notify(entity, ServerMsg::private(String::from(cmd.help_string)));
+fn handle_help(server: &mut Server, entity: EcsEntity, args: String, action: &ChatCommand) {
+    let available = CHAT_COMMANDS
+        .iter()
+        .filter(|cmd| cmd.is_available(server, entity))
+        .collect::<Vec<_>>();
+    match scan_fmt!(&args, &action.arg_fmt, String) {
+        Ok(keyword) => {
+            let keyword = keyword.trim_start_matches('/');
+            let msg = match available.iter().find(|cmd| cmd.keyword == keyword) {
+                Some(cmd) => cmd.detailed_help(),
+                None => format!("Unknown command '/{}'.", keyword),
+            };
+            server.notify_client(entity, ServerMsg::private(msg));
+        }
+        Err(_) => {
+            for cmd in available {
+                server.notify_client(entity, ServerMsg::private(cmd.help_string()));
+            }
+        }
     }
 }
 
@@ -519,24 +1043,26 @@ fn kind_to_body(kind: NpcKind) -> comp::Body {
 }
 
 fn handle_killnpcs(server: &mut Server, entity: EcsEntity, _args: String, _action: &ChatCommand) {
-    let ecs = server.state.ecs();
-    let mut stats = ecs.write_storage::<comp::Stats>();
-    let players = ecs.read_storage::<comp::Player>();
     let mut count = 0;
-    for (stats, ()) in (&mut stats, !&players).join() {
-        count += 1;
-        stats.health.set_to(0, comp::HealthSource::Command);
+    {
+        let ecs = server.state.ecs();
+        let mut stats = ecs.write_storage::<comp::Stats>();
+        let players = ecs.read_storage::<comp::Player>();
+        for (stats, ()) in (&mut stats, !&players).join() {
+            count += 1;
+            stats.health.set_to(0, comp::HealthSource::Command);
+        }
     }
     let text = if count > 0 {
         format!("Destroyed {} NPCs.", count)
     } else {
         "No NPCs on server.".to_string()
     };
-    server.clients.notify(entity, ServerMsg::private(text));
+    server.notify_client(entity, ServerMsg::private(text));
 }
 
-fn handle_object(server: &mut Server, entity: EcsEntity, args: String, _action: &ChatCommand) {
-    let obj_type = scan_fmt!(&args, _action.arg_fmt, String);
+fn handle_object(server: &mut Server, entity: EcsEntity, args: String, action: &ChatCommand) {
+    let obj_type = scan_fmt!(&args, &action.arg_fmt, String);
 
     let pos = server
         .state
@@ -554,55 +1080,13 @@ fn handle_object(server: &mut Server, entity: EcsEntity, args: String, _action:
     .create_object(pos, ori, obj_type)
     .with(ori);*/
     if let (Some(pos), Some(ori)) = (pos, ori) {
-        let obj_type = match obj_type.as_ref().map(String::as_str) {
-            Ok("scarecrow") => comp::object::Body::Scarecrow,
-            Ok("cauldron") => comp::object::Body::Cauldron,
-            Ok("chest_vines") => comp::object::Body::ChestVines,
-            Ok("chest") => comp::object::Body::Chest,
-            Ok("chest_dark") => comp::object::Body::ChestDark,
-            Ok("chest_demon") => comp::object::Body::ChestDemon,
-            Ok("chest_gold") => comp::object::Body::ChestGold,
-            Ok("chest_light") => comp::object::Body::ChestLight,
-            Ok("chest_open") => comp::object::Body::ChestOpen,
-            Ok("chest_skull") => comp::object::Body::ChestSkull,
-            Ok("pumpkin") => comp::object::Body::Pumpkin,
-            Ok("pumpkin_2") => comp::object::Body::Pumpkin2,
-            Ok("pumpkin_3") => comp::object::Body::Pumpkin3,
-            Ok("pumpkin_4") => comp::object::Body::Pumpkin4,
-            Ok("pumpkin_5") => comp::object::Body::Pumpkin5,
-            Ok("campfire") => comp::object::Body::Campfire,
-            Ok("lantern_ground") => comp::object::Body::LanternGround,
-            Ok("lantern_ground_open") => comp::object::Body::LanternGroundOpen,
-            Ok("lantern_2") => comp::object::Body::LanternStanding2,
-            Ok("lantern") => comp::object::Body::LanternStanding,
-            Ok("potion_blue") => comp::object::Body::PotionBlue,
-            Ok("potion_green") => comp::object::Body::PotionGreen,
-            Ok("potion_red") => comp::object::Body::PotionRed,
-            Ok("crate") => comp::object::Body::Crate,
-            Ok("tent") => comp::object::Body::Tent,
-            Ok("bomb") => comp::object::Body::Bomb,
-            Ok("window_spooky") => comp::object::Body::WindowSpooky,
-            Ok("door_spooky") => comp::object::Body::DoorSpooky,
-            Ok("carpet") => comp::object::Body::Carpet,
-            Ok("table_human") => comp::object::Body::Table,
-            Ok("table_human_2") => comp::object::Body::Table2,
-            Ok("table_human_3") => comp::object::Body::Table3,
-            Ok("drawer") => comp::object::Body::Drawer,
-            Ok("bed_human_blue") => comp::object::Body::BedBlue,
-            Ok("anvil") => comp::object::Body::Anvil,
-            Ok("gravestone") => comp::object::Body::Gravestone,
-            Ok("gravestone_2") => comp::object::Body::Gravestone2,
-            Ok("chair") => comp::object::Body::Chair,
-            Ok("chair_2") => comp::object::Body::Chair2,
-            Ok("chair_3") => comp::object::Body::Chair3,
-            Ok("bench_human") => comp::object::Body::Bench,
-            Ok("bedroll") => comp::object::Body::Bedroll,
-            Ok("carpet_human_round") => comp::object::Body::CarpetHumanRound,
-            Ok("carpet_human_square") => comp::object::Body::CarpetHumanSquare,
-            Ok("carpet_human_square_2") => comp::object::Body::CarpetHumanSquare2,
-            Ok("carpet_human_squircle") => comp::object::Body::CarpetHumanSquircle,
-            _ => {
-                return server.clients.notify(
+        let obj_type = match obj_type
+            .ok()
+            .and_then(|name| OBJECTS.iter().find(|(object, _)| *object == name))
+        {
+            Some((_, body)) => *body,
+            None => {
+                return server.notify_client(
                     entity,
                     ServerMsg::private(String::from("Object not found!")),
                 );
@@ -623,19 +1107,15 @@ fn handle_object(server: &mut Server, entity: EcsEntity, args: String, _action:
                     .normalized(),
             ))
             .build();
-        server
-            .clients
-            .notify(entity, ServerMsg::private(format!("Spawned object.")));
+        server.notify_client(entity, ServerMsg::private(format!("Spawned object.")));
     } else {
-        server
-            .clients
-            .notify(entity, ServerMsg::private(format!("You have no position!")));
+        server.notify_client(entity, ServerMsg::private(format!("You have no position!")));
     }
 }
 
 fn handle_light(server: &mut Server, entity: EcsEntity, args: String, action: &ChatCommand) {
     let (opt_r, opt_g, opt_b, opt_x, opt_y, opt_z, opt_s) =
-        scan_fmt_some!(&args, action.arg_fmt, f32, f32, f32, f32, f32, f32, f32);
+        scan_fmt_some!(&args, &action.arg_fmt, f32, f32, f32, f32, f32, f32, f32);
 
     let mut light_emitter = comp::LightEmitter::default();
 
@@ -666,18 +1146,14 @@ fn handle_light(server: &mut Server, entity: EcsEntity, args: String, action: &C
             .with(comp::ForceUpdate)
             .with(light_emitter)
             .build();
-        server
-            .clients
-            .notify(entity, ServerMsg::private(format!("Spawned object.")));
+        server.notify_client(entity, ServerMsg::private(format!("Spawned object.")));
     } else {
-        server
-            .clients
-            .notify(entity, ServerMsg::private(format!("You have no position!")));
+        server.notify_client(entity, ServerMsg::private(format!("You have no position!")));
     }
 }
 
 fn handle_lantern(server: &mut Server, entity: EcsEntity, args: String, action: &ChatCommand) {
-    let opt_s = scan_fmt_some!(&args, action.arg_fmt, f32);
+    let opt_s = scan_fmt_some!(&args, &action.arg_fmt, f32);
 
     if server
         .state
@@ -693,18 +1169,18 @@ fn handle_lantern(server: &mut Server, entity: EcsEntity, args: String, action:
                 .get_mut(entity)
             {
                 light.strength = s.max(0.1).min(20.0);
-                server.clients.notify(
-                    entity,
-                    ServerMsg::private(String::from("You played with flame strength.")),
-                );
             }
+            server.notify_client(
+                entity,
+                ServerMsg::private(String::from("You played with flame strength.")),
+            );
         } else {
             server
                 .state
                 .ecs()
                 .write_storage::<comp::LightEmitter>()
                 .remove(entity);
-            server.clients.notify(
+            server.notify_client(
                 entity,
                 ServerMsg::private(String::from("You put out the lantern.")),
             );
@@ -727,7 +1203,7 @@ fn handle_lantern(server: &mut Server, entity: EcsEntity, args: String, action:
                 },
             );
 
-        server.clients.notify(
+        server.notify_client(
             entity,
             ServerMsg::private(String::from("You lighted your lantern.")),
         );
@@ -735,7 +1211,7 @@ fn handle_lantern(server: &mut Server, entity: EcsEntity, args: String, action:
 }
 
 fn handle_explosion(server: &mut Server, entity: EcsEntity, args: String, action: &ChatCommand) {
-    let radius = scan_fmt!(&args, action.arg_fmt, f32).unwrap_or(8.0);
+    let radius = scan_fmt!(&args, &action.arg_fmt, f32).unwrap_or(8.0);
 
     match server.state.read_component_cloned::<comp::Pos>(entity) {
         Some(pos) => server
@@ -743,104 +1219,303 @@ fn handle_explosion(server: &mut Server, entity: EcsEntity, args: String, action
             .ecs()
             .read_resource::<EventBus<ServerEvent>>()
             .emit(ServerEvent::Explosion { pos: pos.0, radius }),
-        None => server.clients.notify(
+        None => server.notify_client(
             entity,
             ServerMsg::private(String::from("You have no position!")),
         ),
     }
 }
 
-fn handle_adminify(server: &mut Server, entity: EcsEntity, args: String, action: &ChatCommand) {
-    if let Ok(alias) = scan_fmt!(&args, action.arg_fmt, String) {
-        let ecs = server.state.ecs();
-        let opt_player = (&ecs.entities(), &ecs.read_storage::<comp::Player>())
-            .join()
-            .find(|(_, player)| player.alias == alias)
-            .map(|(entity, _)| entity);
-        match opt_player {
-            Some(player) => match server.state.read_component_cloned::<comp::Admin>(player) {
-                Some(_admin) => {
-                    ecs.write_storage::<comp::Admin>().remove(player);
-                }
-                None => {
-                    server.state.write_component(player, comp::Admin);
+fn handle_role(server: &mut Server, entity: EcsEntity, args: String, action: &ChatCommand) {
+    let (op, alias, role) = scan_fmt_some!(&args, &action.arg_fmt, String, String, String);
+    let alias = alias.map(|alias| server.account_by_name(&alias));
+    let msg = match (op.as_ref().map(String::as_str), alias, role) {
+        (Some("show"), Some(alias), None) => {
+            let roles = server
+                .account_roles
+                .roles_of(&server.server_settings, &alias);
+            if roles.is_empty() {
+                format!("{} has no roles.", alias)
+            } else {
+                format!("{} has the roles: {}", alias, roles.join(", "))
+            }
+        }
+        (Some(op @ "grant"), Some(alias), Some(role))
+        | (Some(op @ "revoke"), Some(alias), Some(role)) => {
+            if !server.server_settings.roles.iter().any(|r| r.name == role) {
+                format!("There is no role called '{}'.", role)
+            } else {
+                let changed = if op == "grant" {
+                    server
+                        .account_roles
+                        .grant(&server.server_settings, &alias, &role)
+                } else {
+                    server
+                        .account_roles
+                        .revoke(&server.server_settings, &alias, &role)
+                };
+                if changed {
+                    server.save_account_roles();
+                    // Build mode can't stay on without the permission to build.
+                    if let Some(player) = server.player_by_account(&alias) {
+                        if !server.entity_has_permission(player, Permission::Build) {
+                            server
+                                .state
+                                .ecs()
+                                .write_storage::<comp::CanBuild>()
+                                .remove(player);
+                        }
+                        let change = if op == "grant" { "were given" } else { "lost" };
+                        server.notify_client(
+                            player,
+                            ServerMsg::private(format!("You {} the role '{}'.", change, role)),
+                        );
+                    }
+                    format!("Updated the roles of {}.", alias)
+                } else if op == "grant" {
+                    format!("{} already has the role '{}'.", alias, role)
+                } else {
+                    format!("{} doesn't have the role '{}'.", alias, role)
                 }
-            },
-            None => {
-                server.clients.notify(
+            }
+        }
+        _ => action.help_string(),
+    };
+    server.notify_client(entity, ServerMsg::private(msg));
+}
+
+fn handle_faction(server: &mut Server, entity: EcsEntity, args: String, action: &ChatCommand) {
+    let mut args = args.trim().splitn(2, ' ');
+    let action_name = args.next().unwrap_or("");
+    let name = args.next().map_or("", str::trim);
+
+    match (action_name, name) {
+        ("create", name) if !name.is_empty() => {
+            let faction = comp::Faction(name.to_owned());
+            if faction_members(server.state.ecs(), &faction).is_empty() {
+                join_faction(server, entity, faction);
+            } else {
+                server.notify_client(
                     entity,
-                    ServerMsg::private(format!("Player '{}' not found!", alias)),
+                    ServerMsg::private(format!(
+                        "The faction '{}' already exists. Ask one of its members to invite you.",
+                        name
+                    )),
                 );
-                server
-                    .clients
-                    .notify(entity, ServerMsg::private(String::from(action.help_string)));
             }
         }
-    } else {
-        server
-            .clients
-            .notify(entity, ServerMsg::private(String::from(action.help_string)));
+        ("invite", alias) if !alias.is_empty() => invite_to_faction(server, entity, alias),
+        ("accept", "") => match server.faction_invites.remove(&entity) {
+            // Factions only exist while somebody is in them.
+            Some(faction) if !faction_members(server.state.ecs(), &faction).is_empty() => {
+                join_faction(server, entity, faction)
+            }
+            _ => server.notify_client(
+                entity,
+                ServerMsg::private(String::from("You haven't been invited to a faction.")),
+            ),
+        },
+        ("leave", "") => {
+            let msg = match leave_faction(server, entity) {
+                Some(comp::Faction(old)) => format!("You left the faction '{}'.", old),
+                None => String::from("You are not in a faction."),
+            };
+            server.notify_client(entity, ServerMsg::private(msg));
+        }
+        _ => server.notify_client(entity, ServerMsg::private(action.help_string())),
+    }
+}
+
+fn invite_to_faction(server: &mut Server, inviter: EcsEntity, alias: &str) {
+    let faction = match server.state.read_component_cloned::<comp::Faction>(inviter) {
+        Some(faction) => faction,
+        None => {
+            return server.notify_client(
+                inviter,
+                ServerMsg::private(String::from("You are not in a faction.")),
+            )
+        }
+    };
+    let msg = match server.player_by_alias(alias) {
+        Some(invitee) if invitee == inviter => String::from("You can't invite yourself."),
+        Some(invitee)
+            if server
+                .state
+                .read_component_cloned::<comp::Faction>(invitee)
+                .as_ref()
+                == Some(&faction) =>
+        {
+            format!("{} is already in your faction.", alias)
+        }
+        Some(invitee) => {
+            let inviter_alias = player_alias(server, inviter);
+            server.notify_client(
+                invitee,
+                ServerMsg::private(format!(
+                    "{} invited you to the faction '{}'. Type '/faction accept' to join it.",
+                    inviter_alias, faction.0
+                )),
+            );
+            let msg = format!("You invited {} to the faction '{}'.", alias, faction.0);
+            server.faction_invites.insert(invitee, faction);
+            msg
+        }
+        None => format!("Player '{}' not found!", alias),
+    };
+    server.notify_client(inviter, ServerMsg::private(msg));
+}
+
+/// Move a player into a faction, out of the one they were in.
+fn join_faction(server: &mut Server, entity: EcsEntity, faction: comp::Faction) {
+    leave_faction(server, entity);
+    let alias = player_alias(server, entity);
+    notify_faction(server, &faction, format!("{} joined the faction.", alias));
+    let msg = format!("You joined the faction '{}'.", faction.0);
+    server.state.write_component(entity, faction);
+    server.notify_client(entity, ServerMsg::private(msg));
+}
+
+/// Take a player out of their faction, telling the members who are left.
+fn leave_faction(server: &mut Server, entity: EcsEntity) -> Option<comp::Faction> {
+    let old = server
+        .state
+        .ecs()
+        .write_storage::<comp::Faction>()
+        .remove(entity);
+    if let Some(old) = &old {
+        let alias = player_alias(server, entity);
+        notify_faction(server, old, format!("{} left the faction.", alias));
+    }
+    old
+}
+
+fn player_alias(server: &Server, entity: EcsEntity) -> String {
+    server
+        .state
+        .read_component_cloned::<comp::Player>(entity)
+        .map(|player| player.alias)
+        .unwrap_or_else(|| String::from("<Unknown>"))
+}
+
+/// Send a message to the players in a faction on the faction channel.
+fn notify_faction(server: &mut Server, faction: &comp::Faction, message: String) {
+    for member in faction_members(server.state.ecs(), faction) {
+        server.notify_client(
+            member,
+            ServerMsg::ChatMsg {
+                chat_type: ChatType::Faction,
+                message: message.clone(),
+            },
+        );
+    }
+}
+
+fn handle_group(server: &mut Server, entity: EcsEntity, args: String, action: &ChatCommand) {
+    let (action_name, alias) = scan_fmt_some!(&args, &action.arg_fmt, String, String);
+    let player = match alias.map(|alias| (server.player_by_alias(&alias), alias)) {
+        Some((Some(player), _)) => Some(player),
+        Some((None, alias)) => {
+            return server.notify_client(
+                entity,
+                ServerMsg::private(format!("Player '{}' not found!", alias)),
+            )
+        }
+        None => None,
+    };
+
+    let handled = group::with_groups(server, |groups| {
+        match (action_name.as_ref().map(String::as_str), player) {
+            (Some("invite"), Some(player)) => groups.invite(entity, player),
+            (Some("kick"), Some(player)) => groups.kick(entity, player),
+            (Some("leader"), Some(player)) => groups.promote(entity, player),
+            (Some("accept"), _) => groups.accept(entity),
+            (Some("decline"), _) => groups.decline(entity),
+            (Some("leave"), _) => groups.leave(entity),
+            _ => return false,
+        }
+        true
+    });
+    if !handled {
+        server.notify_client(entity, ServerMsg::private(action.help_string()));
+    }
+}
+
+fn handle_friend(server: &mut Server, entity: EcsEntity, args: String, action: &ChatCommand) {
+    let request = match scan_fmt!(&args, &action.arg_fmt, String, String) {
+        Ok((action_name, alias)) => match action_name.as_str() {
+            "add" => Some(FriendRequest::Add(alias)),
+            "accept" => Some(FriendRequest::Accept(alias)),
+            "decline" => Some(FriendRequest::Decline(alias)),
+            "remove" => Some(FriendRequest::Remove(alias)),
+            _ => None,
+        },
+        Err(_) => None,
+    };
+    match request {
+        Some(request) => friends::handle_request(server, entity, request),
+        None => server.notify_client(entity, ServerMsg::private(action.help_string())),
     }
 }
 
 fn handle_tell(server: &mut Server, entity: EcsEntity, args: String, action: &ChatCommand) {
-    if let Ok(alias) = scan_fmt!(&args, action.arg_fmt, String) {
-        let ecs = server.state.ecs();
+    if let Ok(alias) = scan_fmt!(&args, &action.arg_fmt, String) {
         let msg = &args[alias.len()..args.len()];
-        if let Some(player) = (&ecs.entities(), &ecs.read_storage::<comp::Player>())
-            .join()
-            .find(|(_, player)| player.alias == alias)
-            .map(|(entity, _)| entity)
-        {
-            if player != entity {
-                if msg.len() > 1 {
-                    if let Some(name) = ecs
-                        .read_storage::<comp::Player>()
-                        .get(entity)
-                        .map(|s| s.alias.clone())
-                    {
-                        server.clients.notify(
-                            player,
-                            ServerMsg::tell(format!("[{}] tells you:{}", name, msg)),
-                        );
-                        server.clients.notify(
-                            entity,
-                            ServerMsg::tell(format!("You tell [{}]:{}", alias, msg)),
-                        );
-                    } else {
-                        server.clients.notify(
-                            entity,
-                            ServerMsg::private(String::from("Failed to send message.")),
-                        );
-                    }
+        tell(server, entity, &alias, msg.trim_start());
+    } else {
+        server.notify_client(entity, ServerMsg::private(action.help_string()));
+    }
+}
+
+/// Send a private message to a player, wherever they are.
+pub fn tell(server: &mut Server, entity: EcsEntity, alias: &str, msg: &str) {
+    if let Some(mute) = server.mute_message(entity) {
+        return server.notify_client(entity, ServerMsg::private(mute));
+    }
+    if let Some(player) = server.player_by_name(alias) {
+        if player != entity {
+            if !msg.is_empty() {
+                if let Some(name) = server
+                    .state
+                    .read_component_cloned::<comp::Player>(entity)
+                    .map(|s| s.alias)
+                {
+                    server.notify_client(
+                        player,
+                        ServerMsg::tell(format!("[{}] tells you: {}", name, msg)),
+                    );
+                    server.notify_client(
+                        entity,
+                        ServerMsg::tell(format!("You tell [{}]: {}", alias, msg)),
+                    );
                 } else {
-                    server.clients.notify(
+                    server.notify_client(
                         entity,
-                        ServerMsg::private(format!("[{}] wants to talk to you.", alias)),
+                        ServerMsg::private(String::from("Failed to send message.")),
                     );
                 }
             } else {
-                server.clients.notify(
+                server.notify_client(
                     entity,
-                    ServerMsg::private(format!("You can't /tell yourself.")),
+                    ServerMsg::private(format!("[{}] wants to talk to you.", alias)),
                 );
             }
         } else {
-            server.clients.notify(
+            server.notify_client(
                 entity,
-                ServerMsg::private(format!("Player '{}' not found!", alias)),
+                ServerMsg::private(format!("You can't /tell yourself.")),
             );
         }
     } else {
-        server
-            .clients
-            .notify(entity, ServerMsg::private(String::from(action.help_string)));
+        server.notify_client(
+            entity,
+            ServerMsg::private(format!("Player '{}' not found!", alias)),
+        );
     }
 }
 
 fn handle_debug_column(server: &mut Server, entity: EcsEntity, args: String, action: &ChatCommand) {
     let sim = server.world.sim();
-    if let Ok((x, y)) = scan_fmt!(&args, action.arg_fmt, i32, i32) {
+    if let Ok((x, y)) = scan_fmt!(&args, &action.arg_fmt, i32, i32) {
         let wpos = Vec2::new(x, y);
         /* let chunk_pos = wpos.map2(Vec2::from(TerrainChunkSize::SIZE), |e, sz: u32| {
             e / sz as i32
@@ -856,6 +1531,9 @@ fn handle_debug_column(server: &mut Server, entity: EcsEntity, args: String, act
             let rockiness = sim.get_interpolated(wpos, |chunk| chunk.rockiness)?;
             let tree_density = sim.get_interpolated(wpos, |chunk| chunk.tree_density)?;
             let spawn_rate = sim.get_interpolated(wpos, |chunk| chunk.spawn_rate)?;
+            let biome = sim
+                .get(server.state.terrain().pos_key(wpos.into()))?
+                .get_biome(sim.opts());
 
             Some(format!(
                 r#"wpos: {:?}
@@ -866,21 +1544,306 @@ temp {:?}
 humidity {:?}
 rockiness {:?}
 tree_density {:?}
-spawn_rate {:?} "#,
-                wpos, alt_base, alt, chaos, temp, humidity, rockiness, tree_density, spawn_rate
+spawn_rate {:?}
+biome {:?} ({}°C) "#,
+                wpos,
+                alt_base,
+                alt,
+                chaos,
+                temp,
+                humidity,
+                rockiness,
+                tree_density,
+                spawn_rate,
+                biome,
+                biome.temperature()
             ))
         };
         if let Some(s) = foo() {
-            server.clients.notify(entity, ServerMsg::private(s));
+            server.notify_client(entity, ServerMsg::private(s));
         } else {
-            server.clients.notify(
+            server.notify_client(
                 entity,
                 ServerMsg::private(String::from("Not a pregenerated chunk.")),
             );
         }
     } else {
-        server
-            .clients
-            .notify(entity, ServerMsg::private(String::from(action.help_string)));
+        server.notify_client(entity, ServerMsg::private(action.help_string()));
+    }
+}
+
+/// Parse a duration such as `30m` or `7d` (units are s, m, h, d and w).
+fn parse_duration(s: &str) -> Option<Duration> {
+    if s.len() < 2 || !s.is_char_boundary(s.len() - 1) {
+        return None;
+    }
+    let (amount, unit) = s.split_at(s.len() - 1);
+    let amount = amount.parse::<u64>().ok()?;
+    let unit_secs = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return None,
+    };
+    amount.checked_mul(unit_secs).map(Duration::from_secs)
+}
+
+/// Split the arguments of a moderation command into its target, an optional duration and the
+/// reason.
+fn parse_moderation_args(args: &str) -> Option<(String, Option<Duration>, String)> {
+    let mut words = args.split_whitespace();
+    let target = words.next()?.to_owned();
+    let mut rest = words.collect::<Vec<_>>();
+    let duration = rest.first().and_then(|word| parse_duration(word));
+    if duration.is_some() {
+        rest.remove(0);
+    }
+    let reason = if rest.is_empty() {
+        String::from("No reason given")
+    } else {
+        rest.join(" ")
+    };
+    Some((target, duration, reason))
+}
+
+fn handle_kick(server: &mut Server, entity: EcsEntity, args: String, action: &ChatCommand) {
+    let mut args = args.trim().splitn(2, ' ');
+    match args.next().filter(|alias| !alias.is_empty()) {
+        Some(alias) => match server.player_by_alias(alias) {
+            Some(player) => {
+                let reason = args
+                    .next()
+                    .map(str::trim)
+                    .filter(|reason| !reason.is_empty())
+                    .unwrap_or("No reason given");
+                server.kick_player(player, reason);
+                server.notify_client(entity, ServerMsg::private(format!("Kicked {}.", alias)));
+            }
+            None => server.notify_client(
+                entity,
+                ServerMsg::private(format!("Player '{}' not found!", alias)),
+            ),
+        },
+        None => server.notify_client(entity, ServerMsg::private(action.help_string())),
+    }
+}
+
+fn handle_ban(server: &mut Server, entity: EcsEntity, args: String, action: &ChatCommand) {
+    match parse_moderation_args(&args) {
+        Some((alias, duration, reason)) => {
+            let account = server.account_by_name(&alias);
+            let msg = server.ban(BanTarget::Account(account), reason, duration);
+            server.notify_client(entity, ServerMsg::private(msg));
+        }
+        None => server.notify_client(entity, ServerMsg::private(action.help_string())),
+    }
+}
+
+fn handle_banip(server: &mut Server, entity: EcsEntity, args: String, action: &ChatCommand) {
+    match parse_moderation_args(&args) {
+        Some((target, duration, reason)) => {
+            let ip = target.parse::<IpAddr>().ok().or_else(|| {
+                server
+                    .player_by_alias(&target)
+                    .and_then(|player| server.clients.get(&player))
+                    .and_then(|client| client.ip())
+            });
+            match ip {
+                Some(ip) => {
+                    let msg = server.ban(BanTarget::Ip(ip), reason, duration);
+                    server.notify_client(entity, ServerMsg::private(msg));
+                }
+                None => server.notify_client(
+                    entity,
+                    ServerMsg::private(format!("No address found for '{}'!", target)),
+                ),
+            }
+        }
+        None => server.notify_client(entity, ServerMsg::private(action.help_string())),
+    }
+}
+
+fn handle_unban(server: &mut Server, entity: EcsEntity, args: String, action: &ChatCommand) {
+    if let Ok(target) = scan_fmt!(&args, &action.arg_fmt, String) {
+        let target = match target.parse::<IpAddr>() {
+            Ok(ip) => BanTarget::Ip(ip),
+            Err(_) => BanTarget::Account(target),
+        };
+        let msg = if server.banlist.unban(&target) {
+            server.save_banlist();
+            format!("Unbanned {}.", target)
+        } else {
+            format!("{} is not banned.", target)
+        };
+        server.notify_client(entity, ServerMsg::private(msg));
+    } else {
+        server.notify_client(entity, ServerMsg::private(action.help_string()));
+    }
+}
+
+fn handle_mute(server: &mut Server, entity: EcsEntity, args: String, action: &ChatCommand) {
+    match parse_moderation_args(&args) {
+        Some((alias, duration, reason)) => {
+            let alias = server.account_by_name(&alias);
+            let mute = server.banlist.mute(alias.clone(), reason, duration).clone();
+            server.save_banlist();
+            if let Some(player) = server.player_by_account(&alias) {
+                server.notify_client(player, ServerMsg::private(mute.message()));
+            }
+            server.notify_client(
+                entity,
+                ServerMsg::private(format!(
+                    "Muted {} {}: {}",
+                    alias,
+                    banlist::describe_until(mute.until),
+                    mute.reason
+                )),
+            );
+        }
+        None => server.notify_client(entity, ServerMsg::private(action.help_string())),
+    }
+}
+
+fn handle_unmute(server: &mut Server, entity: EcsEntity, args: String, action: &ChatCommand) {
+    if let Ok(alias) = scan_fmt!(&args, &action.arg_fmt, String) {
+        let alias = server.account_by_name(&alias);
+        let msg = if server.banlist.unmute(&alias) {
+            server.save_banlist();
+            if let Some(player) = server.player_by_account(&alias) {
+                server.notify_client(
+                    player,
+                    ServerMsg::private(String::from("You can chat again.")),
+                );
+            }
+            format!("Unmuted {}.", alias)
+        } else {
+            format!("{} is not muted.", alias)
+        };
+        server.notify_client(entity, ServerMsg::private(msg));
+    } else {
+        server.notify_client(entity, ServerMsg::private(action.help_string()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(keyword: &str) -> &'static ChatCommand {
+        CHAT_COMMANDS
+            .iter()
+            .find(|cmd| cmd.keyword == keyword)
+            .unwrap()
+    }
+
+    #[test]
+    fn integers_are_checked_against_their_range() {
+        let health = command("health");
+        assert!(health.check_args("100").is_ok());
+        assert!(health.check_args("").is_err());
+        assert!(health.check_args("lots").is_err());
+        assert!(health.check_args("-1").is_err());
+        assert!(health.check_args("4294967296").is_err());
+        assert!(health.check_args("100 5").is_err());
+
+        let debug_column = command("debug_column");
+        assert!(debug_column.check_args("-2147483648 2147483647").is_ok());
+        assert!(debug_column.check_args("0 2147483648").is_err());
+    }
+
+    #[test]
+    fn arguments_are_checked() {
+        let jump = command("jump");
+        assert!(jump.check_args("1 -2 3.5").is_ok());
+        assert!(jump.check_args("1 2").is_err());
+        assert!(jump.check_args("1 up 3").is_err());
+
+        let spawn = command("spawn");
+        assert!(spawn.check_args("hostile wolf").is_ok());
+        assert!(spawn.check_args("hostile wolf 3").is_ok());
+        assert!(spawn.check_args("hostile dragon").is_err());
+        assert!(spawn.check_args("hostile wolf 11").is_err());
+
+        // Messages take the rest of the line
+        let tell = command("tell");
+        assert!(tell.check_args("Pfau hello there").is_ok());
+        assert!(tell.check_args("Pfau").is_ok());
+        assert!(tell.check_args("").is_err());
+    }
+
+    #[test]
+    fn completes_commands_and_arguments() {
+        let aliases = vec!["Pfau".to_owned(), "Other".to_owned()];
+        let complete = |line: &str| complete_line(line, &aliases, |_| true);
+
+        assert_eq!(complete("/hea"), vec!["/health"]);
+        assert_eq!(complete("/spawn hostile w"), vec!["/spawn hostile wolf"]);
+        assert_eq!(complete("/tp P"), vec!["/tp Pfau"]);
+        assert!(complete("/tp Pfau ").is_empty());
+        assert!(complete("hea").is_empty());
+
+        // Commands that aren't available aren't offered
+        let without_jump = complete_line("/ju", &aliases, |cmd| cmd.keyword != "jump");
+        assert!(without_jump.is_empty());
+    }
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("30s"), Some(Duration::from_secs(30)));
+        assert_eq!(parse_duration("30m"), Some(Duration::from_secs(30 * 60)));
+        assert_eq!(
+            parse_duration("7d"),
+            Some(Duration::from_secs(7 * 24 * 60 * 60))
+        );
+        assert_eq!(
+            parse_duration("1w"),
+            Some(Duration::from_secs(7 * 24 * 60 * 60))
+        );
+        assert_eq!(parse_duration("30"), None);
+        assert_eq!(parse_duration("m"), None);
+        assert_eq!(parse_duration("5y"), None);
+        assert_eq!(parse_duration("5é"), None);
+        assert_eq!(parse_duration("18446744073709551615w"), None);
+    }
+
+    #[test]
+    fn parses_moderation_args() {
+        assert_eq!(
+            parse_moderation_args("Griefer 2d burned the town"),
+            Some((
+                "Griefer".to_owned(),
+                Some(Duration::from_secs(2 * 24 * 60 * 60)),
+                "burned the town".to_owned()
+            ))
+        );
+        assert_eq!(
+            parse_moderation_args("Griefer spamming"),
+            Some(("Griefer".to_owned(), None, "spamming".to_owned()))
+        );
+        assert_eq!(
+            parse_moderation_args("Griefer 30m"),
+            Some((
+                "Griefer".to_owned(),
+                Some(Duration::from_secs(30 * 60)),
+                "No reason given".to_owned()
+            ))
+        );
+        assert_eq!(parse_moderation_args("  "), None);
+    }
+
+    #[test]
+    fn parses_times() {
+        assert_eq!(parse_time("dawn"), Some(NaiveTime::from_hms(5, 0, 0)));
+        assert_eq!(parse_time("13:30"), Some(NaiveTime::from_hms(13, 30, 0)));
+        assert_eq!(
+            parse_time("13:30:15"),
+            Some(NaiveTime::from_hms(13, 30, 15))
+        );
+        assert_eq!(parse_time("3600"), Some(NaiveTime::from_hms(1, 0, 0)));
+        assert_eq!(parse_time("86400"), None);
+        assert_eq!(parse_time("25:00"), None);
+        assert_eq!(parse_time("noon"), None);
     }
 }
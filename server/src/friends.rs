@@ -0,0 +1,393 @@
+//! Friends lists and friend requests, which are kept in their own RON file so that they survive
+//! restarts. Friendship is mutual and accounts are referred to by the name they registered with,
+//! not the alias they may have changed to since.
+
+use crate::Server;
+use common::{
+    comp,
+    msg::{FriendInfo, FriendRequest, ServerMsg},
+};
+use hashbrown::HashMap;
+use serde_derive::{Deserialize, Serialize};
+use specs::Entity as EcsEntity;
+use std::{fs, io::prelude::*, path::Path};
+
+/// The most requests an account can have waiting for an answer at once.
+pub const MAX_SENT_REQUESTS: usize = 20;
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FriendLists {
+    /// The friends of each account.
+    pub friends: HashMap<String, Vec<String>>,
+    /// Requests waiting for an answer, from the account that was asked to the accounts that
+    /// asked.
+    pub requests: HashMap<String, Vec<String>>,
+}
+
+impl FriendLists {
+    pub fn load(path: &Path) -> Self {
+        match fs::File::open(path) {
+            Ok(file) => match ron::de::from_reader(file) {
+                Ok(friends) => friends,
+                Err(e) => {
+                    log::warn!("Failed to parse the friends lists! Ignoring them. {}", e);
+                    Self::default()
+                }
+            },
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let mut file = fs::File::create(path)?;
+
+        let s: &str = &ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()).unwrap();
+        file.write_all(s.as_bytes())
+    }
+
+    pub fn friends_of(&self, account: &str) -> &[String] {
+        self.friends
+            .get(account)
+            .map_or(&[], |friends| friends.as_slice())
+    }
+
+    /// The accounts that asked to be friends with `account`.
+    pub fn requests_for(&self, account: &str) -> &[String] {
+        self.requests
+            .get(account)
+            .map_or(&[], |requests| requests.as_slice())
+    }
+
+    pub fn are_friends(&self, a: &str, b: &str) -> bool {
+        self.friends_of(a).iter().any(|friend| friend == b)
+    }
+
+    /// Whether `account` has ever had friends, which means it exists even if it hasn't logged
+    /// in since the server started.
+    pub fn knows(&self, account: &str) -> bool {
+        self.friends.contains_key(account)
+    }
+
+    /// How many of the requests `account` made are waiting for an answer.
+    pub fn requests_from(&self, account: &str) -> usize {
+        self.requests
+            .values()
+            .filter(|requests| requests.iter().any(|r| r == account))
+            .count()
+    }
+
+    /// Ask `to` to be friends with `from`, returning whether the request is new.
+    pub fn request(&mut self, from: &str, to: &str) -> bool {
+        let requests = self.requests.entry(to.to_owned()).or_default();
+        if requests.iter().any(|r| r == from) {
+            return false;
+        }
+        requests.push(from.to_owned());
+        true
+    }
+
+    /// Forget a request, returning whether there was one.
+    pub fn remove_request(&mut self, from: &str, to: &str) -> bool {
+        let requests = match self.requests.get_mut(to) {
+            Some(requests) => requests,
+            None => return false,
+        };
+        let len = requests.len();
+        requests.retain(|r| r != from);
+        let removed = requests.len() != len;
+        if requests.is_empty() {
+            self.requests.remove(to);
+        }
+        removed
+    }
+
+    /// Accept a request from `other`, making them friends with `account`. Returns whether there
+    /// was a request to accept.
+    pub fn accept(&mut self, account: &str, other: &str) -> bool {
+        if !self.remove_request(other, account) {
+            return false;
+        }
+        // They might have asked each other.
+        self.remove_request(account, other);
+        self.add(account, other);
+        true
+    }
+
+    pub fn add(&mut self, a: &str, b: &str) {
+        for (account, friend) in &[(a, b), (b, a)] {
+            let friends = self.friends.entry((*account).to_owned()).or_default();
+            if !friends.iter().any(|f| f == friend) {
+                friends.push((*friend).to_owned());
+            }
+        }
+    }
+
+    /// End a friendship, returning whether there was one.
+    pub fn remove(&mut self, a: &str, b: &str) -> bool {
+        let mut removed = false;
+        for (account, friend) in &[(a, b), (b, a)] {
+            if let Some(friends) = self.friends.get_mut(*account) {
+                let len = friends.len();
+                friends.retain(|f| f != friend);
+                removed |= friends.len() != len;
+            }
+        }
+        removed
+    }
+}
+
+pub fn handle_request(server: &mut Server, entity: EcsEntity, request: FriendRequest) {
+    let account = match account(server, entity) {
+        Some(account) => account,
+        None => return,
+    };
+
+    match request {
+        FriendRequest::Add(other) => {
+            let other = server.account_by_name(&other);
+            add(server, entity, &account, &other)
+        }
+        FriendRequest::Accept(other) => {
+            let other = server.account_by_name(&other);
+            accept(server, entity, &account, &other)
+        }
+        FriendRequest::Decline(other) => {
+            let other = server.account_by_name(&other);
+            if server.friend_lists.remove_request(&other, &account) {
+                save(server);
+                notify(server, entity, format!("You declined {}'s request.", other));
+                send_list(server, entity);
+            } else {
+                notify(
+                    server,
+                    entity,
+                    format!("{} hasn't asked to be your friend.", other),
+                );
+            }
+        }
+        FriendRequest::Remove(other) => {
+            let other = server.account_by_name(&other);
+            if server.friend_lists.remove(&account, &other) {
+                save(server);
+                notify(
+                    server,
+                    entity,
+                    format!("{} is no longer your friend.", other),
+                );
+                send_list(server, entity);
+                if let Some(other) = server.player_by_account(&other) {
+                    send_list(server, other);
+                }
+            } else {
+                notify(server, entity, format!("{} is not your friend.", other));
+            }
+        }
+    }
+}
+
+fn add(server: &mut Server, entity: EcsEntity, account: &str, other: &str) {
+    if account == other {
+        return notify(server, entity, "You can't befriend yourself.".to_owned());
+    }
+    if server.friend_lists.are_friends(account, other) {
+        return notify(server, entity, format!("{} is already your friend.", other));
+    }
+    let exists = server.player_by_account(other).is_some()
+        || server.accounts.exists(other)
+        || server.friend_lists.knows(other);
+    if !exists {
+        return notify(
+            server,
+            entity,
+            format!("There is no player called {}.", other),
+        );
+    }
+    // Asking someone who already asked us is the same as accepting.
+    if server
+        .friend_lists
+        .requests_for(account)
+        .iter()
+        .any(|r| r == other)
+    {
+        return accept(server, entity, account, other);
+    }
+    if server.friend_lists.requests_from(account) >= MAX_SENT_REQUESTS {
+        return notify(
+            server,
+            entity,
+            "You have too many friend requests waiting for an answer. \
+             Wait for them to be answered before asking anyone else."
+                .to_owned(),
+        );
+    }
+    if server.friend_lists.request(account, other) {
+        save(server);
+    }
+    notify(
+        server,
+        entity,
+        format!("You asked {} to be your friend.", other),
+    );
+    if let Some(other) = server.player_by_account(other) {
+        notify(
+            server,
+            other,
+            format!(
+                "{} wants to be your friend. Type '/friend accept {}' to accept.",
+                account, account
+            ),
+        );
+        send_list(server, other);
+    }
+}
+
+fn accept(server: &mut Server, entity: EcsEntity, account: &str, other: &str) {
+    if !server.friend_lists.accept(account, other) {
+        return notify(
+            server,
+            entity,
+            format!("{} hasn't asked to be your friend.", other),
+        );
+    }
+    save(server);
+
+    notify(server, entity, format!("{} is now your friend.", other));
+    send_list(server, entity);
+    if let Some(other) = server.player_by_account(other) {
+        notify(server, other, format!("{} is now your friend.", account));
+        send_list(server, other);
+    }
+}
+
+/// Send a player who just registered their friends list and tell their friends they came online.
+pub fn player_online(server: &mut Server, entity: EcsEntity) {
+    send_list(server, entity);
+    if let Some(account) = account(server, entity) {
+        announce_presence(server, &account, "is now online");
+    }
+}
+
+/// Tell the friends of a player whose entity was removed that they went offline.
+pub fn player_offline(server: &mut Server, account: &str) {
+    announce_presence(server, account, "went offline");
+}
+
+fn announce_presence(server: &mut Server, account: &str, presence: &str) {
+    let online_friends = server
+        .friend_lists
+        .friends_of(account)
+        .iter()
+        .filter_map(|friend| server.player_by_account(friend))
+        .collect::<Vec<_>>();
+    for friend in online_friends {
+        notify(
+            server,
+            friend,
+            format!("Your friend {} {}.", account, presence),
+        );
+        send_list(server, friend);
+    }
+}
+
+/// Send a player their friends, whether each is online, and the requests waiting for them.
+fn send_list(server: &mut Server, entity: EcsEntity) {
+    let account = match account(server, entity) {
+        Some(account) => account,
+        None => return,
+    };
+    let friends = server
+        .friend_lists
+        .friends_of(&account)
+        .iter()
+        .map(|friend| FriendInfo {
+            account: friend.clone(),
+            alias: server.player_by_account(friend).and_then(|player| {
+                server
+                    .state
+                    .read_component_cloned::<comp::Player>(player)
+                    .map(|player| player.alias)
+            }),
+        })
+        .collect();
+    let requests = server.friend_lists.requests_for(&account).to_vec();
+    server.notify_client(entity, ServerMsg::Friends { friends, requests });
+}
+
+fn save(server: &mut Server) {
+    if let Err(err) = server
+        .friend_lists
+        .save(&server.server_settings.friends_file)
+    {
+        log::warn!("Failed to save the friends lists: {}", err);
+    }
+}
+
+fn account(server: &Server, entity: EcsEntity) -> Option<String> {
+    server.account_of(entity).map(str::to_owned)
+}
+
+fn notify(server: &mut Server, entity: EcsEntity, msg: String) {
+    server.notify_client(entity, ServerMsg::private(msg));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn friendship_is_mutual() {
+        let mut lists = FriendLists::default();
+        lists.add("a", "b");
+        lists.add("b", "a");
+        assert_eq!(lists.friends_of("a"), ["b".to_owned()]);
+        assert_eq!(lists.friends_of("b"), ["a".to_owned()]);
+        assert!(lists.knows("a") && !lists.knows("c"));
+
+        assert!(lists.remove("b", "a"));
+        assert!(!lists.are_friends("a", "b"));
+        assert!(!lists.are_friends("b", "a"));
+        assert!(!lists.remove("a", "b"));
+    }
+
+    #[test]
+    fn requests_are_accepted_once() {
+        let mut lists = FriendLists::default();
+        assert!(lists.request("a", "b"));
+        assert!(!lists.request("a", "b"));
+        assert_eq!(lists.requests_for("b"), ["a".to_owned()]);
+
+        assert!(!lists.accept("a", "b"));
+        assert!(lists.accept("b", "a"));
+        assert!(lists.are_friends("a", "b"));
+        assert!(lists.requests.is_empty());
+        assert!(!lists.accept("b", "a"));
+    }
+
+    #[test]
+    fn crossed_requests_are_both_answered() {
+        let mut lists = FriendLists::default();
+        lists.request("a", "b");
+        lists.request("b", "a");
+        assert_eq!(lists.requests_from("a"), 1);
+
+        assert!(lists.accept("a", "b"));
+        assert!(lists.are_friends("a", "b"));
+        assert!(lists.requests.is_empty());
+        assert_eq!(lists.requests_from("a"), 0);
+    }
+
+    #[test]
+    fn declined_requests_are_forgotten() {
+        let mut lists = FriendLists::default();
+        for i in 0..3 {
+            lists.request("a", &format!("friend{}", i));
+        }
+        assert_eq!(lists.requests_from("a"), 3);
+
+        assert!(lists.remove_request("a", "friend1"));
+        assert!(!lists.remove_request("a", "friend1"));
+        assert_eq!(lists.requests_from("a"), 2);
+        assert!(!lists.requests.contains_key("friend1"));
+    }
+}
@@ -0,0 +1,587 @@
+//! Groups of players, who share a chat channel and the exp from their kills.
+//!
+//! A group is identified by its leader: every member, the leader included, has a `comp::Group`
+//! pointing at the leader's uid.
+
+use crate::Server;
+use common::{
+    comp,
+    msg::{GroupInfo, GroupRequest, ServerMsg},
+    state::State,
+};
+use hashbrown::HashMap;
+use specs::{Entity as EcsEntity, Join};
+
+/// The most players a group can have.
+pub const MAX_GROUP_SIZE: usize = 6;
+/// How far, in blocks, members can be from the killer and still get a share of the exp.
+const EXP_SHARE_RADIUS: f32 = 128.0;
+
+/// The parts of the server that groups are kept in. Messages for players are collected rather
+/// than sent, so that groups can be managed without a running server.
+pub struct Groups<'a> {
+    state: &'a mut State,
+    /// Invites waiting for an answer, from the invitee to the inviter.
+    invites: &'a mut HashMap<EcsEntity, EcsEntity>,
+    messages: Vec<(EcsEntity, ServerMsg)>,
+}
+
+/// Change the server's groups, then send the messages that produced.
+pub fn with_groups<R>(server: &mut Server, f: impl FnOnce(&mut Groups) -> R) -> R {
+    let mut groups = Groups::new(&mut server.state, &mut server.group_invites);
+    let result = f(&mut groups);
+    for (entity, msg) in groups.messages {
+        server.notify_client(entity, msg);
+    }
+    result
+}
+
+pub fn handle_request(server: &mut Server, entity: EcsEntity, request: GroupRequest) {
+    with_groups(server, |groups| groups.handle_request(entity, request));
+}
+
+/// Take a player out of their group and forget their invites, e.g. when they go offline.
+pub fn forget_player(server: &mut Server, entity: EcsEntity) {
+    with_groups(server, |groups| groups.forget_player(entity));
+}
+
+/// The entities that share the exp for a kill: the killer and the members of its group nearby,
+/// except for the victim if the killer turned on their own group.
+pub fn exp_recipients(ecs: &specs::World, killer: EcsEntity, victim: EcsEntity) -> Vec<EcsEntity> {
+    let groups = ecs.read_storage::<comp::Group>();
+    let positions = ecs.read_storage::<comp::Pos>();
+    let recipients = match (groups.get(killer), positions.get(killer)) {
+        (Some(group), Some(killer_pos)) => (&ecs.entities(), &groups, &positions)
+            .join()
+            .filter(|(member, other, pos)| {
+                *member != victim
+                    && *other == group
+                    && pos.0.distance_squared(killer_pos.0) <= EXP_SHARE_RADIUS.powi(2)
+            })
+            .map(|(member, _, _)| member)
+            .collect(),
+        _ => vec![killer],
+    };
+    recipients
+}
+
+/// Split the exp for a kill evenly between its recipients. Shares are rounded down and the
+/// killer gets what is left over, so no more exp is handed out than the kill was worth.
+pub fn exp_shares(exp: i64, killer: EcsEntity, recipients: &[EcsEntity]) -> Vec<(EcsEntity, i64)> {
+    let count = recipients.len().max(1) as i64;
+    recipients
+        .iter()
+        .map(|recipient| {
+            let remainder = if *recipient == killer { exp % count } else { 0 };
+            (*recipient, exp / count + remainder)
+        })
+        .collect()
+}
+
+impl<'a> Groups<'a> {
+    pub fn new(state: &'a mut State, invites: &'a mut HashMap<EcsEntity, EcsEntity>) -> Self {
+        Self {
+            state,
+            invites,
+            messages: Vec::new(),
+        }
+    }
+
+    pub fn handle_request(&mut self, entity: EcsEntity, request: GroupRequest) {
+        let player = |groups: &mut Self, uid: u64| {
+            let player = groups.state.ecs().entity_from_uid(uid).filter(|player| {
+                groups
+                    .state
+                    .read_storage::<comp::Player>()
+                    .get(*player)
+                    .is_some()
+            });
+            if player.is_none() {
+                groups.notify(entity, "That player isn't online.".to_owned());
+            }
+            player
+        };
+
+        match request {
+            GroupRequest::Invite(uid) => {
+                if let Some(invitee) = player(self, uid) {
+                    self.invite(entity, invitee);
+                }
+            }
+            GroupRequest::Accept => self.accept(entity),
+            GroupRequest::Decline => self.decline(entity),
+            GroupRequest::Kick(uid) => {
+                if let Some(member) = player(self, uid) {
+                    self.kick(entity, member);
+                }
+            }
+            GroupRequest::Leave => self.leave(entity),
+            GroupRequest::Promote(uid) => {
+                if let Some(member) = player(self, uid) {
+                    self.promote(entity, member);
+                }
+            }
+        }
+    }
+
+    pub fn invite(&mut self, inviter: EcsEntity, invitee: EcsEntity) {
+        if inviter == invitee {
+            return self.notify(inviter, "You can't invite yourself.".to_owned());
+        }
+        if let Some(group) = self.group_of(inviter) {
+            if Some(group.leader) != self.state.ecs().uid_from_entity(inviter) {
+                return self.notify(
+                    inviter,
+                    "Only the leader of your group can invite players.".to_owned(),
+                );
+            }
+            if self.members(group).len() >= MAX_GROUP_SIZE {
+                return self.notify(inviter, "Your group is full.".to_owned());
+            }
+        }
+        let invitee_alias = self.alias(invitee);
+        if self.group_of(invitee).is_some() {
+            return self.notify(inviter, format!("{} is already in a group.", invitee_alias));
+        }
+        let inviter_uid = match self.state.ecs().uid_from_entity(inviter) {
+            Some(uid) => uid.into(),
+            None => return,
+        };
+
+        self.invites.insert(invitee, inviter);
+        let inviter_alias = self.alias(inviter);
+        self.messages.push((
+            invitee,
+            ServerMsg::GroupInvite {
+                inviter: inviter_uid,
+                alias: inviter_alias.clone(),
+            },
+        ));
+        self.notify(
+            invitee,
+            format!(
+                "{} invited you to their group. Type '/group accept' to join it.",
+                inviter_alias
+            ),
+        );
+        self.notify(
+            inviter,
+            format!("You invited {} to your group.", invitee_alias),
+        );
+    }
+
+    pub fn accept(&mut self, entity: EcsEntity) {
+        let inviter = match self.invites.remove(&entity) {
+            Some(inviter) if self.state.ecs().is_alive(inviter) => inviter,
+            _ => return self.notify(entity, "You haven't been invited to a group.".to_owned()),
+        };
+        if self.group_of(entity).is_some() {
+            return self.notify(entity, "You are already in a group.".to_owned());
+        }
+
+        // The inviter leads a new group if they weren't in one.
+        let group = match self.group_of(inviter) {
+            Some(group) => group,
+            None => match self.state.ecs().uid_from_entity(inviter) {
+                Some(leader) => {
+                    let group = comp::Group { leader };
+                    self.state.write_component(inviter, group);
+                    group
+                }
+                None => return,
+            },
+        };
+        if self.members(group).len() >= MAX_GROUP_SIZE {
+            return self.notify(entity, "That group is full.".to_owned());
+        }
+
+        self.state.write_component(entity, group);
+        let msg = format!("{} joined the group.", self.alias(entity));
+        self.notify_members(group, msg);
+        self.send_updates(group);
+    }
+
+    pub fn decline(&mut self, entity: EcsEntity) {
+        match self.invites.remove(&entity) {
+            Some(inviter) => {
+                let msg = format!("{} declined your invite.", self.alias(entity));
+                self.notify(inviter, msg);
+                self.notify(entity, "You declined the invite.".to_owned());
+            }
+            None => self.notify(entity, "You haven't been invited to a group.".to_owned()),
+        }
+    }
+
+    pub fn leave(&mut self, entity: EcsEntity) {
+        if self.group_of(entity).is_none() {
+            return self.notify(entity, "You are not in a group.".to_owned());
+        }
+        self.remove_member(entity, "left the group");
+        self.notify(entity, "You left the group.".to_owned());
+    }
+
+    pub fn kick(&mut self, leader: EcsEntity, member: EcsEntity) {
+        let group = match self.led_group(leader) {
+            Some(group) => group,
+            None => return,
+        };
+        if leader == member {
+            return self.notify(leader, "Use '/group leave' to leave the group.".to_owned());
+        }
+        if self.group_of(member) != Some(group) {
+            let msg = format!("{} is not in your group.", self.alias(member));
+            return self.notify(leader, msg);
+        }
+
+        self.remove_member(member, "was removed from the group");
+        self.notify(member, "You were removed from the group.".to_owned());
+    }
+
+    pub fn promote(&mut self, leader: EcsEntity, member: EcsEntity) {
+        let group = match self.led_group(leader) {
+            Some(group) => group,
+            None => return,
+        };
+        if self.group_of(member) != Some(group) {
+            let msg = format!("{} is not in your group.", self.alias(member));
+            return self.notify(leader, msg);
+        }
+        let new_group = match self.state.ecs().uid_from_entity(member) {
+            Some(uid) => comp::Group { leader: uid },
+            None => return,
+        };
+
+        for member in self.members(group) {
+            self.state.write_component(member, new_group);
+        }
+        let msg = format!("{} now leads the group.", self.alias(member));
+        self.notify_members(new_group, msg);
+        self.send_updates(new_group);
+    }
+
+    pub fn forget_player(&mut self, entity: EcsEntity) {
+        self.invites
+            .retain(|invitee, inviter| *invitee != entity && *inviter != entity);
+        if self.group_of(entity).is_some() {
+            self.remove_member(entity, "went offline");
+        }
+    }
+
+    /// Remove a member from its group, handing the lead over if it led the group and disbanding
+    /// the group if only one member is left.
+    fn remove_member(&mut self, entity: EcsEntity, announcement: &str) {
+        let removed = self
+            .state
+            .ecs()
+            .write_storage::<comp::Group>()
+            .remove(entity);
+        let group = match removed {
+            Some(group) => group,
+            None => return,
+        };
+        self.messages.push((entity, ServerMsg::GroupUpdate(None)));
+
+        let remaining = self.members(group);
+        if remaining.len() <= 1 {
+            for member in remaining {
+                self.state
+                    .ecs()
+                    .write_storage::<comp::Group>()
+                    .remove(member);
+                self.messages.push((member, ServerMsg::GroupUpdate(None)));
+                self.notify(member, "Your group was disbanded.".to_owned());
+            }
+            return;
+        }
+
+        let msg = format!("{} {}.", self.alias(entity), announcement);
+        self.notify_members(group, msg);
+
+        let group = if Some(group.leader) == self.state.ecs().uid_from_entity(entity) {
+            let new_leader = remaining[0];
+            let new_group = match self.state.ecs().uid_from_entity(new_leader) {
+                Some(uid) => comp::Group { leader: uid },
+                None => return,
+            };
+            for member in &remaining {
+                self.state.write_component(*member, new_group);
+            }
+            let msg = format!("{} now leads the group.", self.alias(new_leader));
+            self.notify_members(new_group, msg);
+            new_group
+        } else {
+            group
+        };
+        self.send_updates(group);
+    }
+
+    /// The group that `leader` leads, telling them why if there is none.
+    fn led_group(&mut self, leader: EcsEntity) -> Option<comp::Group> {
+        match self.group_of(leader) {
+            Some(group) if Some(group.leader) == self.state.ecs().uid_from_entity(leader) => {
+                Some(group)
+            }
+            Some(_) => {
+                self.notify(
+                    leader,
+                    "Only the leader of your group can do that.".to_owned(),
+                );
+                None
+            }
+            None => {
+                self.notify(leader, "You are not in a group.".to_owned());
+                None
+            }
+        }
+    }
+
+    fn group_of(&self, entity: EcsEntity) -> Option<comp::Group> {
+        self.state.read_component_cloned::<comp::Group>(entity)
+    }
+
+    fn members(&self, group: comp::Group) -> Vec<EcsEntity> {
+        let ecs = self.state.ecs();
+        (&ecs.entities(), &ecs.read_storage::<comp::Group>())
+            .join()
+            .filter(|(_, other)| **other == group)
+            .map(|(member, _)| member)
+            .collect()
+    }
+
+    fn alias(&self, entity: EcsEntity) -> String {
+        self.state
+            .read_component_cloned::<comp::Player>(entity)
+            .map(|player| player.alias)
+            .unwrap_or_else(|| "<Unknown>".to_owned())
+    }
+
+    fn notify(&mut self, entity: EcsEntity, msg: String) {
+        self.messages.push((entity, ServerMsg::private(msg)));
+    }
+
+    fn notify_members(&mut self, group: comp::Group, msg: String) {
+        for member in self.members(group) {
+            self.notify(member, msg.clone());
+        }
+    }
+
+    /// Tell every member who is in the group.
+    fn send_updates(&mut self, group: comp::Group) {
+        let info = GroupInfo {
+            leader: group.leader.into(),
+            members: self
+                .members(group)
+                .into_iter()
+                .filter_map(|member| {
+                    self.state
+                        .ecs()
+                        .uid_from_entity(member)
+                        .map(|uid| (uid.into(), self.alias(member)))
+                })
+                .collect(),
+        };
+        for member in self.members(group) {
+            self.messages
+                .push((member, ServerMsg::GroupUpdate(Some(info.clone()))));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use specs::Builder;
+    use vek::*;
+
+    #[derive(Default)]
+    struct Fixture {
+        state: State,
+        invites: HashMap<EcsEntity, EcsEntity>,
+    }
+
+    impl Fixture {
+        fn groups(&mut self) -> Groups {
+            Groups::new(&mut self.state, &mut self.invites)
+        }
+
+        fn create_player(&mut self, alias: &str, pos: Vec3<f32>) -> EcsEntity {
+            self.state
+                .ecs_mut()
+                .create_entity_synced()
+                .with(comp::Player::new(alias.to_owned(), None))
+                .with(comp::Pos(pos))
+                .build()
+        }
+
+        fn group_of(&mut self, entity: EcsEntity) -> Option<comp::Group> {
+            self.groups().group_of(entity)
+        }
+
+        fn leader_of(&mut self, entity: EcsEntity) -> Option<EcsEntity> {
+            self.group_of(entity)
+                .and_then(|group| self.state.ecs().entity_from_uid(group.leader.into()))
+        }
+
+        fn size_of_group(&mut self, entity: EcsEntity) -> usize {
+            match self.group_of(entity) {
+                Some(group) => self.groups().members(group).len(),
+                None => 0,
+            }
+        }
+
+        /// A group led by the first of the given players.
+        fn form_group(&mut self, players: &[EcsEntity]) {
+            let mut groups = self.groups();
+            for member in &players[1..] {
+                groups.invite(players[0], *member);
+                groups.accept(*member);
+            }
+        }
+    }
+
+    #[test]
+    fn invited_players_can_join() {
+        let mut fixture = Fixture::default();
+        let leader = fixture.create_player("Leader", Vec3::zero());
+        let member = fixture.create_player("Member", Vec3::zero());
+        let stranger = fixture.create_player("Stranger", Vec3::zero());
+
+        fixture.groups().accept(stranger);
+        assert_eq!(fixture.group_of(stranger), None);
+
+        fixture.groups().invite(leader, member);
+        fixture.groups().accept(member);
+        assert_eq!(fixture.leader_of(leader), Some(leader));
+        assert_eq!(fixture.leader_of(member), Some(leader));
+        assert_eq!(fixture.size_of_group(leader), 2);
+
+        // Only the leader can invite
+        fixture.groups().invite(member, stranger);
+        assert!(!fixture.invites.contains_key(&stranger));
+    }
+
+    #[test]
+    fn members_are_told_about_the_group() {
+        let mut fixture = Fixture::default();
+        let leader = fixture.create_player("Leader", Vec3::zero());
+        let member = fixture.create_player("Member", Vec3::zero());
+
+        let mut groups = fixture.groups();
+        groups.invite(leader, member);
+        groups.accept(member);
+        let updates = groups
+            .messages
+            .iter()
+            .filter_map(|(entity, msg)| match msg {
+                ServerMsg::GroupUpdate(Some(info)) => Some((*entity, info.members.len())),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        assert!(updates.contains(&(leader, 2)));
+        assert!(updates.contains(&(member, 2)));
+    }
+
+    #[test]
+    fn lead_is_handed_over_when_the_leader_leaves() {
+        let mut fixture = Fixture::default();
+        let players = ["Leader", "A", "B"]
+            .iter()
+            .map(|alias| fixture.create_player(alias, Vec3::zero()))
+            .collect::<Vec<_>>();
+        fixture.form_group(&players);
+
+        fixture.groups().leave(players[0]);
+
+        assert_eq!(fixture.group_of(players[0]), None);
+        let new_leader = fixture.leader_of(players[1]).unwrap();
+        assert!(new_leader == players[1] || new_leader == players[2]);
+        assert_eq!(fixture.leader_of(players[2]), Some(new_leader));
+    }
+
+    #[test]
+    fn groups_of_one_are_disbanded() {
+        let mut fixture = Fixture::default();
+        let leader = fixture.create_player("Leader", Vec3::zero());
+        let member = fixture.create_player("Member", Vec3::zero());
+        fixture.form_group(&[leader, member]);
+
+        fixture.groups().kick(leader, member);
+
+        assert_eq!(fixture.group_of(member), None);
+        assert_eq!(fixture.group_of(leader), None);
+    }
+
+    #[test]
+    fn groups_are_limited_in_size() {
+        let mut fixture = Fixture::default();
+        let players = (0..=MAX_GROUP_SIZE)
+            .map(|i| fixture.create_player(&format!("Player{}", i), Vec3::zero()))
+            .collect::<Vec<_>>();
+        fixture.form_group(&players[..MAX_GROUP_SIZE]);
+        assert_eq!(fixture.size_of_group(players[0]), MAX_GROUP_SIZE);
+
+        let late = players[MAX_GROUP_SIZE];
+        fixture.groups().invite(players[0], late);
+        fixture.groups().accept(late);
+        assert_eq!(fixture.group_of(late), None);
+        assert_eq!(fixture.size_of_group(players[0]), MAX_GROUP_SIZE);
+    }
+
+    #[test]
+    fn forgotten_players_leave_their_group_and_invites() {
+        let mut fixture = Fixture::default();
+        let players = ["Leader", "A", "B"]
+            .iter()
+            .map(|alias| fixture.create_player(alias, Vec3::zero()))
+            .collect::<Vec<_>>();
+        let invitee = fixture.create_player("Invitee", Vec3::zero());
+        fixture.form_group(&players);
+        fixture.groups().invite(players[0], invitee);
+
+        fixture.groups().forget_player(players[0]);
+
+        assert_eq!(fixture.group_of(players[0]), None);
+        assert_eq!(fixture.size_of_group(players[1]), 2);
+        assert!(fixture.invites.is_empty());
+    }
+
+    #[test]
+    fn exp_is_shared_with_members_nearby() {
+        let mut fixture = Fixture::default();
+        let killer = fixture.create_player("Killer", Vec3::zero());
+        let near = fixture.create_player("Near", Vec3::new(10.0, 0.0, 0.0));
+        let far = fixture.create_player("Far", Vec3::new(1000.0, 0.0, 0.0));
+        let victim = fixture.create_player("Victim", Vec3::new(1.0, 0.0, 0.0));
+        let loner = fixture.create_player("Loner", Vec3::zero());
+        fixture.form_group(&[killer, near, far, victim]);
+
+        let mut recipients = exp_recipients(fixture.state.ecs(), killer, victim);
+        recipients.sort();
+        let mut expected = vec![killer, near];
+        expected.sort();
+        assert_eq!(recipients, expected);
+
+        assert_eq!(
+            exp_recipients(fixture.state.ecs(), loner, victim),
+            vec![loner]
+        );
+    }
+
+    #[test]
+    fn exp_shares_add_up_to_the_kill() {
+        let mut fixture = Fixture::default();
+        let players = (0..3)
+            .map(|i| fixture.create_player(&format!("Player{}", i), Vec3::zero()))
+            .collect::<Vec<_>>();
+        let killer = players[1];
+
+        let shares = exp_shares(100, killer, &players);
+        assert_eq!(shares.iter().map(|(_, share)| share).sum::<i64>(), 100);
+        assert!(shares.contains(&(players[0], 33)));
+        assert!(shares.contains(&(killer, 34)));
+        assert!(shares.contains(&(players[2], 33)));
+
+        assert_eq!(exp_shares(7, killer, &[killer]), vec![(killer, 7)]);
+    }
+}
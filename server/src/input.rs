@@ -1,9 +1,181 @@
+use crate::DEFAULT_SHUTDOWN_GRACE;
+use std::time::Duration;
+
+/// A command given by the operator of the server, e.g. through the server-cli console.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConsoleCommand {
+    /// Disconnect the player with the given alias.
+    Kick {
+        alias: String,
+        reason: Option<String>,
+    },
+    /// Permanently ban the account with the given alias, kicking the player if they are online.
+    Ban {
+        alias: String,
+        reason: Option<String>,
+    },
+    /// Send a message to every player.
+    Broadcast(String),
+    ListPlayers,
+    /// Write the ban list and the roles given in game back to disk.
+    Save,
+    /// Stop the server once the grace period is over, warning players as it counts down.
+    Shutdown {
+        grace: Duration,
+        reason: Option<String>,
+    },
+    /// Run one of the chat commands as the console, omitting the leading '/'.
+    ChatCommand(String),
+    Help,
+}
+
+impl ConsoleCommand {
+    /// Parse a line typed into the console. Lines starting with '/' run chat commands.
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let line = line.trim();
+        if line.starts_with('/') {
+            return if line.len() > 1 {
+                Ok(ConsoleCommand::ChatCommand(line[1..].to_owned()))
+            } else {
+                Err("Missing chat command after '/'".to_owned())
+            };
+        }
+
+        let mut words = line.splitn(2, ' ');
+        let keyword = words.next().unwrap_or("");
+        let args = words.next().map(str::trim).unwrap_or("");
+        let alias_and_reason = || {
+            let mut args = args.splitn(2, ' ');
+            match args.next() {
+                Some(alias) if !alias.is_empty() => Ok((
+                    alias.to_owned(),
+                    args.next()
+                        .map(|reason| reason.trim().to_owned())
+                        .filter(|reason| !reason.is_empty()),
+                )),
+                _ => Err(format!("Usage: {} <alias> [reason]", keyword)),
+            }
+        };
+
+        match keyword {
+            "kick" => {
+                alias_and_reason().map(|(alias, reason)| ConsoleCommand::Kick { alias, reason })
+            }
+            "ban" => {
+                alias_and_reason().map(|(alias, reason)| ConsoleCommand::Ban { alias, reason })
+            }
+            "say" | "broadcast" if !args.is_empty() => {
+                Ok(ConsoleCommand::Broadcast(args.to_owned()))
+            }
+            "say" | "broadcast" => Err(format!("Usage: {} <message>", keyword)),
+            "players" | "list" => Ok(ConsoleCommand::ListPlayers),
+            "save" => Ok(ConsoleCommand::Save),
+            "shutdown" | "stop" => {
+                let mut args = args.splitn(2, ' ');
+                let grace = match args.next().filter(|secs| !secs.is_empty()) {
+                    Some(secs) => match secs.parse() {
+                        Ok(secs) => Duration::from_secs(secs),
+                        Err(_) => return Err(format!("Usage: {} [seconds] [reason]", keyword)),
+                    },
+                    None => DEFAULT_SHUTDOWN_GRACE,
+                };
+                Ok(ConsoleCommand::Shutdown {
+                    grace,
+                    reason: args
+                        .next()
+                        .map(|reason| reason.trim().to_owned())
+                        .filter(|reason| !reason.is_empty()),
+                })
+            }
+            "help" => Ok(ConsoleCommand::Help),
+            "" => Err("Empty command".to_owned()),
+            _ => Err(format!(
+                "Unknown command '{}'. Type 'help' for available commands",
+                keyword
+            )),
+        }
+    }
+
+    /// A description of the console commands.
+    pub fn help() -> &'static str {
+        "Console commands:\n\
+         kick <alias> [reason] : Disconnect a player\n\
+         ban <alias> [reason] : Permanently ban an account\n\
+         say <message> : Send a message to every player\n\
+         players : List players currently online\n\
+         save : Write the ban list and account roles to disk\n\
+         shutdown [seconds] [reason] : Warn players, then stop the server\n\
+         /<command> [args] : Run a chat command as the console, e.g. '/help'"
+    }
+}
+
+/// Input from the frontend, applied at the start of each tick.
+#[derive(Default)]
 pub struct Input {
-    // TODO: Use this type to manage server input.
+    pub console_commands: Vec<ConsoleCommand>,
 }
 
-impl Default for Input {
-    fn default() -> Self {
-        Input {}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chat_commands_are_passed_through() {
+        assert_eq!(
+            ConsoleCommand::parse("/tp Pfau"),
+            Ok(ConsoleCommand::ChatCommand("tp Pfau".to_owned()))
+        );
+        assert!(ConsoleCommand::parse("/").is_err());
+    }
+
+    #[test]
+    fn moderation_needs_an_alias() {
+        assert_eq!(
+            ConsoleCommand::parse("kick Pfau  being rude "),
+            Ok(ConsoleCommand::Kick {
+                alias: "Pfau".to_owned(),
+                reason: Some("being rude".to_owned()),
+            })
+        );
+        assert_eq!(
+            ConsoleCommand::parse("ban Pfau"),
+            Ok(ConsoleCommand::Ban {
+                alias: "Pfau".to_owned(),
+                reason: None,
+            })
+        );
+        assert!(ConsoleCommand::parse("kick").is_err());
+        assert!(ConsoleCommand::parse("ban   ").is_err());
+    }
+
+    #[test]
+    fn shutdown_grace_must_be_a_number() {
+        assert_eq!(
+            ConsoleCommand::parse("shutdown"),
+            Ok(ConsoleCommand::Shutdown {
+                grace: DEFAULT_SHUTDOWN_GRACE,
+                reason: None,
+            })
+        );
+        assert_eq!(
+            ConsoleCommand::parse("stop 30 Updating"),
+            Ok(ConsoleCommand::Shutdown {
+                grace: Duration::from_secs(30),
+                reason: Some("Updating".to_owned()),
+            })
+        );
+        assert!(ConsoleCommand::parse("shutdown soon").is_err());
+        assert!(ConsoleCommand::parse("shutdown -5").is_err());
+    }
+
+    #[test]
+    fn unknown_commands_are_rejected() {
+        assert_eq!(
+            ConsoleCommand::parse("players"),
+            Ok(ConsoleCommand::ListPlayers)
+        );
+        assert!(ConsoleCommand::parse("say").is_err());
+        assert!(ConsoleCommand::parse("").is_err());
+        assert!(ConsoleCommand::parse("teleport Pfau").is_err());
     }
 }
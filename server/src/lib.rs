@@ -2,41 +2,69 @@
 #![feature(drain_filter, bind_by_move_pattern_guards)]
 
 pub mod auth_provider;
+pub mod banlist;
 pub mod client;
 pub mod cmd;
 pub mod error;
+pub mod friends;
+pub mod group;
 pub mod input;
+pub mod metrics;
+pub mod roles;
 pub mod settings;
 
 // Reexports
-pub use crate::{error::Error, input::Input, settings::ServerSettings};
+pub use crate::{
+    error::Error,
+    input::{ConsoleCommand, Input},
+    settings::ServerSettings,
+};
 
 use crate::{
     auth_provider::AuthProvider,
+    banlist::{BanList, BanTarget, Mute},
     client::{Client, Clients},
     cmd::CHAT_COMMANDS,
+    friends::FriendLists,
+    metrics::{MetricsServer, ServerMetrics, TickTimes},
+    roles::{AccountRoles, Permission},
 };
 use common::{
+    assets::watch::ReloadIndicator,
     comp,
     event::{EventBus, ServerEvent},
-    msg::{ClientMsg, ClientState, RequestStateError, ServerError, ServerInfo, ServerMsg},
+    msg::{
+        ClientMsg, ClientState, GroupRequest, RequestStateError, ServerError, ServerInfo,
+        ServerMsg, WorldMap,
+    },
     net::PostOffice,
     state::{BlockChange, State, TimeOfDay, Uid},
-    terrain::{block::Block, TerrainChunk, TerrainChunkSize, TerrainMap},
+    terrain::{block::Block, BiomeKind, TerrainChunk, TerrainChunkSize, TerrainMap},
     vol::Vox,
     vol::{ReadVol, VolSize},
+    ChatType,
 };
 use crossbeam::channel;
-use hashbrown::HashSet;
-use log::debug;
+use hashbrown::{HashMap, HashSet};
+use log::{debug, info, warn};
 use rand::Rng;
 use specs::{join::Join, world::EntityBuilder as EcsEntityBuilder, Builder, Entity as EcsEntity};
-use std::{i32, net::SocketAddr, sync::Arc, time::Duration};
+use std::{
+    fs, i32,
+    net::SocketAddr,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 use uvth::{ThreadPool, ThreadPoolBuilder};
 use vek::*;
-use world::{ChunkSupplement, World};
+use world::{sim::CacheError, ChunkSupplement, World};
 
 const CLIENT_TIMEOUT: f64 = 20.0; // Seconds
+/// How long players are given to finish what they're doing when the server is asked to stop.
+pub const DEFAULT_SHUTDOWN_GRACE: Duration = Duration::from_secs(10);
+/// Seconds before a shutdown at which players are warned, in ascending order.
+const SHUTDOWN_WARNINGS: [u64; 11] = [1, 2, 3, 4, 5, 10, 30, 60, 120, 300, 600];
 
 pub enum Event {
     ClientConnected {
@@ -49,11 +77,74 @@ pub enum Event {
         entity: Option<EcsEntity>,
         msg: String,
     },
+    /// The reply to a console command.
+    ConsoleOutput {
+        msg: String,
+    },
+    /// The server has shut down and shouldn't be ticked any more.
+    Shutdown,
 }
 
 #[derive(Copy, Clone)]
 struct SpawnPoint(Vec3<f32>);
 
+/// A shutdown that has been asked for but hasn't happened yet.
+struct PendingShutdown {
+    at: Instant,
+    reason: String,
+    /// The countdown warning that was broadcast last.
+    last_warning: Option<u64>,
+}
+
+/// What a pending shutdown needs the server to do this tick.
+#[derive(Debug, PartialEq)]
+enum ShutdownStep {
+    Wait,
+    /// Warn players that the server shuts down in this many seconds.
+    Warn(u64),
+    ShutDown,
+}
+
+impl PendingShutdown {
+    fn new(now: Instant, grace: Duration, reason: String) -> Self {
+        Self {
+            at: now + grace,
+            reason,
+            // Players were just told about the shutdown, so the countdown starts below the grace.
+            last_warning: next_shutdown_warning(grace.as_secs(), None),
+        }
+    }
+
+    fn step(&mut self, now: Instant) -> ShutdownStep {
+        if now >= self.at {
+            return ShutdownStep::ShutDown;
+        }
+        let left_secs = secs_rounded_up(self.at - now);
+        match next_shutdown_warning(left_secs, self.last_warning) {
+            Some(warning) => {
+                self.last_warning = Some(warning);
+                ShutdownStep::Warn(left_secs)
+            }
+            None => ShutdownStep::Wait,
+        }
+    }
+}
+
+/// Round up, so that the last warning is at one second rather than zero.
+fn secs_rounded_up(duration: Duration) -> u64 {
+    duration.as_secs() + if duration.subsec_nanos() > 0 { 1 } else { 0 }
+}
+
+/// The countdown warning due with `left_secs` seconds to go, if it hasn't been given already.
+/// Warnings only ever count down, so none is repeated.
+fn next_shutdown_warning(left_secs: u64, last_warning: Option<u64>) -> Option<u64> {
+    SHUTDOWN_WARNINGS
+        .iter()
+        .find(|warning| **warning >= left_secs)
+        .cloned()
+        .filter(|warning| last_warning.map_or(true, |last| *warning < last))
+}
+
 pub struct Server {
     state: State,
     world: Arc<World>,
@@ -68,9 +159,35 @@ pub struct Server {
 
     server_settings: ServerSettings,
     server_info: ServerInfo,
+    /// Overview of the world sent to clients when they register.
+    world_map: WorldMap,
 
     // TODO: anything but this
     accounts: AuthProvider,
+    banlist: BanList,
+    account_roles: AccountRoles,
+    friend_lists: FriendLists,
+
+    /// Entity that console commands are run as. It has no components and no client.
+    console: EcsEntity,
+    /// Events raised while handling commands, passed to the frontend at the end of the tick.
+    pending_events: Vec<Event>,
+
+    /// Pending group invites, from the invited player to the player who invited them.
+    group_invites: HashMap<EcsEntity, EcsEntity>,
+    /// Pending faction invites, from the invited player to the faction they may join.
+    faction_invites: HashMap<EcsEntity, comp::Faction>,
+
+    /// Serves measurements of the server's health, if enabled in the settings.
+    metrics: Option<MetricsServer>,
+    ticks: u64,
+
+    /// The settings file and the indicator telling us it changed, if it's being watched.
+    settings_watch: Option<(PathBuf, ReloadIndicator)>,
+
+    shutdown: Option<PendingShutdown>,
+    /// Whether the shutdown has happened and clients have been told about it.
+    stopped: bool,
 }
 
 impl Server {
@@ -83,20 +200,41 @@ impl Server {
     pub fn bind<A: Into<SocketAddr>>(addrs: A, settings: ServerSettings) -> Result<Self, Error> {
         let (chunk_tx, chunk_rx) = channel::unbounded();
 
+        let world = load_world(&settings)?;
+        // Spawn in the middle of the world
+        let spawn_point = world
+            .sim()
+            .opts()
+            .size
+            .map2(Vec2::from(TerrainChunkSize::SIZE), |e, sz: u32| {
+                (e * sz / 2) as f32
+            });
+
         let mut state = State::default();
         state
             .ecs_mut()
-            .add_resource(SpawnPoint(Vec3::new(16_384.0, 16_384.0, 512.0)));
+            .add_resource(SpawnPoint(Vec3::new(spawn_point.x, spawn_point.y, 512.0)));
         state
             .ecs_mut()
             .add_resource(EventBus::<ServerEvent>::default());
 
         // Set starting time for the server.
         state.ecs_mut().write_resource::<TimeOfDay>().0 = settings.start_time;
+        state.set_tick_rate(settings.physics_tick_rate);
+        state.ecs_mut().add_resource(settings.energy.clone());
+
+        let world_map = world.sim().generate_map(WorldMap::MAX_SIZE);
+        let console = state.ecs_mut().create_entity().build();
+
+        let metrics = settings.metrics_address.and_then(|addr| {
+            MetricsServer::start(addr)
+                .map_err(|err| warn!("Failed to serve metrics on {}: {}", addr, err))
+                .ok()
+        });
 
         let this = Self {
             state,
-            world: Arc::new(World::generate(settings.world_seed)),
+            world: Arc::new(world),
 
             postoffice: PostOffice::bind(addrs.into())?,
             clients: Clients::empty(),
@@ -112,14 +250,44 @@ impl Server {
                 name: settings.server_name.clone(),
                 description: settings.server_description.clone(),
                 git_hash: common::util::GIT_HASH.to_string(),
+                physics_tick_rate: settings.physics_tick_rate,
+                energy_config: settings.energy.clone(),
             },
+            world_map,
             accounts: AuthProvider::new(),
+            banlist: BanList::load(&settings.banlist_file),
+            account_roles: AccountRoles::load(&settings.account_roles_file),
+            friend_lists: FriendLists::load(&settings.friends_file),
             server_settings: settings,
+
+            console,
+            pending_events: Vec::new(),
+
+            group_invites: HashMap::new(),
+            faction_invites: HashMap::new(),
+
+            metrics,
+            ticks: 0,
+
+            settings_watch: None,
+
+            shutdown: None,
+            stopped: false,
         };
 
         Ok(this)
     }
 
+    /// Apply changes to the settings file at `path` while the server is running. Settings that
+    /// can't change without a restart are reported and keep their old values.
+    pub fn watch_settings(&mut self, path: PathBuf) {
+        // Notify reports changes with absolute paths.
+        let path = fs::canonicalize(&path).unwrap_or(path);
+        let mut indicator = ReloadIndicator::new();
+        indicator.add(path.clone(), || {});
+        self.settings_watch = Some((path, indicator));
+    }
+
     pub fn with_thread_pool(mut self, thread_pool: ThreadPool) -> Self {
         self.thread_pool = thread_pool;
         self
@@ -192,6 +360,7 @@ impl Server {
             .with(vel)
             .with(comp::Ori(Vec3::unit_y()))
             .with(body)
+            .with(comp::NoEntityCollision)
     }
 
     pub fn create_player_character(
@@ -200,7 +369,6 @@ impl Server {
         client: &mut Client,
         name: String,
         body: comp::Body,
-        server_settings: &ServerSettings,
     ) {
         let spawn_point = state.ecs().read_resource::<SpawnPoint>().0;
 
@@ -216,17 +384,6 @@ impl Server {
         // Make sure physics are accepted.
         state.write_component(entity, comp::ForceUpdate);
 
-        // Give the Admin component to the player if their name exists in admin list
-        if server_settings.admins.contains(
-            &state
-                .ecs()
-                .read_storage::<comp::Player>()
-                .get(entity)
-                .unwrap()
-                .alias,
-        ) {
-            state.write_component(entity, comp::Admin);
-        }
         // Tell the client its request was successful.
         client.allow_state(ClientState::Character);
     }
@@ -306,19 +463,22 @@ impl Server {
                     }
 
                     {
-                        // Give EXP to the client
+                        // Give EXP to the client, shared with the members of its group nearby
                         let mut stats = ecs.write_storage::<comp::Stats>();
 
                         if let Some(entity_stats) = stats.get(entity).cloned() {
                             if let comp::HealthSource::Attack { by } = cause {
                                 ecs.entity_from_uid(by.into()).map(|attacker| {
-                                    if let Some(attacker_stats) = stats.get_mut(attacker) {
-                                        // TODO: Discuss whether we should give EXP by Player Killing or not.
-                                        attacker_stats.exp.change_by(
-                                            (entity_stats.health.maximum() as f64 / 10.0
-                                                + entity_stats.level.level() as f64 * 10.0)
-                                                as i64,
-                                        );
+                                    // TODO: Discuss whether we should give EXP by Player Killing or not.
+                                    let exp = entity_stats.health.maximum() as f64 / 10.0
+                                        + entity_stats.level.level() as f64 * 10.0;
+                                    let recipients = group::exp_recipients(ecs, attacker, entity);
+                                    let shares =
+                                        group::exp_shares(exp as i64, attacker, &recipients);
+                                    for (recipient, share) in shares {
+                                        if let Some(recipient_stats) = stats.get_mut(recipient) {
+                                            recipient_stats.exp.change_by(share);
+                                        }
                                     }
                                 });
                             }
@@ -354,12 +514,43 @@ impl Server {
                             .insert(entity, comp::ForceUpdate);
                     }
                 }
+
+                ServerEvent::Mine { entity, pos } => {
+                    let ecs = state.ecs_mut();
+                    let ingredient = match ecs
+                        .read_resource::<TerrainMap>()
+                        .get(pos)
+                        .ok()
+                        .and_then(|block| block.mined_ingredient())
+                    {
+                        Some(ingredient) => ingredient,
+                        // Somebody else got there first
+                        None => continue,
+                    };
+
+                    let item = comp::Item::Ingredient { kind: ingredient };
+                    let inserted = ecs
+                        .write_storage::<comp::Inventory>()
+                        .get_mut(entity)
+                        .map(|inv| inv.insert(item).is_none())
+                        .unwrap_or(false);
+
+                    if inserted {
+                        ecs.write_resource::<BlockChange>().set(pos, Block::empty());
+                        state.write_component(entity, comp::InventoryUpdate);
+                    } else {
+                        clients.notify(
+                            entity,
+                            ServerMsg::private(String::from("Your inventory is full.")),
+                        );
+                    }
+                }
             }
         }
     }
 
     /// Execute a single server tick, handle input and update the game state by the given duration.
-    pub fn tick(&mut self, _input: Input, dt: Duration) -> Result<Vec<Event>, Error> {
+    pub fn tick(&mut self, input: Input, dt: Duration) -> Result<Vec<Event>, Error> {
         // This tick function is the centre of the Veloren universe. Most server-side things are
         // managed from here, and as such it's important that it stays organised. Please consult
         // the core developers before making significant changes to this code. Here is the
@@ -376,12 +567,22 @@ impl Server {
 
         // 1) Build up a list of events for this frame, to be passed to the frontend.
         let mut frontend_events = Vec::new();
+        let tick_start = Instant::now();
 
         // If networking has problems, handle them.
         if let Some(err) = self.postoffice.error() {
             return Err(err.into());
         }
 
+        // Apply commands typed into the frontend's console.
+        self.handle_console_commands(input.console_commands);
+
+        // Pick up changes to the settings file.
+        self.reload_settings();
+
+        // Warn players about an upcoming shutdown, or shut down if it's time.
+        self.tick_shutdown();
+
         // 2)
 
         // 3) Handle inputs from clients
@@ -391,12 +592,16 @@ impl Server {
         // Handle game events
         self.handle_events();
 
+        let input_done = Instant::now();
+
         // 4) Tick the client's LocalState.
         self.state.tick(dt);
 
         // Tick the world
         self.world.tick(dt);
 
+        let state_done = Instant::now();
+
         // 5) Fetch any generated `TerrainChunk`s and insert them into the terrain.
         // Also, send the chunk data to anybody that is close by.
         if let Ok((key, (chunk, supplement))) = self.chunk_rx.try_recv() {
@@ -427,20 +632,13 @@ impl Server {
                 }
             }
 
+            let biome = chunk.meta().biome();
             self.state.insert_chunk(key, chunk);
             self.pending_chunks.remove(&key);
 
             // Handle chunk supplement
             for npc in supplement.npcs {
-                let (mut stats, mut body) = if rand::random() {
-                    let stats = comp::Stats::new("Humanoid".to_string());
-                    let body = comp::Body::Humanoid(comp::humanoid::Body::random());
-                    (stats, body)
-                } else {
-                    let stats = comp::Stats::new("Wolf".to_string());
-                    let body = comp::Body::QuadrupedMedium(comp::quadruped_medium::Body::random());
-                    (stats, body)
-                };
+                let (mut stats, mut body) = random_npc(biome);
                 let mut scale = 1.0;
 
                 if npc.boss {
@@ -457,6 +655,51 @@ impl Server {
                     .with(comp::Scale(scale))
                     .build();
             }
+
+            // Light the way into caves
+            for pos in supplement.cave_entrances {
+                self.create_object(
+                    comp::Pos(pos + Vec3::new(5.0, 0.0, 2.0)),
+                    comp::object::Body::LanternStanding,
+                )
+                .build();
+            }
+
+            // Cave chambers are guarded by a pack of cave dwellers, with loot at the back
+            for pos in supplement.cave_chambers {
+                for _ in 0..1 + rand::random::<u32>() % 3 {
+                    let offset = Vec3::new(
+                        rand::random::<f32>() - 0.5,
+                        rand::random::<f32>() - 0.5,
+                        0.0,
+                    ) * 4.0;
+                    self.create_npc(
+                        comp::Pos(pos + offset),
+                        comp::Stats::new("Cave Wolf".to_string()),
+                        comp::Body::QuadrupedMedium(comp::quadruped_medium::Body::random()),
+                    )
+                    .with(comp::Agent::enemy())
+                    .build();
+                }
+
+                let loot = if rand::random() {
+                    comp::Item::Consumable {
+                        effect: comp::item::ConsumptionEffect::Health(50),
+                    }
+                } else {
+                    comp::Item::Tool {
+                        kind: comp::item::ALL_TOOLS
+                            [rand::random::<usize>() % comp::item::ALL_TOOLS.len()],
+                        power: 10 + rand::random::<u32>() % 20,
+                    }
+                };
+                self.create_object(
+                    comp::Pos(pos - Vec3::unit_y() * 3.0),
+                    comp::object::Body::ChestDark,
+                )
+                .with(loot)
+                .build();
+            }
         }
 
         fn chunk_in_vd(
@@ -504,6 +747,8 @@ impl Server {
             self.state.remove_chunk(key);
         }
 
+        let terrain_done = Instant::now();
+
         // 6) Synchronise clients with the new state of the world.
         self.sync_clients();
 
@@ -568,11 +813,45 @@ impl Server {
             let _ = self.state.ecs_mut().delete_entity(entity);
         }
 
+        let sync_done = Instant::now();
+
         // 7) Finish the tick, pass control back to the frontend.
+        frontend_events.append(&mut self.pending_events);
+
+        self.ticks += 1;
+        if self.metrics.is_some() {
+            let secs = |from: Instant, to: Instant| {
+                let d = to - from;
+                d.as_secs() as f64 + d.subsec_nanos() as f64 * 1e-9
+            };
+            self.update_metrics(TickTimes {
+                input: secs(tick_start, input_done),
+                state: secs(input_done, state_done),
+                terrain: secs(state_done, terrain_done),
+                sync: secs(terrain_done, sync_done),
+                total: secs(tick_start, sync_done),
+            });
+        }
 
         Ok(frontend_events)
     }
 
+    fn update_metrics(&mut self, tick_times: TickTimes) {
+        let metrics = ServerMetrics {
+            tick_times,
+            ticks: self.ticks,
+            entities: (&self.state.ecs().entities()).join().count(),
+            chunks: self.state.terrain().iter().count(),
+            pending_chunks: self.pending_chunks.len(),
+            clients: self.clients.len(),
+            bytes_sent: self.postoffice.traffic().bytes_sent(),
+            bytes_received: self.postoffice.traffic().bytes_received(),
+        };
+        if let Some(server) = &self.metrics {
+            server.update(metrics);
+        }
+    }
+
     /// Clean up the server after a tick.
     pub fn cleanup(&mut self) {
         // Cleanup the local state
@@ -589,9 +868,14 @@ impl Server {
                 client_state: ClientState::Connected,
                 postbox,
                 last_ping: self.state.get_time(),
+                account: None,
             };
 
-            if self.server_settings.max_players <= self.clients.len() {
+            if let Some(shutdown) = &self.shutdown {
+                client.notify(ServerMsg::Shutdown(shutdown.reason.clone()));
+            } else if let Some(ban) = self.banlist.find_ban(None, client.ip()) {
+                client.notify(ServerMsg::Error(ServerError::Banned(ban.message())));
+            } else if self.server_settings.max_players <= self.clients.len() {
                 client.notify(ServerMsg::Error(ServerError::TooManyPlayers));
             } else {
                 // Return the state of the current world (all of the components that Sphynx tracks).
@@ -615,10 +899,18 @@ impl Server {
         let mut frontend_events = Vec::new();
 
         let accounts = &mut self.accounts;
+        let banlist = &self.banlist;
         let server_settings = &self.server_settings;
+        let account_roles = &self.account_roles;
+        let world_map = &self.world_map;
 
         let state = &mut self.state;
         let mut new_chat_msgs = Vec::new();
+        let mut group_requests = Vec::new();
+        let mut friend_requests = Vec::new();
+        let mut whispers = Vec::new();
+        let mut completion_requests = Vec::new();
+        let mut registered_clients = Vec::new();
         let mut disconnected_clients = Vec::new();
         let mut requested_chunks = Vec::new();
         let mut modified_blocks = Vec::new();
@@ -676,9 +968,16 @@ impl Server {
                                 client.error_state(RequestStateError::Denied);
                                 break;
                             }
+                            if let Some(ban) = banlist.find_ban(Some(&player.alias), client.ip()) {
+                                client.notify(ServerMsg::Error(ServerError::Banned(ban.message())));
+                                break;
+                            }
                             match client.client_state {
                                 ClientState::Connected => {
+                                    client.account = Some(player.alias.clone());
                                     Self::initialize_player(state, entity, client, player);
+                                    client.notify(ServerMsg::WorldMap(world_map.clone()));
+                                    registered_clients.push(entity);
                                 }
                                 // Use RequestState instead (No need to send `player` again).
                                 _ => client.error_state(RequestStateError::Impossible),
@@ -767,14 +1066,7 @@ impl Server {
                             ClientState::Registered
                             | ClientState::Spectator
                             | ClientState::Dead => {
-                                Self::create_player_character(
-                                    state,
-                                    entity,
-                                    client,
-                                    name,
-                                    body,
-                                    &server_settings,
-                                );
+                                Self::create_player_character(state, entity, client, name, body);
                                 if let Some(player) =
                                     state.ecs().read_storage::<comp::Player>().get(entity)
                                 {
@@ -824,22 +1116,12 @@ impl Server {
                             _ => client.error_state(RequestStateError::Impossible),
                         },
                         ClientMsg::BreakBlock(pos) => {
-                            if state
-                                .ecs_mut()
-                                .read_storage::<comp::CanBuild>()
-                                .get(entity)
-                                .is_some()
-                            {
+                            if can_build(state, server_settings, account_roles, entity, client) {
                                 modified_blocks.push((pos, Block::empty()));
                             }
                         }
                         ClientMsg::PlaceBlock(pos, block) => {
-                            if state
-                                .ecs_mut()
-                                .read_storage::<comp::CanBuild>()
-                                .get(entity)
-                                .is_some()
-                            {
+                            if can_build(state, server_settings, account_roles, entity, client) {
                                 modified_blocks.push((pos, block));
                             }
                         }
@@ -865,6 +1147,42 @@ impl Server {
                         // Always possible.
                         ClientMsg::Ping => client.postbox.send_message(ServerMsg::Pong),
                         ClientMsg::Pong => {}
+                        ClientMsg::Group(request) => match client.client_state {
+                            ClientState::Connected | ClientState::Pending => {
+                                client.error_state(RequestStateError::Impossible)
+                            }
+                            ClientState::Registered
+                            | ClientState::Spectator
+                            | ClientState::Dead
+                            | ClientState::Character => group_requests.push((entity, request)),
+                        },
+                        ClientMsg::Friend(request) => match client.client_state {
+                            ClientState::Connected | ClientState::Pending => {
+                                client.error_state(RequestStateError::Impossible)
+                            }
+                            ClientState::Registered
+                            | ClientState::Spectator
+                            | ClientState::Dead
+                            | ClientState::Character => friend_requests.push((entity, request)),
+                        },
+                        ClientMsg::Whisper { alias, message } => match client.client_state {
+                            ClientState::Connected | ClientState::Pending => {
+                                client.error_state(RequestStateError::Impossible)
+                            }
+                            ClientState::Registered
+                            | ClientState::Spectator
+                            | ClientState::Dead
+                            | ClientState::Character => whispers.push((entity, alias, message)),
+                        },
+                        ClientMsg::CompleteCommand(line) => match client.client_state {
+                            ClientState::Connected | ClientState::Pending => {
+                                client.error_state(RequestStateError::Impossible)
+                            }
+                            ClientState::Registered
+                            | ClientState::Spectator
+                            | ClientState::Dead
+                            | ClientState::Character => completion_requests.push((entity, line)),
+                        },
                         ClientMsg::Disconnect => {
                             disconnect = true;
                         }
@@ -887,7 +1205,7 @@ impl Server {
                         ServerMsg::broadcast(format!("{} went offline.", &player.alias)),
                     ));
                 }
-                disconnected_clients.push(entity);
+                disconnected_clients.push((entity, client.account.clone()));
                 client.postbox.send_message(ServerMsg::Disconnect);
                 true
             } else {
@@ -895,6 +1213,29 @@ impl Server {
             }
         });
 
+        // Handle group invites and changes.
+        for (entity, request) in group_requests {
+            group::handle_request(self, entity, request);
+        }
+
+        // Send new players their friends lists and tell their friends they're online.
+        for entity in registered_clients {
+            friends::player_online(self, entity);
+        }
+
+        for (entity, request) in friend_requests {
+            friends::handle_request(self, entity, request);
+        }
+
+        for (entity, alias, message) in whispers {
+            cmd::tell(self, entity, &alias, &message);
+        }
+
+        for (entity, line) in completion_requests {
+            let completions = cmd::complete(self, entity, &line);
+            self.notify_client(entity, ServerMsg::CommandCompletions { line, completions });
+        }
+
         // Handle new chat messages.
         for (entity, msg) in new_chat_msgs {
             match msg {
@@ -904,20 +1245,20 @@ impl Server {
                         if message.starts_with("/") && message.len() > 1 {
                             let argv = String::from(&message[1..]);
                             self.process_chat_cmd(entity, argv);
+                        } else if let Some(mute) = self.mute_message(entity) {
+                            self.notify_client(entity, ServerMsg::private(mute));
                         } else {
                             let message =
                                 match self.state.ecs().read_storage::<comp::Player>().get(entity) {
-                                    Some(player) => {
-                                        if self.entity_is_admin(entity) {
-                                            format!("[ADMIN][{}] {}", &player.alias, message)
-                                        } else {
-                                            format!("[{}] {}", &player.alias, message)
+                                    Some(player) => match self.role_tag(entity) {
+                                        Some(tag) => {
+                                            format!("[{}][{}] {}", tag, &player.alias, message)
                                         }
-                                    }
+                                        None => format!("[{}] {}", &player.alias, message),
+                                    },
                                     None => format!("[<Unknown>] {}", message),
                                 };
-                            self.clients
-                                .notify_registered(ServerMsg::ChatMsg { chat_type, message });
+                            self.route_chat(entity, chat_type, message);
                         }
                     } else {
                         self.clients
@@ -931,10 +1272,15 @@ impl Server {
         }
 
         // Handle client disconnects.
-        for entity in disconnected_clients {
+        for (entity, account) in disconnected_clients {
+            group::forget_player(self, entity);
+            self.faction_invites.remove(&entity);
             if let Err(err) = self.state.ecs_mut().delete_entity_synced(entity) {
                 debug!("Failed to delete disconnected client: {:?}", err);
             }
+            if let Some(account) = account {
+                friends::player_offline(self, &account);
+            }
 
             frontend_events.push(Event::ClientDisconnected { entity });
         }
@@ -956,6 +1302,7 @@ impl Server {
                 .with(comp::Pos(pos.0 + Vec3::unit_z() * 0.25))
                 .with(item)
                 .with(comp::Vel(vel))
+                .with(comp::NoEntityCollision)
                 .build();
         }
 
@@ -1179,7 +1526,7 @@ impl Server {
             Some(action) => action.execute(self, entity, args),
             // Unknown command
             None => {
-                self.clients.notify(
+                self.notify_client(
                     entity,
                     ServerMsg::private(format!(
                         "Unknown command '/{}'.\nType '/help' for available commands",
@@ -1190,16 +1537,627 @@ impl Server {
         }
     }
 
-    fn entity_is_admin(&self, entity: EcsEntity) -> bool {
-        self.state
-            .read_storage::<comp::Admin>()
+    /// Handle commands from the frontend's console. Replies are reported as events.
+    fn handle_console_commands(&mut self, commands: Vec<ConsoleCommand>) {
+        for command in commands {
+            match command {
+                ConsoleCommand::Kick { alias, reason } => match self.player_by_alias(&alias) {
+                    Some(entity) => {
+                        let reason = reason.unwrap_or_else(|| "No reason given".to_owned());
+                        self.kick_player(entity, &reason);
+                        self.console_reply(format!("Kicked {}.", alias));
+                    }
+                    None => self.console_reply(format!("Player '{}' not found!", alias)),
+                },
+                ConsoleCommand::Ban { alias, reason } => {
+                    let reason = reason.unwrap_or_else(|| "No reason given".to_owned());
+                    let account = self.account_by_name(&alias);
+                    let reply = self.ban(BanTarget::Account(account), reason, None);
+                    self.console_reply(reply);
+                }
+                ConsoleCommand::Broadcast(msg) => {
+                    self.clients
+                        .notify_registered(ServerMsg::broadcast(format!("[Server] {}", msg)));
+                    self.console_reply(format!("[Server] {}", msg));
+                }
+                ConsoleCommand::ListPlayers => {
+                    self.process_chat_cmd(self.console, "players".to_owned())
+                }
+                ConsoleCommand::Save => match self.save() {
+                    Ok(()) => {
+                        self.console_reply("Saved the ban list and account roles.".to_owned())
+                    }
+                    Err(err) => self.console_reply(format!("Failed to save: {}", err)),
+                },
+                ConsoleCommand::Shutdown { grace, reason } => {
+                    let reason = reason.unwrap_or_else(|| "The server is shutting down".to_owned());
+                    self.shutdown(grace, reason);
+                    self.console_reply(format!("Shutting down in {} seconds.", grace.as_secs()));
+                }
+                ConsoleCommand::ChatCommand(cmd) => self.process_chat_cmd(self.console, cmd),
+                ConsoleCommand::Help => self.console_reply(ConsoleCommand::help().to_owned()),
+            }
+        }
+    }
+
+    /// Stop the server after a grace period, warning players as it counts down. New players are
+    /// turned away in the meantime. Once the grace period is over, everything is saved, players
+    /// are disconnected with the reason and `Event::Shutdown` is raised.
+    ///
+    /// Asking again replaces the earlier countdown, e.g. to shut down sooner.
+    pub fn shutdown(&mut self, grace: Duration, reason: String) {
+        info!("Shutting down in {} seconds: {}", grace.as_secs(), reason);
+        if grace.as_secs() > 0 {
+            self.clients.notify_registered(ServerMsg::broadcast(format!(
+                "The server will shut down in {} seconds: {}",
+                grace.as_secs(),
+                reason
+            )));
+        }
+
+        self.shutdown = Some(PendingShutdown::new(Instant::now(), grace, reason));
+    }
+
+    /// Whether the server has been asked to shut down.
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutdown.is_some()
+    }
+
+    fn tick_shutdown(&mut self) {
+        if self.stopped {
+            return;
+        }
+        let shutdown = match &mut self.shutdown {
+            Some(shutdown) => shutdown,
+            None => return,
+        };
+
+        match shutdown.step(Instant::now()) {
+            ShutdownStep::Wait => return,
+            ShutdownStep::Warn(left_secs) => {
+                return self.clients.notify_registered(ServerMsg::broadcast(format!(
+                    "The server will shut down in {} seconds.",
+                    left_secs
+                )))
+            }
+            ShutdownStep::ShutDown => {}
+        }
+
+        let reason = shutdown.reason.clone();
+        if let Err(err) = self.save() {
+            warn!("Failed to save before shutting down: {}", err);
+        }
+        self.clients.notify_registered(ServerMsg::Shutdown(reason));
+        self.stopped = true;
+        self.pending_events.push(Event::Shutdown);
+        info!("Server shut down");
+    }
+
+    fn reload_settings(&mut self) {
+        let path = match &self.settings_watch {
+            Some((path, indicator)) if indicator.reloaded() => path.clone(),
+            _ => return,
+        };
+
+        let settings = match ServerSettings::reload(&path) {
+            Ok(settings) => settings,
+            Err(err) => {
+                warn!(
+                    "Failed to reload the settings, keeping the old ones: {}",
+                    err
+                );
+                self.console_reply(format!("Failed to reload the settings: {}", err));
+                return;
+            }
+        };
+
+        let needs_restart = self.server_settings.apply_live(settings);
+        // Players who join from now on see the new name and description.
+        self.server_info.name = self.server_settings.server_name.clone();
+        self.server_info.description = self.server_settings.server_description.clone();
+
+        info!("Reloaded the settings from {}", path.display());
+        self.console_reply("Reloaded the settings.".to_owned());
+        if !needs_restart.is_empty() {
+            let msg = format!(
+                "These settings changed but only take effect after a restart: {}",
+                needs_restart.join(", ")
+            );
+            warn!("{}", msg);
+            self.console_reply(msg);
+        }
+    }
+
+    /// Send a player's chat message to everyone on its channel.
+    fn route_chat(&mut self, entity: EcsEntity, chat_type: ChatType, message: String) {
+        let recipients = chat_recipients(
+            self.state.ecs(),
+            entity,
+            &chat_type,
+            self.server_settings.say_radius,
+        );
+
+        match recipients {
+            // Players can't send broadcasts or game updates, so anything else is general chat.
+            Ok(None) => self.clients.notify_registered(ServerMsg::chat(message)),
+            Ok(Some(recipients)) => {
+                for recipient in recipients {
+                    self.clients.notify(
+                        recipient,
+                        ServerMsg::ChatMsg {
+                            chat_type: chat_type.clone(),
+                            message: message.clone(),
+                        },
+                    );
+                }
+            }
+            Err(reason) => self.notify_client(entity, ServerMsg::private(reason.to_owned())),
+        }
+    }
+
+    fn console_reply(&mut self, msg: String) {
+        self.pending_events.push(Event::ConsoleOutput { msg });
+    }
+
+    /// Write the ban list, the friends lists and the roles given in game to disk. The settings
+    /// are only ever written by the operator.
+    pub fn save(&mut self) -> std::io::Result<()> {
+        self.account_roles
+            .save(&self.server_settings.account_roles_file)?;
+        self.friend_lists.save(&self.server_settings.friends_file)?;
+        self.banlist.remove_expired();
+        self.banlist.save(&self.server_settings.banlist_file)
+    }
+
+    fn save_banlist(&mut self) {
+        self.banlist.remove_expired();
+        if let Err(err) = self.banlist.save(&self.server_settings.banlist_file) {
+            warn!("Failed to save the ban list: {}", err);
+        }
+    }
+
+    fn player_by_alias(&self, alias: &str) -> Option<EcsEntity> {
+        let ecs = self.state.ecs();
+        (&ecs.entities(), &ecs.read_storage::<comp::Player>())
+            .join()
+            .find(|(_, player)| player.alias == alias)
+            .map(|(entity, _)| entity)
+    }
+
+    /// The account a player registered with.
+    fn account_of(&self, entity: EcsEntity) -> Option<&str> {
+        self.clients
+            .get(&entity)
+            .and_then(|client| client.account.as_ref())
+            .map(String::as_str)
+    }
+
+    fn player_by_account(&self, account: &str) -> Option<EcsEntity> {
+        self.clients.entity_with_account(account)
+    }
+
+    /// The account of the player online with the alias `name`, or else `name` itself, taken as
+    /// the name of an account that may be offline.
+    fn account_by_name(&self, name: &str) -> String {
+        self.player_by_alias(name)
+            .and_then(|player| self.account_of(player))
+            .unwrap_or(name)
+            .to_owned()
+    }
+
+    /// The player online with the alias `name`, or else the one who registered the account
+    /// `name`, which is how friends lists name players.
+    fn player_by_name(&self, name: &str) -> Option<EcsEntity> {
+        self.player_by_alias(name)
+            .or_else(|| self.player_by_account(name))
+    }
+
+    /// Disconnect a player, telling them and everybody else why.
+    fn kick_player(&mut self, entity: EcsEntity, reason: &str) {
+        self.remove_player(
+            entity,
+            ServerError::Kicked(reason.to_owned()),
+            &format!("was kicked: {}", reason),
+        );
+    }
+
+    /// Disconnect a player with an error, announcing it to everybody else.
+    fn remove_player(&mut self, entity: EcsEntity, error: ServerError, announcement: &str) {
+        let alias = self
+            .state
+            .read_component_cloned::<comp::Player>(entity)
+            .map(|player| player.alias);
+        let account = self.account_of(entity).map(str::to_owned);
+
+        self.clients.notify(entity, ServerMsg::Error(error));
+        self.clients.notify(entity, ServerMsg::Disconnect);
+        self.clients.remove_if(|e, _| e == entity);
+        group::forget_player(self, entity);
+        self.faction_invites.remove(&entity);
+        if let Err(err) = self.state.ecs_mut().delete_entity_synced(entity) {
+            debug!("Failed to delete kicked client: {:?}", err);
+        }
+
+        if let Some(alias) = alias {
+            self.clients
+                .notify_registered(ServerMsg::broadcast(format!("{} {}", alias, announcement)));
+        }
+        if let Some(account) = account {
+            friends::player_offline(self, &account);
+        }
+        self.pending_events
+            .push(Event::ClientDisconnected { entity });
+    }
+
+    /// Ban an account or address and kick everybody it applies to that is online. Returns a
+    /// description of the ban.
+    fn ban(&mut self, target: BanTarget, reason: String, duration: Option<Duration>) -> String {
+        let ban = self.banlist.ban(target.clone(), reason, duration).clone();
+        self.save_banlist();
+
+        let banned = match &target {
+            BanTarget::Account(account) => self.player_by_account(account).into_iter().collect(),
+            BanTarget::Ip(ip) => self.clients.entities_with_ip(*ip),
+        };
+        for entity in banned {
+            self.remove_player(
+                entity,
+                ServerError::Banned(ban.message()),
+                &format!("was banned: {}", ban.reason),
+            );
+        }
+
+        format!(
+            "Banned {} {}: {}",
+            target,
+            banlist::describe_until(ban.until),
+            ban.reason
+        )
+    }
+
+    /// Whether the player is muted, and if so the message explaining why.
+    fn mute_message(&self, entity: EcsEntity) -> Option<String> {
+        let account = self.account_of(entity)?;
+        self.banlist.find_mute(account).map(Mute::message)
+    }
+
+    /// Send a message to a client. Messages to the console are shown to the frontend instead.
+    fn notify_client(&mut self, entity: EcsEntity, msg: ServerMsg) {
+        if entity == self.console {
+            if let ServerMsg::ChatMsg { message, .. } = msg {
+                self.console_reply(message);
+            }
+        } else {
+            self.clients.notify(entity, msg);
+        }
+    }
+
+    fn save_account_roles(&self) {
+        if let Err(err) = self
+            .account_roles
+            .save(&self.server_settings.account_roles_file)
+        {
+            warn!("Failed to save the account roles: {}", err);
+        }
+    }
+
+    /// Whether the entity may do something. The console may do anything.
+    fn entity_has_permission(&self, entity: EcsEntity, permission: Permission) -> bool {
+        entity == self.console
+            || self.account_of(entity).map_or(false, |account| {
+                self.account_roles
+                    .has_permission(&self.server_settings, account, permission)
+            })
+    }
+
+    /// The first role of the player, which is shown next to their name in chat.
+    fn role_tag(&self, entity: EcsEntity) -> Option<String> {
+        let account = self.account_of(entity)?;
+        self.account_roles
+            .roles_of(&self.server_settings, account)
+            .first()
+            .map(|role| role.to_uppercase())
+    }
+}
+
+/// The players that should hear a chat message sent by `entity` on a channel, or `None` if
+/// everybody should. Fails with the reason if the sender can't use the channel.
+fn chat_recipients(
+    ecs: &specs::World,
+    entity: EcsEntity,
+    chat_type: &ChatType,
+    say_radius: f32,
+) -> Result<Option<Vec<EcsEntity>>, &'static str> {
+    let entities = ecs.entities();
+    let players = ecs.read_storage::<comp::Player>();
+    match chat_type {
+        ChatType::Say => {
+            let positions = ecs.read_storage::<comp::Pos>();
+            positions
+                .get(entity)
+                .map(|pos| {
+                    (&entities, &players, &positions)
+                        .join()
+                        .filter(|(_, _, other)| {
+                            other.0.distance_squared(pos.0) <= say_radius.powi(2)
+                        })
+                        .map(|(member, _, _)| member)
+                        .collect::<Vec<_>>()
+                })
+                .map(Some)
+                .ok_or("You need to be in the world to talk to players nearby.")
+        }
+        ChatType::Group => {
+            let groups = ecs.read_storage::<comp::Group>();
+            groups
+                .get(entity)
+                .map(|group| {
+                    (&entities, &players, &groups)
+                        .join()
+                        .filter(|(_, _, other)| *other == group)
+                        .map(|(member, _, _)| member)
+                        .collect::<Vec<_>>()
+                })
+                .map(Some)
+                .ok_or("You are not in a group.")
+        }
+        ChatType::Faction => ecs
+            .read_storage::<comp::Faction>()
             .get(entity)
-            .is_some()
+            .map(|faction| Some(faction_members(ecs, faction)))
+            .ok_or("You are not in a faction. Create one with '/faction create <name>'."),
+        _ => Ok(None),
+    }
+}
+
+/// The players in a faction.
+fn faction_members(ecs: &specs::World, faction: &comp::Faction) -> Vec<EcsEntity> {
+    (
+        &ecs.entities(),
+        &ecs.read_storage::<comp::Player>(),
+        &ecs.read_storage::<comp::Faction>(),
+    )
+        .join()
+        .filter(|(_, _, other)| *other == faction)
+        .map(|(member, _, _)| member)
+        .collect()
+}
+
+/// Whether a client may place and break blocks: they need build mode on and the permission to
+/// build.
+fn can_build(
+    state: &State,
+    server_settings: &ServerSettings,
+    account_roles: &AccountRoles,
+    entity: EcsEntity,
+    client: &Client,
+) -> bool {
+    state
+        .ecs()
+        .read_storage::<comp::CanBuild>()
+        .get(entity)
+        .is_some()
+        && client.account.as_ref().map_or(false, |account| {
+            account_roles.has_permission(server_settings, account, Permission::Build)
+        })
+}
+
+/// Load the world from the cache file named in the settings if it was generated from the same
+/// seed and options by the same version of the world generator, otherwise generate it (and cache
+/// the result).
+fn load_world(settings: &ServerSettings) -> Result<World, Error> {
+    let opts = settings.world_opts();
+    opts.validate()
+        .map_err(|err| Error::Other(format!("Invalid world options: {:?}", err)))?;
+
+    let path = match &settings.world_file {
+        Some(path) => path,
+        None => return Ok(World::generate(settings.world_seed, opts)),
+    };
+
+    if settings.regenerate_world {
+        info!("Regenerating the world as requested in the server settings");
+    } else {
+        match World::load(path, settings.world_seed, opts.clone()) {
+            Ok(world) => {
+                info!("Loaded the world from {:?}", path);
+                return Ok(world);
+            }
+            Err(CacheError::Io(ref err)) if err.kind() == std::io::ErrorKind::NotFound => {
+                info!("No cached world found at {:?}, generating one", path)
+            }
+            Err(err) => info!(
+                "Cached world at {:?} can't be used ({:?}), regenerating",
+                path, err
+            ),
+        }
+    }
+
+    let world = World::generate(settings.world_seed, opts);
+    match world.save(path) {
+        Ok(()) => info!("Saved the world to {:?}", path),
+        Err(err) => warn!("Failed to save the world to {:?}: {:?}", path, err),
+    }
+    Ok(world)
+}
+
+/// Pick the kind of creature that spawns in the wilderness of a biome.
+fn random_npc(biome: BiomeKind) -> (comp::Stats, comp::Body) {
+    let humanoid = || {
+        (
+            comp::Stats::new("Humanoid".to_string()),
+            comp::Body::Humanoid(comp::humanoid::Body::random()),
+        )
+    };
+    let wolf = |name: &str| {
+        (
+            comp::Stats::new(name.to_string()),
+            comp::Body::QuadrupedMedium(comp::quadruped_medium::Body::random()),
+        )
+    };
+    let pig = || {
+        (
+            comp::Stats::new("Boar".to_string()),
+            comp::Body::Quadruped(comp::quadruped::Body::random()),
+        )
+    };
+
+    let roll = rand::random::<f32>();
+    match biome {
+        BiomeKind::Snowlands | BiomeKind::Tundra | BiomeKind::Taiga if roll < 0.8 => {
+            wolf("Frost Wolf")
+        }
+        BiomeKind::Mountain if roll < 0.6 => wolf("Wolf"),
+        BiomeKind::Forest | BiomeKind::Jungle if roll < 0.5 => wolf("Wolf"),
+        BiomeKind::Grassland | BiomeKind::Savannah if roll < 0.5 => pig(),
+        BiomeKind::Swamp | BiomeKind::Mangrove if roll < 0.6 => pig(),
+        BiomeKind::Desert | BiomeKind::Beach => humanoid(),
+        _ if roll < 0.75 => humanoid(),
+        _ => wolf("Wolf"),
     }
 }
 
 impl Drop for Server {
     fn drop(&mut self) {
-        self.clients.notify_registered(ServerMsg::Shutdown);
+        if !self.stopped {
+            self.clients
+                .notify_registered(ServerMsg::Shutdown("The server was stopped.".to_owned()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seconds_left_are_rounded_up() {
+        assert_eq!(secs_rounded_up(Duration::from_secs(3)), 3);
+        assert_eq!(secs_rounded_up(Duration::from_millis(2001)), 3);
+        assert_eq!(secs_rounded_up(Duration::from_millis(1)), 1);
+        assert_eq!(secs_rounded_up(Duration::from_secs(0)), 0);
+    }
+
+    #[test]
+    fn shutdown_warnings_count_down() {
+        assert_eq!(next_shutdown_warning(600, None), Some(600));
+        assert_eq!(next_shutdown_warning(45, None), Some(60));
+        assert_eq!(next_shutdown_warning(700, None), None);
+
+        assert_eq!(next_shutdown_warning(60, Some(120)), Some(60));
+        assert_eq!(next_shutdown_warning(45, Some(60)), None);
+        assert_eq!(next_shutdown_warning(30, Some(60)), Some(30));
+        assert_eq!(next_shutdown_warning(30, Some(30)), None);
+        // Warnings that were skipped, e.g. by a long tick, aren't given late.
+        assert_eq!(next_shutdown_warning(1, Some(30)), Some(1));
+    }
+
+    #[test]
+    fn pending_shutdowns_warn_then_shut_down() {
+        let start = Instant::now();
+        let after = |millis| start + Duration::from_millis(millis);
+        let mut shutdown = PendingShutdown::new(start, Duration::from_secs(65), String::new());
+
+        // Players are told when the shutdown is asked for, so there's no warning straight away.
+        assert_eq!(shutdown.step(start), ShutdownStep::Wait);
+        assert_eq!(shutdown.step(after(5_000)), ShutdownStep::Warn(60));
+        assert_eq!(shutdown.step(after(5_000)), ShutdownStep::Wait);
+        assert_eq!(shutdown.step(after(6_500)), ShutdownStep::Wait);
+        assert_eq!(shutdown.step(after(35_000)), ShutdownStep::Warn(30));
+        assert_eq!(shutdown.step(after(64_200)), ShutdownStep::Warn(1));
+        assert_eq!(shutdown.step(after(64_500)), ShutdownStep::Wait);
+        assert_eq!(shutdown.step(after(65_000)), ShutdownStep::ShutDown);
+    }
+
+    const SAY_RADIUS: f32 = 100.0;
+
+    fn create_player(state: &mut State, pos: Vec3<f32>) -> EcsEntity {
+        state
+            .ecs_mut()
+            .create_entity_synced()
+            .with(comp::Player::new("Player".to_owned(), None))
+            .with(comp::Pos(pos))
+            .build()
+    }
+
+    fn recipients(state: &State, entity: EcsEntity, chat_type: ChatType) -> Vec<EcsEntity> {
+        let mut recipients = chat_recipients(state.ecs(), entity, &chat_type, SAY_RADIUS)
+            .unwrap()
+            .unwrap();
+        recipients.sort();
+        recipients
+    }
+
+    #[test]
+    fn say_reaches_players_nearby() {
+        let mut state = State::default();
+        let speaker = create_player(&mut state, Vec3::zero());
+        let near = create_player(&mut state, Vec3::new(SAY_RADIUS, 0.0, 0.0));
+        let _far = create_player(&mut state, Vec3::new(SAY_RADIUS + 1.0, 0.0, 0.0));
+        let unplaced = state
+            .ecs_mut()
+            .create_entity_synced()
+            .with(comp::Player::new("Unplaced".to_owned(), None))
+            .build();
+
+        let mut expected = vec![speaker, near];
+        expected.sort();
+        assert_eq!(recipients(&state, speaker, ChatType::Say), expected);
+        assert!(chat_recipients(state.ecs(), unplaced, &ChatType::Say, SAY_RADIUS).is_err());
+    }
+
+    #[test]
+    fn group_chat_reaches_only_members() {
+        let mut state = State::default();
+        let leader = create_player(&mut state, Vec3::zero());
+        let member = create_player(&mut state, Vec3::new(5000.0, 0.0, 0.0));
+        let other_leader = create_player(&mut state, Vec3::zero());
+        let loner = create_player(&mut state, Vec3::zero());
+        for (entity, leader) in &[
+            (leader, leader),
+            (member, leader),
+            (other_leader, other_leader),
+        ] {
+            let leader = state.ecs().uid_from_entity(*leader).unwrap();
+            state.write_component(*entity, comp::Group { leader });
+        }
+
+        let mut expected = vec![leader, member];
+        expected.sort();
+        assert_eq!(recipients(&state, member, ChatType::Group), expected);
+        assert_eq!(
+            recipients(&state, other_leader, ChatType::Group),
+            vec![other_leader]
+        );
+        assert!(chat_recipients(state.ecs(), loner, &ChatType::Group, SAY_RADIUS).is_err());
+    }
+
+    #[test]
+    fn faction_chat_reaches_only_members() {
+        let mut state = State::default();
+        let a = create_player(&mut state, Vec3::zero());
+        let b = create_player(&mut state, Vec3::new(5000.0, 0.0, 0.0));
+        let rival = create_player(&mut state, Vec3::zero());
+        let loner = create_player(&mut state, Vec3::zero());
+        state.write_component(a, comp::Faction("Order".to_owned()));
+        state.write_component(b, comp::Faction("Order".to_owned()));
+        state.write_component(rival, comp::Faction("Chaos".to_owned()));
+
+        let mut expected = vec![a, b];
+        expected.sort();
+        assert_eq!(recipients(&state, a, ChatType::Faction), expected);
+        assert_eq!(recipients(&state, rival, ChatType::Faction), vec![rival]);
+        assert!(chat_recipients(state.ecs(), loner, &ChatType::Faction, SAY_RADIUS).is_err());
+    }
+
+    #[test]
+    fn general_chat_reaches_everyone() {
+        let mut state = State::default();
+        let loner = create_player(&mut state, Vec3::zero());
+
+        assert_eq!(
+            chat_recipients(state.ecs(), loner, &ChatType::Chat, SAY_RADIUS),
+            Ok(None)
+        );
     }
 }
@@ -0,0 +1,234 @@
+//! Measurements of the server's health, served over HTTP in the Prometheus text format.
+
+use log::{info, warn};
+use std::{
+    fmt::Write as _,
+    io::{self, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+/// How long each phase of the last tick took, in seconds.
+#[derive(Clone, Debug, Default)]
+pub struct TickTimes {
+    /// Console commands, new connections, client messages and game events.
+    pub input: f64,
+    /// Ticking the ECS and the world.
+    pub state: f64,
+    /// Inserting generated chunks and dropping the ones nobody can see.
+    pub terrain: f64,
+    /// Sending entity, chunk and block changes to clients.
+    pub sync: f64,
+    /// The whole tick, including anything that isn't part of a phase.
+    pub total: f64,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ServerMetrics {
+    pub tick_times: TickTimes,
+    /// Ticks since the server started.
+    pub ticks: u64,
+    pub entities: usize,
+    pub chunks: usize,
+    /// Chunks that are being generated.
+    pub pending_chunks: usize,
+    pub clients: usize,
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+}
+
+impl ServerMetrics {
+    /// Render the metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, samples: &[(&str, String)]| {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} {}", name, kind);
+            for (labels, value) in samples {
+                let _ = writeln!(out, "{}{} {}", name, labels, value);
+            }
+        };
+
+        let times = &self.tick_times;
+        metric(
+            "veloren_tick_seconds",
+            "gauge",
+            "How long each phase of the last tick took.",
+            &[
+                ("{phase=\"input\"}", times.input.to_string()),
+                ("{phase=\"state\"}", times.state.to_string()),
+                ("{phase=\"terrain\"}", times.terrain.to_string()),
+                ("{phase=\"sync\"}", times.sync.to_string()),
+            ],
+        );
+        // Kept out of the phases so that summing over them doesn't count the tick twice.
+        metric(
+            "veloren_tick_total_seconds",
+            "gauge",
+            "How long the whole of the last tick took.",
+            &[("", times.total.to_string())],
+        );
+        metric(
+            "veloren_ticks_total",
+            "counter",
+            "Ticks since the server started.",
+            &[("", self.ticks.to_string())],
+        );
+        metric(
+            "veloren_entities",
+            "gauge",
+            "Entities in the world, including players.",
+            &[("", self.entities.to_string())],
+        );
+        metric(
+            "veloren_chunks_loaded",
+            "gauge",
+            "Terrain chunks in memory.",
+            &[("", self.chunks.to_string())],
+        );
+        metric(
+            "veloren_chunks_pending",
+            "gauge",
+            "Terrain chunks being generated.",
+            &[("", self.pending_chunks.to_string())],
+        );
+        metric(
+            "veloren_clients",
+            "gauge",
+            "Connected clients.",
+            &[("", self.clients.to_string())],
+        );
+        metric(
+            "veloren_network_sent_bytes_total",
+            "counter",
+            "Bytes sent to clients.",
+            &[("", self.bytes_sent.to_string())],
+        );
+        metric(
+            "veloren_network_received_bytes_total",
+            "counter",
+            "Bytes received from clients.",
+            &[("", self.bytes_received.to_string())],
+        );
+
+        out
+    }
+}
+
+/// Serves the latest metrics to anyone who asks for `/metrics`.
+pub struct MetricsServer {
+    metrics: Arc<Mutex<ServerMetrics>>,
+}
+
+impl MetricsServer {
+    /// Start answering requests on `addr` from another thread.
+    pub fn start(addr: SocketAddr) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        let metrics = Arc::new(Mutex::new(ServerMetrics::default()));
+
+        let served = metrics.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => {
+                        if let Err(err) = respond(stream, &served) {
+                            warn!("Failed to answer a metrics request: {}", err);
+                        }
+                    }
+                    Err(err) => warn!("Failed to accept a metrics connection: {}", err),
+                }
+            }
+        });
+        info!("Serving metrics on http://{}/metrics", addr);
+
+        Ok(Self { metrics })
+    }
+
+    /// Replace the metrics that are served with the ones from the latest tick.
+    pub fn update(&self, metrics: ServerMetrics) {
+        *self.metrics.lock().unwrap() = metrics;
+    }
+}
+
+fn respond(mut stream: TcpStream, metrics: &Mutex<ServerMetrics>) -> io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(1)))?;
+
+    // Only the request line matters, and it fits in the first read.
+    let mut buf = [0; 1024];
+    let len = stream.read(&mut buf)?;
+    let request = String::from_utf8_lossy(&buf[..len]);
+    let path = request.split_whitespace().nth(1).unwrap_or("");
+
+    let (status, body) = if request.starts_with("GET ") && (path == "/metrics" || path == "/") {
+        ("200 OK", metrics.lock().unwrap().render())
+    } else {
+        ("404 Not Found", "Not found\n".to_owned())
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\n\
+         Content-Type: text/plain; version=0.0.4\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metrics_are_rendered_in_the_text_format() {
+        let metrics = ServerMetrics {
+            tick_times: TickTimes {
+                input: 0.25,
+                state: 0.5,
+                terrain: 0.125,
+                sync: 0.0625,
+                total: 1.0,
+            },
+            ticks: 42,
+            entities: 7,
+            chunks: 300,
+            pending_chunks: 12,
+            clients: 3,
+            bytes_sent: 1024,
+            bytes_received: 512,
+        };
+        let rendered = metrics.render();
+        let lines = rendered.lines().collect::<Vec<_>>();
+
+        for expected in &[
+            "# HELP veloren_tick_seconds How long each phase of the last tick took.",
+            "# TYPE veloren_tick_seconds gauge",
+            "veloren_tick_seconds{phase=\"input\"} 0.25",
+            "veloren_tick_seconds{phase=\"state\"} 0.5",
+            "veloren_tick_seconds{phase=\"terrain\"} 0.125",
+            "veloren_tick_seconds{phase=\"sync\"} 0.0625",
+            "# TYPE veloren_tick_total_seconds gauge",
+            "veloren_tick_total_seconds 1",
+            "# TYPE veloren_ticks_total counter",
+            "veloren_ticks_total 42",
+            "veloren_entities 7",
+            "veloren_chunks_loaded 300",
+            "veloren_chunks_pending 12",
+            "veloren_clients 3",
+            "# TYPE veloren_network_sent_bytes_total counter",
+            "veloren_network_sent_bytes_total 1024",
+            "veloren_network_received_bytes_total 512",
+        ] {
+            assert!(lines.contains(expected), "missing line: {}", expected);
+        }
+        assert!(!rendered.contains("phase=\"total\""));
+        // Every sample belongs to a metric that has been described.
+        for line in lines.iter().filter(|line| !line.starts_with('#')) {
+            let name = line.split(|c| c == '{' || c == ' ').next().unwrap();
+            assert!(rendered.contains(&format!("# TYPE {} ", name)), "{}", line);
+        }
+    }
+}
@@ -0,0 +1,202 @@
+//! Named roles that grant permissions, and the roles given to each account.
+
+use crate::settings::ServerSettings;
+use hashbrown::HashMap;
+use serde_derive::{Deserialize, Serialize};
+use std::{fs, io::prelude::*, path::Path};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Permission {
+    /// Toggle build mode, then place and break blocks.
+    Build,
+    /// Move around with `/jump`, `/goto` and `/tp`.
+    Teleport,
+    /// Spawn NPCs, objects and lights, or kill all NPCs.
+    Spawn,
+    /// Change the time of day.
+    Time,
+    /// Set your own health.
+    Cheat,
+    /// Kick and mute players.
+    Moderate,
+    /// Ban and unban accounts and addresses.
+    Ban,
+    /// Give roles to accounts and take them away.
+    ManageRoles,
+}
+
+impl Permission {
+    pub const ALL: [Permission; 8] = [
+        Permission::Build,
+        Permission::Teleport,
+        Permission::Spawn,
+        Permission::Time,
+        Permission::Cheat,
+        Permission::Moderate,
+        Permission::Ban,
+        Permission::ManageRoles,
+    ];
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Role {
+    pub name: String,
+    pub permissions: Vec<Permission>,
+}
+
+impl Role {
+    pub fn new(name: &str, permissions: &[Permission]) -> Self {
+        Self {
+            name: name.to_owned(),
+            permissions: permissions.to_vec(),
+        }
+    }
+
+    /// The roles available on a new server.
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            Role::new(
+                "moderator",
+                &[Permission::Moderate, Permission::Ban, Permission::Teleport],
+            ),
+            Role::new(
+                "builder",
+                &[Permission::Build, Permission::Teleport, Permission::Time],
+            ),
+            Role::new("admin", &Permission::ALL),
+        ]
+    }
+}
+
+/// Roles given to accounts in game. An account listed here ignores the roles given to it by
+/// `ServerSettings::account_roles`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccountRoles {
+    pub accounts: HashMap<String, Vec<String>>,
+}
+
+impl AccountRoles {
+    pub fn load(path: &Path) -> Self {
+        match fs::File::open(path) {
+            Ok(file) => match ron::de::from_reader(file) {
+                Ok(account_roles) => account_roles,
+                Err(e) => {
+                    log::warn!("Failed to parse the account roles! Ignoring them. {}", e);
+                    Self::default()
+                }
+            },
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let mut file = fs::File::create(path)?;
+
+        let s: &str = &ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()).unwrap();
+        file.write_all(s.as_bytes())
+    }
+
+    /// The names of the roles an account has.
+    pub fn roles_of<'a>(&'a self, settings: &'a ServerSettings, account: &str) -> &'a [String] {
+        self.accounts
+            .get(account)
+            .or_else(|| settings.account_roles.get(account))
+            .map(|roles| roles.as_slice())
+            .unwrap_or(&[])
+    }
+
+    pub fn has_permission(
+        &self,
+        settings: &ServerSettings,
+        account: &str,
+        permission: Permission,
+    ) -> bool {
+        self.roles_of(settings, account)
+            .iter()
+            .filter_map(|name| settings.roles.iter().find(|role| &role.name == name))
+            .any(|role| role.permissions.contains(&permission))
+    }
+
+    /// Give a role to an account, returning whether it didn't have it already.
+    pub fn grant(&mut self, settings: &ServerSettings, account: &str, role: &str) -> bool {
+        let mut roles = self.roles_of(settings, account).to_vec();
+        if roles.iter().any(|r| r == role) {
+            return false;
+        }
+        roles.push(role.to_owned());
+        self.accounts.insert(account.to_owned(), roles);
+        true
+    }
+
+    /// Take a role away from an account, returning whether it had it.
+    pub fn revoke(&mut self, settings: &ServerSettings, account: &str, role: &str) -> bool {
+        let mut roles = self.roles_of(settings, account).to_vec();
+        let len = roles.len();
+        roles.retain(|r| r != role);
+        if roles.len() == len {
+            return false;
+        }
+        self.accounts.insert(account.to_owned(), roles);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> ServerSettings {
+        let mut settings = ServerSettings::default();
+        settings.account_roles.clear();
+        settings
+            .account_roles
+            .insert("builder_account".to_owned(), vec!["builder".to_owned()]);
+        settings
+    }
+
+    #[test]
+    fn permissions_come_from_roles() {
+        let settings = settings();
+        let roles = AccountRoles::default();
+
+        assert!(roles.has_permission(&settings, "builder_account", Permission::Build));
+        assert!(!roles.has_permission(&settings, "builder_account", Permission::Ban));
+        assert!(!roles.has_permission(&settings, "someone_else", Permission::Build));
+    }
+
+    #[test]
+    fn unknown_roles_grant_nothing() {
+        let settings = settings();
+        let mut roles = AccountRoles::default();
+
+        assert!(roles.grant(&settings, "account", "wizard"));
+        assert!(Permission::ALL
+            .iter()
+            .all(|p| !roles.has_permission(&settings, "account", *p)));
+    }
+
+    #[test]
+    fn granted_roles_replace_the_settings() {
+        let settings = settings();
+        let mut roles = AccountRoles::default();
+
+        assert!(roles.grant(&settings, "builder_account", "admin"));
+        assert!(!roles.grant(&settings, "builder_account", "admin"));
+        assert_eq!(
+            roles.roles_of(&settings, "builder_account"),
+            &["builder".to_owned(), "admin".to_owned()]
+        );
+
+        // Revoking a role given in the settings must not bring it back from there.
+        assert!(roles.revoke(&settings, "builder_account", "builder"));
+        assert!(!roles.revoke(&settings, "builder_account", "builder"));
+        assert_eq!(
+            roles.roles_of(&settings, "builder_account"),
+            &["admin".to_owned()]
+        );
+        assert!(roles.revoke(&settings, "builder_account", "admin"));
+        assert!(roles.roles_of(&settings, "builder_account").is_empty());
+        assert!(!roles.has_permission(&settings, "builder_account", Permission::Build));
+    }
+}
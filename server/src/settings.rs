@@ -1,5 +1,17 @@
+use crate::roles::Role;
+use common::{comp::EnergyConfig, state::DEFAULT_TICK_RATE};
+use hashbrown::HashMap;
 use serde_derive::{Deserialize, Serialize};
-use std::{fs, io::prelude::*, net::SocketAddr, path::PathBuf};
+use std::{
+    env, fs,
+    io::prelude::*,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+};
+use world::{WorldOpts, WorldPreset};
+
+/// Environment variable that overrides where the settings are read from.
+pub const SETTINGS_PATH_VAR: &str = "VELOREN_SERVER_SETTINGS";
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(default)]
@@ -7,12 +19,44 @@ pub struct ServerSettings {
     pub address: SocketAddr,
     pub max_players: usize,
     pub world_seed: u32,
+    /// The kind of world to generate.
+    pub world_preset: WorldPreset,
+    /// Detailed world generation options.  When present, these are used instead of the preset.
+    pub world_opts: Option<WorldOpts>,
+    /// Where the generated world is cached between runs, if anywhere.  The cache is only used if
+    /// it was generated from `world_seed` by the same version of the world generator.
+    pub world_file: Option<PathBuf>,
+    /// Generate the world again even if a usable cache exists.
+    pub regenerate_world: bool,
     //pub pvp_enabled: bool,
     pub server_name: String,
     pub server_description: String,
+    /// How far, in blocks, `Say` chat messages can be heard.
+    pub say_radius: f32,
     //pub login_server: whatever
     pub start_time: f64,
+    /// The roles that can be given to accounts, and the permissions they grant.
+    pub roles: Vec<Role>,
+    /// The names of the roles given to each account.
+    pub account_roles: HashMap<String, Vec<String>>,
+    /// Deprecated: the accounts that were admins before roles existed. They are given the
+    /// "admin" role in `account_roles` when the settings are read.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub admins: Vec<String>,
+    /// Where roles given to accounts in game are kept. These replace the ones in `account_roles`.
+    pub account_roles_file: PathBuf,
+    /// Where bans and mutes are kept between runs.
+    pub banlist_file: PathBuf,
+    /// Where friends lists and friend requests are kept between runs.
+    pub friends_file: PathBuf,
+    /// Simulation steps per second. Clients are told to use the same rate so that their
+    /// predictions match the server.
+    pub physics_tick_rate: u32,
+    /// Energy costs of actions and how quickly energy regenerates.
+    pub energy: EnergyConfig,
+    /// Where to serve metrics in the Prometheus text format, e.g. `"127.0.0.1:14005"`. Metrics
+    /// aren't collected when this is unset.
+    pub metrics_address: Option<SocketAddr>,
 }
 
 impl Default for ServerSettings {
@@ -20,22 +64,35 @@ impl Default for ServerSettings {
         Self {
             address: SocketAddr::from(([0; 4], 14004)),
             world_seed: 1337,
+            world_preset: WorldPreset::Default,
+            world_opts: None,
+            world_file: None,
+            regenerate_world: false,
             server_name: "Veloren Alpha".to_owned(),
             server_description: "This is the best Veloren server.".to_owned(),
+            say_radius: 48.0,
             max_players: 100,
             start_time: 9.0 * 3600.0,
-            admins: vec!["Pfau".to_owned()],
+            roles: Role::defaults(),
+            account_roles: admin_account("Pfau"),
+            admins: Vec::new(),
+            account_roles_file: PathBuf::from("account_roles.ron"),
+            banlist_file: PathBuf::from("banlist.ron"),
+            friends_file: PathBuf::from("friends.ron"),
+            physics_tick_rate: DEFAULT_TICK_RATE,
+            energy: EnergyConfig::default(),
+            metrics_address: None,
         }
     }
 }
 
 impl ServerSettings {
-    pub fn load() -> Self {
-        let path = ServerSettings::get_settings_path();
-
+    /// Load the settings from `path`, creating the file with the default settings if it doesn't
+    /// exist.
+    pub fn load(path: &Path) -> Self {
         if let Ok(file) = fs::File::open(path) {
-            match ron::de::from_reader(file) {
-                Ok(x) => x,
+            match ron::de::from_reader::<_, Self>(file) {
+                Ok(x) => x.migrate(),
                 Err(e) => {
                     log::warn!("Failed to parse setting file! Fallback to default. {}", e);
                     Self::default()
@@ -44,7 +101,7 @@ impl ServerSettings {
         } else {
             let default_settings = Self::default();
 
-            match default_settings.save_to_file() {
+            match default_settings.save_to_file(path) {
                 Err(e) => log::error!("Failed to create default setting file! {}", e),
                 _ => {}
             }
@@ -52,8 +109,85 @@ impl ServerSettings {
         }
     }
 
-    pub fn save_to_file(&self) -> std::io::Result<()> {
-        let path = ServerSettings::get_settings_path();
+    /// Read the settings again after the file changed. Unlike `load`, mistakes in the file are
+    /// reported instead of replacing the settings with the defaults.
+    pub fn reload(path: &Path) -> Result<Self, String> {
+        let file = fs::File::open(path).map_err(|e| e.to_string())?;
+        ron::de::from_reader::<_, Self>(file)
+            .map(Self::migrate)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Give the accounts in the deprecated `admins` list the "admin" role.
+    fn migrate(mut self) -> Self {
+        if !self.admins.is_empty() {
+            log::warn!(
+                "The 'admins' setting is deprecated, giving its accounts the 'admin' role. \
+                 Move them to 'account_roles' instead."
+            );
+        }
+        for admin in self.admins.drain(..) {
+            let roles = self.account_roles.entry(admin).or_default();
+            if !roles.iter().any(|role| role == "admin") {
+                roles.push("admin".to_owned());
+            }
+        }
+        self
+    }
+
+    /// Take the settings that can change while the server is running from `new`. Returns the
+    /// names of the settings that changed but only take effect after a restart.
+    pub fn apply_live(&mut self, new: ServerSettings) -> Vec<&'static str> {
+        let mut needs_restart = Vec::new();
+        let mut check = |name, changed| {
+            if changed {
+                needs_restart.push(name);
+            }
+        };
+        check("address", self.address != new.address);
+        check("world_seed", self.world_seed != new.world_seed);
+        check("world_preset", self.world_preset != new.world_preset);
+        check("world_opts", self.world_opts != new.world_opts);
+        check("world_file", self.world_file != new.world_file);
+        check(
+            "regenerate_world",
+            self.regenerate_world != new.regenerate_world,
+        );
+        check("start_time", self.start_time != new.start_time);
+        check(
+            "account_roles_file",
+            self.account_roles_file != new.account_roles_file,
+        );
+        check("banlist_file", self.banlist_file != new.banlist_file);
+        check("friends_file", self.friends_file != new.friends_file);
+        check(
+            "physics_tick_rate",
+            self.physics_tick_rate != new.physics_tick_rate,
+        );
+        check("energy", self.energy != new.energy);
+        check(
+            "metrics_address",
+            self.metrics_address != new.metrics_address,
+        );
+
+        self.max_players = new.max_players;
+        self.server_name = new.server_name;
+        self.server_description = new.server_description;
+        self.say_radius = new.say_radius;
+        self.roles = new.roles;
+        self.account_roles = new.account_roles;
+
+        needs_restart
+    }
+
+    /// The options that the world should be generated with.
+    pub fn world_opts(&self) -> WorldOpts {
+        self.world_opts
+            .clone()
+            .unwrap_or_else(|| WorldOpts::preset(self.world_preset))
+    }
+
+    pub fn save_to_file(&self, path: &Path) -> std::io::Result<()> {
         let mut config_file = fs::File::create(path)?;
 
         let s: &str = &ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()).unwrap();
@@ -65,15 +199,95 @@ impl ServerSettings {
         Self {
             address: SocketAddr::from(([0; 4], 14004)),
             world_seed: 1337,
+            world_preset: WorldPreset::Default,
+            world_opts: None,
+            world_file: None,
+            regenerate_world: false,
             server_name: "Singleplayer".to_owned(),
             server_description: "Who needs friends anyway?".to_owned(),
+            say_radius: 48.0,
             max_players: 100,
             start_time: 9.0 * 3600.0,
-            admins: vec!["singleplayer".to_string()], // TODO: Let the player choose if they want to use admin commands or not
+            roles: Role::defaults(),
+            account_roles: admin_account("singleplayer"), // TODO: Let the player choose if they want to use admin commands or not
+            admins: Vec::new(),
+            account_roles_file: PathBuf::from("account_roles.ron"),
+            banlist_file: PathBuf::from("banlist.ron"),
+            friends_file: PathBuf::from("friends.ron"),
+            physics_tick_rate: DEFAULT_TICK_RATE,
+            energy: EnergyConfig::default(),
+            metrics_address: None,
         }
     }
 
-    fn get_settings_path() -> PathBuf {
-        PathBuf::from(r"settings.ron")
+    /// Where the settings are read from when no path is given on the command line.
+    pub fn default_path() -> PathBuf {
+        env::var_os(SETTINGS_PATH_VAR)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(r"settings.ron"))
+    }
+}
+
+fn admin_account(alias: &str) -> HashMap<String, Vec<String>> {
+    let mut account_roles = HashMap::new();
+    account_roles.insert(alias.to_owned(), vec!["admin".to_owned()]);
+    account_roles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn admins_become_account_roles() {
+        let settings = ron::de::from_str::<ServerSettings>(
+            r#"(
+                account_roles: { "builder": ["builder"], "both": ["builder"] },
+                admins: ["old_admin", "both"],
+            )"#,
+        )
+        .unwrap()
+        .migrate();
+
+        assert!(settings.admins.is_empty());
+        assert_eq!(
+            settings.account_roles["old_admin"],
+            vec!["admin".to_owned()]
+        );
+        assert_eq!(
+            settings.account_roles["both"],
+            vec!["builder".to_owned(), "admin".to_owned()]
+        );
+        assert_eq!(
+            settings.account_roles["builder"],
+            vec!["builder".to_owned()]
+        );
+    }
+
+    #[test]
+    fn only_live_settings_are_applied() {
+        let mut settings = ServerSettings::default();
+        let old_seed = settings.world_seed;
+        let new = ServerSettings {
+            server_name: "Renamed".to_owned(),
+            say_radius: 10.0,
+            world_seed: old_seed + 1,
+            banlist_file: PathBuf::from("other_banlist.ron"),
+            ..ServerSettings::default()
+        };
+
+        let needs_restart = settings.apply_live(new);
+
+        assert_eq!(settings.server_name, "Renamed");
+        assert!((settings.say_radius - 10.0).abs() < std::f32::EPSILON);
+        assert_eq!(settings.world_seed, old_seed);
+        assert_eq!(settings.banlist_file, PathBuf::from("banlist.ron"));
+        assert_eq!(needs_restart, vec!["world_seed", "banlist_file"]);
+    }
+
+    #[test]
+    fn unchanged_settings_need_no_restart() {
+        let mut settings = ServerSettings::default();
+        assert!(settings.apply_live(ServerSettings::default()).is_empty());
     }
 }
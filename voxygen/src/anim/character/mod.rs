@@ -8,6 +8,7 @@ pub mod idle;
 pub mod jump;
 pub mod roll;
 pub mod run;
+pub mod swim;
 
 // Reexports
 pub use self::attack::AttackAnimation;
@@ -20,6 +21,7 @@ pub use self::idle::IdleAnimation;
 pub use self::jump::JumpAnimation;
 pub use self::roll::RollAnimation;
 pub use self::run::RunAnimation;
+pub use self::swim::SwimAnimation;
 
 use super::{Bone, Skeleton};
 use crate::render::FigureBoneData;
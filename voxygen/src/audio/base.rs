@@ -1,5 +1,5 @@
 use crate::settings::{AudioSettings, Settings};
-use common::assets::read_dir;
+use common::{assets::read_dir, terrain::BiomeKind};
 use crossbeam::{
     atomic::AtomicCell,
     channel::{unbounded, Sender},
@@ -399,6 +399,37 @@ pub(crate) fn select_random_music(genre: &Genre) -> String {
     soundtracks[index].clone()
 }
 
+/// The soundtracks that suit a biome, by file name.
+fn biome_soundtracks(biome: BiomeKind) -> &'static [&'static str] {
+    match biome {
+        BiomeKind::Snowlands | BiomeKind::Tundra | BiomeKind::Taiga | BiomeKind::Mountain => {
+            &["Snowtop_volume", "Mineral_Deposits"]
+        }
+        BiomeKind::Grassland | BiomeKind::Savannah => &["field_grazing", "Ethereal_Bonds"],
+        BiomeKind::Desert | BiomeKind::Beach => &["Ruination", "sacred_temple"],
+        BiomeKind::Forest | BiomeKind::Jungle | BiomeKind::Swamp | BiomeKind::Mangrove => {
+            &["Ethereal_Bonds", "library_theme_with_harpsichord"]
+        }
+        BiomeKind::Ocean | BiomeKind::Lake => &["fiesta_del_pueblo", "Ethereal_Bonds"],
+        BiomeKind::Void => &[],
+    }
+}
+
+/// Select a soundtrack that suits the biome, falling back to any soundtrack if there are none.
+pub(crate) fn select_music(genre: &Genre, biome: Option<BiomeKind>) -> String {
+    let names = biome.map(biome_soundtracks).unwrap_or(&[]);
+    let soundtracks = load_soundtracks(genre)
+        .into_iter()
+        .filter(|path| names.iter().any(|name| path.contains(name)))
+        .collect::<Vec<_>>();
+
+    if soundtracks.is_empty() {
+        select_random_music(genre)
+    } else {
+        soundtracks[rand::random::<usize>() % soundtracks.len()].clone()
+    }
+}
+
 /// Returns a vec of the audio devices available.
 /// Does not return rodio Device struct in case our audio backend changes.
 pub(crate) fn list_devices() -> Vec<String> {
@@ -1,10 +1,13 @@
 pub mod base;
 use base::{Genre, Jukebox};
+use common::terrain::BiomeKind;
 
 pub struct AudioFrontend {
     pub(crate) model: Jukebox,
     pub(crate) default_device: String,
     pub(crate) device_list: Vec<String>,
+    /// The biome the player is in, used to pick the next soundtrack.
+    biome: Option<BiomeKind>,
 }
 
 impl AudioFrontend {
@@ -13,12 +16,18 @@ impl AudioFrontend {
             model: Jukebox::new(Genre::Bgm),
             default_device: base::get_default_device(),
             device_list: base::list_devices(),
+            biome: None,
         }
     }
 
+    /// Set the biome that the next soundtrack should suit, or `None` outside of the game.
+    pub(crate) fn set_biome(&mut self, biome: Option<BiomeKind>) {
+        self.biome = biome;
+    }
+
     /// Play audio.
     pub(crate) fn play(&mut self) {
-        let path = base::select_random_music(&Genre::Bgm);
+        let path = base::select_music(&Genre::Bgm, self.biome);
 
         match self.model.player.is_paused() {
             true => match self.model.get_genre() {
@@ -36,6 +45,7 @@ impl AudioFrontend {
             model: Jukebox::new(Genre::None),
             default_device: "None".to_owned(),
             device_list: Vec::new(),
+            biome: None,
         }
     }
 }
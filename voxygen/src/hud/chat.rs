@@ -25,6 +25,20 @@ widget_ids! {
 
 const MAX_MESSAGES: usize = 100;
 
+/// Find the channel a message typed into the chat box is for. Messages starting with `!s`, `!g`
+/// or `!f` are said to players nearby, the group or the faction, and anything else goes to
+/// general chat.
+fn channel(input: &str) -> (ChatType, &str) {
+    let mut words = input.splitn(2, ' ');
+    let chat_type = match words.next() {
+        Some("!s") => ChatType::Say,
+        Some("!g") => ChatType::Group,
+        Some("!f") => ChatType::Faction,
+        _ => return (ChatType::Chat, input),
+    };
+    (chat_type, words.next().unwrap_or("").trim())
+}
+
 #[derive(WidgetCommon)]
 pub struct Chat<'a> {
     new_messages: &'a mut VecDeque<ClientEvent>,
@@ -87,8 +101,10 @@ pub struct State {
 }
 
 pub enum Event {
-    SendMessage(String),
+    SendMessage(ChatType, String),
     Focus(Id),
+    /// Tab was pressed while typing a command.
+    TabComplete(String),
 }
 
 impl<'a> Widget for Chat<'a> {
@@ -164,7 +180,7 @@ impl<'a> Widget for Chat<'a> {
                 .set(state.ids.input, ui)
             {
                 let mut input = str.to_owned();
-                input.retain(|c| c != '\n');
+                input.retain(|c| c != '\n' && c != '\t');
                 state.update(|s| s.input = input);
             }
         }
@@ -265,11 +281,50 @@ impl<'a> Widget for Chat<'a> {
                 _ => false,
             })
         {
-            let msg = state.input.clone();
+            let (chat_type, msg) = channel(&state.input);
+            let event = if msg.is_empty() {
+                None
+            } else {
+                Some(Event::SendMessage(chat_type, msg.to_owned()))
+            };
             state.update(|s| s.input.clear());
-            Some(Event::SendMessage(msg))
+            event
+        }
+        // If tab is pressed while typing a command, complete it.
+        else if state.input.starts_with('/')
+            && ui
+                .widget_input(state.ids.input)
+                .presses()
+                .key()
+                .any(|key_press| key_press.key == Key::Tab)
+        {
+            Some(Event::TabComplete(state.input.clone()))
         } else {
             None
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefixes_pick_the_channel() {
+        assert_eq!(channel("!s hello there"), (ChatType::Say, "hello there"));
+        assert_eq!(channel("!g  regroup "), (ChatType::Group, "regroup"));
+        assert_eq!(
+            channel("!f for the order"),
+            (ChatType::Faction, "for the order")
+        );
+        assert_eq!(channel("!f"), (ChatType::Faction, ""));
+    }
+
+    #[test]
+    fn anything_else_is_general_chat() {
+        assert_eq!(channel("hello"), (ChatType::Chat, "hello"));
+        assert_eq!(channel("!shout"), (ChatType::Chat, "!shout"));
+        assert_eq!(channel("!x message"), (ChatType::Chat, "!x message"));
+        assert_eq!(channel(" !s message"), (ChatType::Chat, " !s message"));
+    }
+}
@@ -1,11 +1,17 @@
 use conrod_core::{
-    color,
+    color, image,
     widget::{self, Button, Image, Rectangle, Text},
     widget_ids, Colorable, Positionable, Sizeable, Widget, WidgetCommon,
 };
 
-use super::{img_ids::Imgs, Fonts, Show, TEXT_COLOR_2};
+use super::{img_ids::Imgs, Fonts, Show, TEXT_COLOR, TEXT_COLOR_2};
+use ::image::{DynamicImage, Rgba, RgbaImage};
 use client::{self, Client};
+use common::{comp, msg::WorldMap};
+use vek::*;
+
+/// Size of the largest side of the map image, in UI units.
+const MAP_SIZE: f64 = 700.0;
 
 widget_ids! {
     struct Ids {
@@ -19,6 +25,9 @@ widget_ids! {
         map_frame_bl,
         map_frame_br,
         location_name,
+        world_map,
+        player_marker,
+        location_names[],
     }
 }
 
@@ -28,17 +37,25 @@ pub struct Map<'a> {
     client: &'a Client,
 
     imgs: &'a Imgs,
-    _fonts: &'a Fonts,
+    fonts: &'a Fonts,
+    world_map: Option<image::Id>,
     #[conrod(common_builder)]
     common: widget::CommonBuilder,
 }
 impl<'a> Map<'a> {
-    pub fn new(show: &'a Show, client: &'a Client, imgs: &'a Imgs, fonts: &'a Fonts) -> Self {
+    pub fn new(
+        show: &'a Show,
+        client: &'a Client,
+        imgs: &'a Imgs,
+        fonts: &'a Fonts,
+        world_map: Option<image::Id>,
+    ) -> Self {
         Self {
             _show: show,
             imgs,
             client,
-            _fonts: fonts,
+            fonts,
+            world_map,
             common: widget::CommonBuilder::default(),
         }
     }
@@ -128,6 +145,67 @@ impl<'a> Widget for Map<'a> {
                 .set(state.ids.location_name, ui),
         }
 
+        // World map
+        if let (Some(map_img), Some(world_map)) = (self.world_map, self.client.world_map()) {
+            let dims = world_map.dimensions.map(|e| e as f64);
+            let map_size = dims * MAP_SIZE / dims.reduce_partial_max();
+            Image::new(map_img)
+                .w_h(map_size.x, map_size.y)
+                .mid_top_with_margin_on(state.ids.map_bg, 120.0)
+                .set(state.ids.world_map, ui);
+
+            // Convert a position in the world into an offset from the centre of the map.
+            let world_size = world_map.world_size.map(|e| e as f64);
+            let map_offset = |pos: Vec2<f64>| (pos / world_size - 0.5) * map_size;
+
+            if state.ids.location_names.len() < world_map.locations.len() {
+                state.update(|s| {
+                    s.ids
+                        .location_names
+                        .resize(world_map.locations.len(), &mut ui.widget_id_generator())
+                });
+            }
+            for (loc, id) in world_map
+                .locations
+                .iter()
+                .zip(state.ids.location_names.iter())
+            {
+                let offset = map_offset(loc.pos.map(|e| e as f64));
+                Text::new(&loc.name)
+                    .x_y_relative_to(state.ids.world_map, offset.x, offset.y)
+                    .font_size(14)
+                    .font_id(self.fonts.opensans)
+                    .color(TEXT_COLOR)
+                    .set(*id, ui);
+            }
+
+            if let Some(pos) = self
+                .client
+                .state()
+                .ecs()
+                .read_storage::<comp::Pos>()
+                .get(self.client.entity())
+            {
+                let offset = map_offset(Vec2::from(pos.0).map(|e: f32| e as f64));
+                Rectangle::fill_with([8.0, 8.0], color::RED)
+                    .x_y_relative_to(state.ids.world_map, offset.x, offset.y)
+                    .set(state.ids.player_marker, ui);
+            }
+        }
+
         None
     }
 }
+
+/// Build the image for the map from the colours sent by the server.
+pub fn world_map_image(world_map: &WorldMap) -> DynamicImage {
+    let dims = world_map.dimensions;
+    DynamicImage::ImageRgba8(RgbaImage::from_fn(dims.x, dims.y, |x, y| {
+        let col = world_map
+            .colors
+            .get((y * dims.x + x) as usize)
+            .copied()
+            .unwrap_or(Rgb::zero());
+        Rgba([col.r, col.g, col.b, 255])
+    }))
+}
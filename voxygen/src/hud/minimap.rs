@@ -1,18 +1,25 @@
 use conrod_core::{
-    color,
+    color, image,
     widget::{self, Button, Image, Rectangle, Text},
-    widget_ids, Color, Colorable, Positionable, Sizeable, Widget, WidgetCommon,
+    widget_ids, Color, Colorable, Positionable, Rect, Sizeable, Widget, WidgetCommon,
 };
 
 use std::time::{Duration, Instant};
 
 use super::{img_ids::Imgs, Fonts, Show, HP_COLOR, TEXT_COLOR};
 use client::{self, Client};
+use common::comp;
+use vek::*;
+
+/// How far from the player the minimap reaches, in blocks.
+const MINIMAP_RADIUS: f64 = 1024.0;
 
 widget_ids! {
     struct Ids {
         mmap_frame,
         mmap_frame_bg,
+        mmap_map,
+        mmap_marker,
         mmap_location,
         mmap_button,
         zone_display,
@@ -27,17 +34,25 @@ pub struct MiniMap<'a> {
 
     imgs: &'a Imgs,
     _fonts: &'a Fonts,
+    world_map: Option<image::Id>,
     #[conrod(common_builder)]
     common: widget::CommonBuilder,
 }
 
 impl<'a> MiniMap<'a> {
-    pub fn new(show: &'a Show, client: &'a Client, imgs: &'a Imgs, fonts: &'a Fonts) -> Self {
+    pub fn new(
+        show: &'a Show,
+        client: &'a Client,
+        imgs: &'a Imgs,
+        fonts: &'a Fonts,
+        world_map: Option<image::Id>,
+    ) -> Self {
         Self {
             show,
             client,
             imgs,
             _fonts: fonts,
+            world_map,
             common: widget::CommonBuilder::default(),
         }
     }
@@ -83,7 +98,37 @@ impl<'a> Widget for MiniMap<'a> {
 
             Rectangle::fill_with([92.0 * 2.0, 82.0 * 2.0], color::TRANSPARENT)
                 .mid_top_with_margin_on(state.ids.mmap_frame, 13.0 * 2.0 + 2.0)
+                .crop_kids()
                 .set(state.ids.mmap_frame_bg, ui);
+
+            let player_pos = self
+                .client
+                .state()
+                .ecs()
+                .read_storage::<comp::Pos>()
+                .get(self.client.entity())
+                .map(|pos| Vec2::from(pos.0).map(|e: f32| e as f64));
+            if let (Some(map_img), Some(world_map), Some(player_pos)) =
+                (self.world_map, self.client.world_map(), player_pos)
+            {
+                // Show the part of the map around the player, measured in map pixels.
+                let scale =
+                    world_map.dimensions.map(|e| e as f64) / world_map.world_size.map(|e| e as f64);
+                let center = player_pos * scale;
+                // The frame is wider than it is tall.
+                let radius = scale * Vec2::new(MINIMAP_RADIUS, MINIMAP_RADIUS * 82.0 / 92.0);
+                Image::new(map_img)
+                    .source_rectangle(Rect::from_corners(
+                        (center - radius).into_array(),
+                        (center + radius).into_array(),
+                    ))
+                    .w_h(92.0 * 2.0, 82.0 * 2.0)
+                    .middle_of(state.ids.mmap_frame_bg)
+                    .set(state.ids.mmap_map, ui);
+                Rectangle::fill_with([4.0, 4.0], color::RED)
+                    .middle_of(state.ids.mmap_frame_bg)
+                    .set(state.ids.mmap_marker, ui);
+            }
         } else {
             Image::new(self.imgs.mmap_frame_closed)
                 .w_h(100.0 * 2.0, 11.0 * 2.0)
@@ -122,7 +167,8 @@ impl<'a> Widget for MiniMap<'a> {
 
         match self.client.current_chunk() {
             Some(chunk) => {
-                let current = chunk.meta().name();
+                // Settlements announce themselves over the surrounding region
+                let current = chunk.meta().settlement().unwrap_or(chunk.meta().name());
                 // Check if no other popup is displayed and a new one is needed
                 if state.last_update.elapsed()
                     > Duration::from_secs_f32(FADE_IN + FADE_HOLD + FADE_OUT)
@@ -159,8 +205,6 @@ impl<'a> Widget for MiniMap<'a> {
                 .set(state.ids.zone_display, ui),
         }
 
-        // TODO: Subregion name display
-
         // Title
         match self.client.current_chunk() {
             Some(chunk) => Text::new(chunk.meta().name())
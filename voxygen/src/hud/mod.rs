@@ -33,19 +33,20 @@ use crate::{
     render::{Consts, Globals, Renderer},
     scene::camera::Camera,
     settings::ControlSettings,
-    ui::{Ingameable, ScaleMode, Ui},
+    ui::{Graphic, Ingameable, ScaleMode, Ui},
     window::{Event as WinEvent, GameInput},
     GlobalState,
 };
 use client::{Client, Event as ClientEvent};
-use common::{comp, terrain::TerrainChunkSize, vol::VolSize};
+use common::{comp, terrain::TerrainChunkSize, vol::VolSize, ChatType};
 use conrod_core::{
+    image,
     text::cursor::Index,
     widget::{self, Button, Image, Rectangle, Text},
     widget_ids, Color, Colorable, Labelable, Positionable, Sizeable, Widget,
 };
 use specs::Join;
-use std::collections::VecDeque;
+use std::{collections::VecDeque, sync::Arc};
 use vek::*;
 
 #[cfg(feature = "discord")]
@@ -78,6 +79,10 @@ widget_ids! {
         crosshair_inner,
         crosshair_outer,
 
+        // Mining progress
+        mining_bg,
+        mining_bar,
+
         // Character Names
         name_tags[],
         // Health Bars
@@ -146,7 +151,9 @@ pub struct DebugInfo {
 }
 
 pub enum Event {
-    SendMessage(String),
+    SendMessage(ChatType, String),
+    /// Ask the server how to complete a partly typed chat command.
+    RequestCompletions(String),
     AdjustMousePan(u32),
     AdjustMouseZoom(u32),
     AdjustViewDistance(u32),
@@ -164,6 +171,11 @@ pub enum Event {
     CharacterSelection,
     SwapInventorySlots(usize, usize),
     DropInventorySlot(usize),
+    AcceptGroupInvite,
+    DeclineGroupInvite,
+    LeaveGroup,
+    AcceptFriendRequest(String),
+    DeclineFriendRequest(String),
     Logout,
     Quit,
 }
@@ -367,6 +379,8 @@ pub struct Hud {
     ids: Ids,
     imgs: Imgs,
     fonts: Fonts,
+    /// The world map, once the server has sent it.
+    world_map: Option<image::Id>,
     new_messages: VecDeque<ClientEvent>,
     inventory_space: usize,
     show: Show,
@@ -374,6 +388,8 @@ pub struct Hud {
     force_ungrab: bool,
     force_chat_input: Option<String>,
     force_chat_cursor: Option<Index>,
+    /// The chat command being completed and which of its completions is shown, if one is yet.
+    chat_completion: Option<(String, Option<usize>)>,
 }
 
 impl Hud {
@@ -395,6 +411,7 @@ impl Hud {
             imgs,
             fonts,
             ids,
+            world_map: None,
             new_messages: VecDeque::new(),
             inventory_space: 8,
             show: Show {
@@ -420,9 +437,18 @@ impl Hud {
             force_ungrab: false,
             force_chat_input: None,
             force_chat_cursor: None,
+            chat_completion: None,
         }
     }
 
+    fn show_chat_completion(&mut self, completion: String) {
+        self.force_chat_cursor = Some(Index {
+            line: 0,
+            char: completion.chars().count(),
+        });
+        self.force_chat_input = Some(completion);
+    }
+
     fn update_layout(
         &mut self,
         client: &Client,
@@ -430,6 +456,16 @@ impl Hud {
         debug_info: DebugInfo,
     ) -> Vec<Event> {
         let mut events = Vec::new();
+
+        if self.world_map.is_none() {
+            if let Some(world_map) = client.world_map() {
+                self.world_map = Some(
+                    self.ui
+                        .add_graphic(Graphic::Image(Arc::new(map::world_map_image(world_map)))),
+                );
+            }
+        }
+
         let ref mut ui_widgets = self.ui.set_widgets().0;
 
         let version = format!("{}-{}", env!("CARGO_PKG_VERSION"), common::util::GIT_HASH);
@@ -459,6 +495,23 @@ impl Hud {
                 .color(Some(Color::Rgba(1.0, 1.0, 1.0, 0.6)))
                 .set(self.ids.crosshair_inner, ui_widgets);
 
+            // Mining progress
+            if let Some(mining) = client
+                .state()
+                .read_storage::<comp::Mining>()
+                .get(client.entity())
+            {
+                Rectangle::fill_with([80.0, 8.0], Color::Rgba(0.0, 0.0, 0.0, 0.5))
+                    .mid_bottom_with_margin_on(self.ids.crosshair_outer, -20.0)
+                    .set(self.ids.mining_bg, ui_widgets);
+                Rectangle::fill_with(
+                    [80.0 * mining.progress.max(0.0).min(1.0) as f64, 8.0],
+                    TEXT_COLOR,
+                )
+                .top_left_of(self.ids.mining_bg)
+                .set(self.ids.mining_bar, ui_widgets);
+            }
+
             // Nametags and healthbars
             let ecs = client.state().ecs();
             let pos = ecs.read_storage::<comp::Pos>();
@@ -696,7 +749,7 @@ impl Hud {
         }
 
         // MiniMap
-        match MiniMap::new(&self.show, client, &self.imgs, &self.fonts)
+        match MiniMap::new(&self.show, client, &self.imgs, &self.fonts, self.world_map)
             .set(self.ids.minimap, ui_widgets)
         {
             Some(minimap::Event::Toggle) => self.show.toggle_mini_map(),
@@ -728,6 +781,24 @@ impl Hud {
         }
 
         // Chat box
+        // Show the first completion of a command once the server answers.
+        let answer = match &self.chat_completion {
+            Some((line, None)) => client
+                .completions(line)
+                .map(|completions| completions.first().cloned()),
+            _ => None,
+        };
+        match answer {
+            Some(Some(completion)) => {
+                if let Some((_, shown)) = &mut self.chat_completion {
+                    *shown = Some(0);
+                }
+                self.show_chat_completion(completion);
+            }
+            Some(None) => self.chat_completion = None,
+            None => {}
+        }
+
         let mut chat = Chat::new(&mut self.new_messages, &self.imgs, &self.fonts);
 
         if let Some(input) = self.force_chat_input.take() {
@@ -739,12 +810,39 @@ impl Hud {
         }
 
         match chat.set(self.ids.chat, ui_widgets) {
-            Some(chat::Event::SendMessage(message)) => {
-                events.push(Event::SendMessage(message));
+            Some(chat::Event::SendMessage(chat_type, message)) => {
+                events.push(Event::SendMessage(chat_type, message));
             }
             Some(chat::Event::Focus(focus_id)) => {
                 self.to_focus = Some(Some(focus_id));
             }
+            Some(chat::Event::TabComplete(input)) => {
+                // Cycle through the completions if the input is still one of them, otherwise
+                // ask for the completions of the input.
+                let next = match &self.chat_completion {
+                    Some((line, Some(shown))) => client.completions(line).and_then(|completions| {
+                        if *line == input || completions.contains(&input) {
+                            let next = (shown + 1) % completions.len();
+                            Some((next, completions[next].clone()))
+                        } else {
+                            None
+                        }
+                    }),
+                    _ => None,
+                };
+                match next {
+                    Some((next, completion)) => {
+                        if let Some((_, shown)) = &mut self.chat_completion {
+                            *shown = Some(next);
+                        }
+                        self.show_chat_completion(completion);
+                    }
+                    None => {
+                        self.chat_completion = Some((input.clone(), None));
+                        events.push(Event::RequestCompletions(input));
+                    }
+                }
+            }
             None => {}
         }
         self.new_messages = VecDeque::new();
@@ -825,6 +923,15 @@ impl Hud {
                     social::Event::ChangeSocialTab(social_tab) => {
                         self.show.open_social_tab(social_tab)
                     }
+                    social::Event::AcceptGroupInvite => events.push(Event::AcceptGroupInvite),
+                    social::Event::DeclineGroupInvite => events.push(Event::DeclineGroupInvite),
+                    social::Event::LeaveGroup => events.push(Event::LeaveGroup),
+                    social::Event::AcceptFriendRequest(alias) => {
+                        events.push(Event::AcceptFriendRequest(alias))
+                    }
+                    social::Event::DeclineFriendRequest(alias) => {
+                        events.push(Event::DeclineFriendRequest(alias))
+                    }
                 }
             }
         }
@@ -872,7 +979,7 @@ impl Hud {
         }
         // Map
         if self.show.map {
-            match Map::new(&self.show, client, &self.imgs, &self.fonts)
+            match Map::new(&self.show, client, &self.imgs, &self.fonts, self.world_map)
                 .set(self.ids.map, ui_widgets)
             {
                 Some(map::Event::Close) => {
@@ -21,12 +21,21 @@ widget_ids! {
         content_align,
         online_tab,
         friends_tab,
-        faction_tab,
+        group_tab,
         online_title,
         online_no,
         scrollbar,
-        friends_test,
-        faction_test,
+        friends_title,
+        friends_names[],
+        friend_request_text,
+        friend_accept,
+        friend_decline,
+        group_title,
+        group_members[],
+        group_invite_text,
+        group_accept,
+        group_decline,
+        group_leave,
         player_names[],
     }
 }
@@ -34,7 +43,7 @@ widget_ids! {
 pub enum SocialTab {
     Online,
     Friends,
-    Faction,
+    Group,
 }
 
 #[derive(WidgetCommon)]
@@ -66,6 +75,11 @@ impl<'a> Social<'a> {
 pub enum Event {
     Close,
     ChangeSocialTab(SocialTab),
+    AcceptGroupInvite,
+    DeclineGroupInvite,
+    LeaveGroup,
+    AcceptFriendRequest(String),
+    DeclineFriendRequest(String),
 }
 
 impl<'a> Widget for Social<'a> {
@@ -228,7 +242,7 @@ impl<'a> Widget for Social<'a> {
         .label("Friends")
         .label_font_size(14)
         .parent(ids.frame)
-        .label_color(TEXT_COLOR_3)
+        .label_color(TEXT_COLOR)
         .set(ids.friends_tab, ui)
         .was_clicked()
         {
@@ -238,16 +252,89 @@ impl<'a> Widget for Social<'a> {
         // Contents
 
         if let SocialTab::Friends = self.show.social_tab {
-            Text::new("Not yet available")
-                .middle_of(ids.content_align)
-                .font_size(18)
+            let friends = self.client.friends();
+            let online = friends
+                .iter()
+                .filter(|friend| friend.alias.is_some())
+                .count();
+
+            Text::new(&if friends.is_empty() {
+                "You have no friends yet.\nAdd players with '/friend add <name>'.".to_owned()
+            } else {
+                format!("{}/{} online\n", online, friends.len())
+            })
+            .top_left_with_margins_on(ids.content_align, -2.0, 7.0)
+            .font_size(14)
+            .font_id(self.fonts.opensans)
+            .color(TEXT_COLOR)
+            .set(ids.friends_title, ui);
+
+            if ids.friends_names.len() < friends.len() {
+                ids.update(|ids| {
+                    ids.friends_names
+                        .resize(friends.len(), &mut ui.widget_id_generator())
+                })
+            }
+            // Online friends first.
+            let mut sorted = friends.iter().collect::<Vec<_>>();
+            sorted.sort_by_key(|friend| friend.alias.is_none());
+            for (i, friend) in sorted.into_iter().enumerate() {
+                Text::new(&match &friend.alias {
+                    Some(alias) if alias != &friend.account => {
+                        format!("{} ({}, Online)", alias, friend.account)
+                    }
+                    Some(_) => format!("{} (Online)", friend.account),
+                    None => format!("{} (Offline)", friend.account),
+                })
+                .down_from(ids.friends_title, i as f64 * (15.0 + 3.0))
+                .font_size(15)
                 .font_id(self.fonts.opensans)
-                .color(TEXT_COLOR_3)
-                .set(ids.friends_test, ui);
+                .color(if friend.alias.is_some() {
+                    TEXT_COLOR
+                } else {
+                    TEXT_COLOR_3
+                })
+                .set(ids.friends_names[i], ui);
+            }
+
+            if let Some(alias) = self.client.friend_requests().first() {
+                Text::new(&format!("{} wants to be your friend.", alias))
+                    .mid_bottom_with_margin_on(ids.content_align, 60.0)
+                    .font_size(14)
+                    .font_id(self.fonts.opensans)
+                    .color(TEXT_COLOR)
+                    .set(ids.friend_request_text, ui);
+                if Button::image(self.imgs.button)
+                    .w_h(100.0, 30.0)
+                    .hover_image(self.imgs.button_hover)
+                    .press_image(self.imgs.button_press)
+                    .bottom_left_with_margins_on(ids.content_align, 15.0, 70.0)
+                    .label("Accept")
+                    .label_font_size(14)
+                    .label_color(TEXT_COLOR)
+                    .set(ids.friend_accept, ui)
+                    .was_clicked()
+                {
+                    events.push(Event::AcceptFriendRequest(alias.clone()));
+                }
+                if Button::image(self.imgs.button)
+                    .w_h(100.0, 30.0)
+                    .hover_image(self.imgs.button_hover)
+                    .press_image(self.imgs.button_press)
+                    .right_from(ids.friend_accept, 20.0)
+                    .label("Decline")
+                    .label_font_size(14)
+                    .label_color(TEXT_COLOR)
+                    .set(ids.friend_decline, ui)
+                    .was_clicked()
+                {
+                    events.push(Event::DeclineFriendRequest(alias.clone()));
+                }
+            }
         }
 
-        // Faction Tab
-        let button_img = if let SocialTab::Faction = self.show.social_tab {
+        // Group Tab
+        let button_img = if let SocialTab::Group = self.show.social_tab {
             self.imgs.social_button_pressed
         } else {
             self.imgs.social_button
@@ -255,25 +342,111 @@ impl<'a> Widget for Social<'a> {
         if Button::image(button_img)
             .w_h(30.0 * 4.0, 12.0 * 4.0)
             .right_from(ids.friends_tab, 0.0)
-            .label("Faction")
+            .label("Group")
             .parent(ids.frame)
             .label_font_size(14)
-            .label_color(TEXT_COLOR_3)
-            .set(ids.faction_tab, ui)
+            .label_color(TEXT_COLOR)
+            .set(ids.group_tab, ui)
             .was_clicked()
         {
-            events.push(Event::ChangeSocialTab(SocialTab::Faction));
+            events.push(Event::ChangeSocialTab(SocialTab::Group));
         }
 
         // Contents
 
-        if let SocialTab::Faction = self.show.social_tab {
-            Text::new("Not yet available")
-                .middle_of(ids.content_align)
-                .font_size(18)
-                .font_id(self.fonts.opensans)
-                .color(TEXT_COLOR_3)
-                .set(ids.faction_test, ui);
+        if let SocialTab::Group = self.show.social_tab {
+            let members = self
+                .client
+                .group()
+                .map(|group| {
+                    group
+                        .members
+                        .iter()
+                        .map(|(uid, alias)| {
+                            if *uid == group.leader {
+                                format!("{} (Leader)", alias)
+                            } else {
+                                alias.clone()
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+
+            Text::new(&if members.is_empty() {
+                "You are not in a group.\nInvite players with '/group invite <name>'.".to_owned()
+            } else {
+                format!("{} member(s)\n", members.len())
+            })
+            .top_left_with_margins_on(ids.content_align, -2.0, 7.0)
+            .font_size(14)
+            .font_id(self.fonts.opensans)
+            .color(TEXT_COLOR)
+            .set(ids.group_title, ui);
+
+            if ids.group_members.len() < members.len() {
+                ids.update(|ids| {
+                    ids.group_members
+                        .resize(members.len(), &mut ui.widget_id_generator())
+                })
+            }
+            for (i, member) in members.iter().enumerate() {
+                Text::new(member)
+                    .down_from(ids.group_title, i as f64 * (15.0 + 3.0))
+                    .font_size(15)
+                    .font_id(self.fonts.opensans)
+                    .color(TEXT_COLOR)
+                    .set(ids.group_members[i], ui);
+            }
+
+            if let Some((_, inviter)) = self.client.group_invite() {
+                Text::new(&format!("{} invited you to their group.", inviter))
+                    .mid_bottom_with_margin_on(ids.content_align, 60.0)
+                    .font_size(14)
+                    .font_id(self.fonts.opensans)
+                    .color(TEXT_COLOR)
+                    .set(ids.group_invite_text, ui);
+                if Button::image(self.imgs.button)
+                    .w_h(100.0, 30.0)
+                    .hover_image(self.imgs.button_hover)
+                    .press_image(self.imgs.button_press)
+                    .bottom_left_with_margins_on(ids.content_align, 15.0, 70.0)
+                    .label("Accept")
+                    .label_font_size(14)
+                    .label_color(TEXT_COLOR)
+                    .set(ids.group_accept, ui)
+                    .was_clicked()
+                {
+                    events.push(Event::AcceptGroupInvite);
+                }
+                if Button::image(self.imgs.button)
+                    .w_h(100.0, 30.0)
+                    .hover_image(self.imgs.button_hover)
+                    .press_image(self.imgs.button_press)
+                    .right_from(ids.group_accept, 20.0)
+                    .label("Decline")
+                    .label_font_size(14)
+                    .label_color(TEXT_COLOR)
+                    .set(ids.group_decline, ui)
+                    .was_clicked()
+                {
+                    events.push(Event::DeclineGroupInvite);
+                }
+            } else if !members.is_empty() {
+                if Button::image(self.imgs.button)
+                    .w_h(100.0, 30.0)
+                    .hover_image(self.imgs.button_hover)
+                    .press_image(self.imgs.button_press)
+                    .mid_bottom_with_margin_on(ids.content_align, 15.0)
+                    .label("Leave")
+                    .label_font_size(14)
+                    .label_color(TEXT_COLOR)
+                    .set(ids.group_leave, ui)
+                    .was_clicked()
+                {
+                    events.push(Event::LeaveGroup);
+                }
+            }
         }
 
         events
@@ -48,6 +48,8 @@ pub struct GlobalState {
     settings: Settings,
     window: Window,
     audio: AudioFrontend,
+    /// Why the last connection ended, shown on the main menu when it's next displayed.
+    info_message: Option<String>,
 }
 
 impl GlobalState {
@@ -56,6 +58,7 @@ impl GlobalState {
     pub fn on_play_state_changed(&mut self) {
         self.window.grab_cursor(false);
         self.window.needs_refresh_resize();
+        self.audio.set_biome(None);
     }
 
     pub fn maintain(&mut self) {
@@ -114,6 +117,7 @@ fn main() {
         audio,
         window: Window::new(&settings).expect("Failed to create window!"),
         settings,
+        info_message: None,
     };
 
     // Initialize logging.
@@ -102,6 +102,9 @@ impl PlayState for CharSelectionState {
                 .tick(comp::Controller::default(), clock.get_last_delta())
             {
                 error!("Failed to tick the scene: {:?}", err);
+                if let client::Error::ServerShutdown(reason) | client::Error::Kicked(reason) = err {
+                    global_state.info_message = Some(reason);
+                }
                 return PlayStateResult::Pop;
             }
             self.client.borrow_mut().cleanup();
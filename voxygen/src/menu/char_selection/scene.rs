@@ -16,7 +16,6 @@ use crate::{
 use client::Client;
 use common::{
     comp::{humanoid, Body},
-    state::DeltaTime,
     terrain::BlockKind,
 };
 use log::error;
@@ -117,10 +116,9 @@ impl Scene {
             client.state().get_time(),
             &SkeletonAttr::from(&body),
         );
-        self.figure_state.skeleton_mut().interpolate(
-            &tgt_skeleton,
-            client.state().ecs().read_resource::<DeltaTime>().0,
-        );
+        self.figure_state
+            .skeleton_mut()
+            .interpolate(&tgt_skeleton, client.state().get_frame_delta_time());
 
         self.figure_state.update(
             renderer,
@@ -18,6 +18,10 @@ pub enum Error {
     InvalidAuth,
     ClientCrashed,
     ServerIsFull,
+    // The server refused us with an explanation of the ban.
+    Banned(String),
+    // The server is stopping, with its reason.
+    ServerShutdown(String),
 }
 
 // Used to asynchronously parse the server address, resolve host names,
@@ -58,11 +62,20 @@ impl ClientInit {
                     for socket_addr in first_addrs.into_iter().chain(second_addrs) {
                         match Client::new(socket_addr, player.view_distance) {
                             Ok(mut client) => {
-                                if let Err(ClientError::InvalidAuth) =
-                                    client.register(player, password)
-                                {
-                                    last_err = Some(Error::InvalidAuth);
-                                    break;
+                                match client.register(player, password) {
+                                    Err(ClientError::InvalidAuth) => {
+                                        last_err = Some(Error::InvalidAuth);
+                                        break;
+                                    }
+                                    Err(ClientError::Banned(reason)) => {
+                                        last_err = Some(Error::Banned(reason));
+                                        break;
+                                    }
+                                    Err(ClientError::ServerShutdown(reason)) => {
+                                        last_err = Some(Error::ServerShutdown(reason));
+                                        break;
+                                    }
+                                    _ => {}
                                 }
                                 //client.register(player, password);
                                 let _ = tx.send(Ok(client));
@@ -92,6 +105,14 @@ impl ClientInit {
                                     ClientError::InvalidAuth => {
                                         last_err = Some(Error::InvalidAuth);
                                     }
+                                    ClientError::Banned(reason) => {
+                                        last_err = Some(Error::Banned(reason));
+                                        break;
+                                    }
+                                    ClientError::ServerShutdown(reason) => {
+                                        last_err = Some(Error::ServerShutdown(reason));
+                                        break;
+                                    }
                                     // TODO: Handle errors?
                                     _ => panic!(
                                         "Unexpected non-network error when creating client: {:?}",
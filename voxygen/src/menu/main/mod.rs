@@ -34,6 +34,11 @@ impl PlayState for MainMenuState {
         // Used for client creation.
         let mut client_init: Option<ClientInit> = None;
 
+        // Explain why we were returned to the main menu, e.g. the server shutting down.
+        if let Some(info) = global_state.info_message.take() {
+            self.main_menu_ui.login_error(info);
+        }
+
         loop {
             // Handle window events.
             for event in global_state.window.fetch_events() {
@@ -61,16 +66,16 @@ impl PlayState for MainMenuState {
                 }
                 Some(Err(err)) => {
                     client_init = None;
-                    self.main_menu_ui.login_error(
-                        match err {
-                            InitError::BadAddress(_) | InitError::NoAddress => "Server not found",
-                            InitError::InvalidAuth => "Invalid credentials",
-                            InitError::ServerIsFull => "Server is Full!",
-                            InitError::ConnectionFailed(_) => "Connection failed",
-                            InitError::ClientCrashed => "Client crashed",
+                    self.main_menu_ui.login_error(match err {
+                        InitError::BadAddress(_) | InitError::NoAddress => {
+                            "Server not found".to_string()
                         }
-                        .to_string(),
-                    );
+                        InitError::InvalidAuth => "Invalid credentials".to_string(),
+                        InitError::ServerIsFull => "Server is Full!".to_string(),
+                        InitError::ConnectionFailed(_) => "Connection failed".to_string(),
+                        InitError::ClientCrashed => "Client crashed".to_string(),
+                        InitError::Banned(reason) | InitError::ServerShutdown(reason) => reason,
+                    });
                 }
                 None => {}
             }
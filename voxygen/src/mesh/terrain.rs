@@ -16,8 +16,11 @@ type FluidVertex = <FluidPipeline as render::Pipeline>::Vertex;
 fn block_shadow_density(kind: BlockKind) -> (f32, f32) {
     // (density, cap)
     match kind {
-        BlockKind::Normal => (0.085, 0.3),
-        BlockKind::Dense => (0.3, 0.0),
+        BlockKind::Normal | BlockKind::Wood => (0.085, 0.3),
+        BlockKind::Dense | BlockKind::Stone | BlockKind::Granite | BlockKind::Sandstone => {
+            (0.3, 0.0)
+        }
+        kind if kind.is_ore() => (0.3, 0.0),
         BlockKind::Water => (0.15, 0.0),
         kind if kind.is_air() => (0.0, 0.0),
         _ => (1.0, 0.0),
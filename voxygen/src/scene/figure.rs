@@ -592,7 +592,7 @@ impl FigureMgr {
         let tick = client.get_tick();
         let ecs = client.state().ecs();
         let view_distance = client.view_distance().unwrap_or(1);
-        let dt = client.state().get_delta_time();
+        let dt = client.state().get_frame_delta_time();
         // Get player position.
         let player_pos = ecs
             .read_storage::<comp::Pos>()
@@ -731,6 +731,12 @@ impl FigureMgr {
                                 skeleton_attr,
                             )
                         }
+                        comp::Animation::Swim => anim::character::SwimAnimation::update_skeleton(
+                            state.skeleton_mut(),
+                            (vel.0.magnitude(), time),
+                            animation_info.time,
+                            skeleton_attr,
+                        ),
                     };
 
                     state.skeleton.interpolate(&target_skeleton, dt);
@@ -241,8 +241,24 @@ impl PlayState for SessionState {
 
                             if let Some(entity) = entity {
                                 client.pick_up(entity);
+                            } else {
+                                // Nothing to pick up, so start mining the block being looked at
+                                let terrain = client.state().terrain();
+                                let ray = terrain.ray(cam_pos, cam_pos + cam_dir * 100.0).cast();
+                                if let Ok(Some(block)) = ray.1 {
+                                    if block.mine_time().is_some() {
+                                        self.controller.mine = Some(
+                                            (cam_pos + cam_dir * (ray.0 + 0.01))
+                                                .map(|e| e.floor() as i32),
+                                        );
+                                    }
+                                }
                             }
                         }
+
+                        if !state {
+                            self.controller.mine = None;
+                        }
                     }
 
                     // Pass all other events to the scene
@@ -267,10 +283,22 @@ impl PlayState for SessionState {
             // Perform an in-game tick.
             if let Err(err) = self.tick(clock.get_avg_delta()) {
                 error!("Failed to tick the scene: {:?}", err);
+                if let Error::ClientError(client::Error::ServerShutdown(reason))
+                | Error::ClientError(client::Error::Kicked(reason))
+                | Error::ClientError(client::Error::Banned(reason)) = err
+                {
+                    global_state.info_message = Some(reason);
+                }
                 return PlayStateResult::Pop;
             }
 
             // Maintain global state.
+            global_state.audio.set_biome(
+                self.client
+                    .borrow()
+                    .current_chunk()
+                    .map(|chunk| chunk.meta().biome()),
+            );
             global_state.maintain();
 
             // Extract HUD events ensuring the client borrow gets dropped.
@@ -303,14 +331,26 @@ impl PlayState for SessionState {
             // Maintain the UI.
             for event in hud_events {
                 match event {
-                    HudEvent::SendMessage(msg) => {
+                    HudEvent::SendMessage(chat_type, msg) => {
                         // TODO: Handle result
-                        self.client.borrow_mut().send_chat(msg);
+                        self.client.borrow_mut().send_chat_on(chat_type, msg);
+                    }
+                    HudEvent::RequestCompletions(line) => {
+                        self.client.borrow_mut().request_completions(line)
                     }
                     HudEvent::CharacterSelection => {
                         self.client.borrow_mut().request_remove_character()
                     }
                     HudEvent::Logout => self.client.borrow_mut().request_logout(),
+                    HudEvent::AcceptGroupInvite => self.client.borrow_mut().accept_group_invite(),
+                    HudEvent::DeclineGroupInvite => self.client.borrow_mut().decline_group_invite(),
+                    HudEvent::LeaveGroup => self.client.borrow_mut().leave_group(),
+                    HudEvent::AcceptFriendRequest(alias) => {
+                        self.client.borrow_mut().accept_friend_request(alias)
+                    }
+                    HudEvent::DeclineFriendRequest(alias) => {
+                        self.client.borrow_mut().decline_friend_request(alias)
+                    }
                     HudEvent::Quit => {
                         return PlayStateResult::Shutdown;
                     }
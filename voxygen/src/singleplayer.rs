@@ -1,10 +1,12 @@
 use client::Client;
 use common::clock::Clock;
 use crossbeam::channel::{unbounded, Receiver, Sender, TryRecvError};
+use directories::ProjectDirs;
 use log::info;
 use portpicker::pick_unused_port;
 use server::{Event, Input, Server, ServerSettings};
 use std::{
+    fs,
     net::SocketAddr,
     thread::{self, JoinHandle},
     time::Duration,
@@ -35,9 +37,19 @@ impl Singleplayer {
             pick_unused_port().expect("Failed to find unused port!"),
         ));
 
+        // Cache the world with the game's other data, not in whatever directory we were started
+        // from.
+        let settings = ServerSettings {
+            world_file: ProjectDirs::from("net", "veloren", "voxygen").and_then(|dirs| {
+                fs::create_dir_all(dirs.cache_dir()).ok()?;
+                Some(dirs.cache_dir().join("world.bin"))
+            }),
+            ..ServerSettings::singleplayer()
+        };
+
         // Create server
-        let server = Server::bind(sock.clone(), ServerSettings::singleplayer())
-            .expect("Failed to create server instance!");
+        let server =
+            Server::bind(sock.clone(), settings).expect("Failed to create server instance!");
 
         let server = match client {
             Some(client) => server.with_thread_pool(client.thread_pool().clone()),
@@ -89,6 +101,8 @@ fn run_server(mut server: Server, rec: Receiver<Msg>) {
                 Event::ClientConnected { .. } => info!("Client connected!"),
                 Event::ClientDisconnected { .. } => info!("Client disconnected!"),
                 Event::Chat { entity: _, msg } => info!("[Client] {}", msg),
+                // Singleplayer servers have no console.
+                Event::ConsoleOutput { .. } | Event::Shutdown => {}
             }
         }
 
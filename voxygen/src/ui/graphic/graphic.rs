@@ -1,7 +1,7 @@
 use dot_vox::DotVoxData;
 use guillotiere::{size2, AllocId, Allocation, AtlasAllocator};
 use hashbrown::HashMap;
-use image::{DynamicImage, RgbaImage};
+use image::{DynamicImage, GenericImageView, RgbaImage};
 use log::{error, warn};
 use std::sync::Arc;
 use vek::*;
@@ -92,15 +92,16 @@ impl GraphicCache {
                     key,
                     match self.graphic_map.get(&graphic_id) {
                         Some(Graphic::Blank) => return None,
-                        // Render image at requested resolution
-                        // TODO: Use source aabr.
-                        Some(Graphic::Image(ref image)) => image
-                            .resize_exact(
+                        // Render the source area of the image at requested resolution
+                        Some(Graphic::Image(ref image)) => {
+                            let (x, y, w, h) = source_pixels(image.dimensions(), source);
+                            image::imageops::resize(
+                                &image.view(x, y, w, h),
                                 u32::from(dims.x),
                                 u32::from(dims.y),
                                 image::FilterType::Nearest,
                             )
-                            .to_rgba(),
+                        }
                         Some(Graphic::Voxel(ref vox, min_samples)) => {
                             super::renderer::draw_vox(&vox.as_ref().into(), dims, *min_samples)
                         }
@@ -244,3 +245,20 @@ impl GraphicCache {
         self.unused_entries_this_frame = None;
     }
 }
+
+/// Convert a source area in uv coordinates (measured from the bottom left) into the
+/// `(x, y, width, height)` of the pixels it covers (measured from the top left), clamped to the
+/// image.
+fn source_pixels((w, h): (u32, u32), source: Aabr<f64>) -> (u32, u32, u32, u32) {
+    let to_px = |uv: f64, sz: u32| ((uv * sz as f64).round().max(0.0) as u32).min(sz);
+    let (x0, x1) = (to_px(source.min.x, w), to_px(source.max.x, w));
+    let (y0, y1) = (to_px(1.0 - source.max.y, h), to_px(1.0 - source.min.y, h));
+    let (x0, y0) = (x0.min(w.saturating_sub(1)), y0.min(h.saturating_sub(1)));
+
+    (
+        x0,
+        y0,
+        x1.max(x0 + 1).min(w) - x0,
+        y1.max(y0 + 1).min(h) - y0,
+    )
+}
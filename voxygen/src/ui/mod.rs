@@ -26,6 +26,7 @@ use crate::{
     window::Window,
     Error,
 };
+use ::image::GenericImageView;
 use cache::Cache;
 use common::{assets, util::srgba_to_linear};
 use conrod_core::{
@@ -382,7 +383,7 @@ impl Ui {
                 PrimitiveKind::Image {
                     image_id,
                     color,
-                    source_rect: _, // TODO: <-- use this
+                    source_rect,
                 } => {
                     let graphic_id = self
                         .image_map
@@ -410,20 +411,23 @@ impl Ui {
                         (rect.w() * p_scale_factor).round() as u16,
                         (rect.h() * p_scale_factor).round() as u16,
                     );
-                    // Transform the source rectangle into uv coordinate.
-                    // TODO: Make sure this is right.
+                    // Transform the source rectangle into uv coordinates.  Like conrod, these
+                    // are measured from the bottom left of the image.
                     let source_aabr = {
-                        let (uv_l, uv_r, uv_b, uv_t) = (0.0, 1.0, 0.0, 1.0);
-                        /*match source_rect {
-                            Some(src_rect) => {
-                                let (l, r, b, t) = src_rect.l_r_b_t();
-                                ((l / image_w) as f32,
-                                (r / image_w) as f32,
-                                (b / image_h) as f32,
-                                (t / image_h) as f32)
-                            }
-                            None => (0.0, 1.0, 0.0, 1.0),
-                        };*/
+                        let (uv_l, uv_r, uv_b, uv_t) =
+                            match (source_rect, graphic_cache.get_graphic(*graphic_id)) {
+                                (Some(src_rect), Some(Graphic::Image(image))) => {
+                                    let (image_w, image_h) = image.dimensions();
+                                    let (l, r, b, t) = src_rect.l_r_b_t();
+                                    (
+                                        l / image_w as f64,
+                                        r / image_w as f64,
+                                        b / image_h as f64,
+                                        t / image_h as f64,
+                                    )
+                                }
+                                _ => (0.0, 1.0, 0.0, 1.0),
+                            };
                         Aabr {
                             min: Vec2::new(uv_l, uv_b),
                             max: Vec2::new(uv_r, uv_t),
@@ -1,12 +1,12 @@
 use std::ops::{Add, Mul, Sub};
 use vek::*;
-use veloren_world::{util::Sampler, World};
+use veloren_world::{util::Sampler, World, WorldOpts};
 
 const W: usize = 640;
 const H: usize = 480;
 
 fn main() {
-    let world = World::generate(0);
+    let world = World::generate(0, WorldOpts::default());
 
     let sampler = world.sample_columns();
 
@@ -24,21 +24,32 @@ fn main() {
             for j in 0..H {
                 let pos = focus + Vec2::new(i as i32, j as i32) * scale;
 
-                let (alt, location) = sampler
+                let (alt, water_depth, river, location) = sampler
                     .get(pos)
                     .map(|sample| {
                         (
                             sample.alt.sub(64.0).add(gain).mul(0.7).max(0.0).min(255.0) as u8,
+                            sample.water_level - sample.alt,
+                            sample.river,
                             sample.location,
                         )
                     })
-                    .unwrap_or((0, None));
+                    .unwrap_or((0, 0.0, 0.0, None));
 
                 let loc_color = location
                     .map(|l| (l.loc_idx as u8 * 17, l.loc_idx as u8 * 13))
                     .unwrap_or((0, 0));
 
-                buf[j * W + i] = u32::from_le_bytes([loc_color.0, loc_color.1, alt, alt]);
+                buf[j * W + i] = if river > 0.0 {
+                    // Rivers
+                    u32::from_le_bytes([255, 160, 64, 255])
+                } else if water_depth > 0.0 {
+                    // Oceans and lakes, darker with depth
+                    let shade = 255.0.sub(water_depth.mul(2.0)).max(96.0) as u8;
+                    u32::from_le_bytes([shade, shade / 3, 0, 255])
+                } else {
+                    u32::from_le_bytes([loc_color.0, loc_color.1, alt, alt])
+                };
             }
         }
 
@@ -1,4 +1,6 @@
-#[derive(Copy, Clone, Debug)]
+use serde_derive::{Deserialize, Serialize};
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum ForestKind {
     Palm,
     Savannah,
@@ -0,0 +1,398 @@
+//! Generate a world and write its layers out as images, without needing a window.
+//!
+//! Usage: `cargo run -p veloren-world --features map_export --bin map_export -- <seed>
+//! [output directory] [preset]`
+//!
+//! Each layer is written as a PNG with one pixel per chunk, along with a `summary.json`
+//! describing the world.  The names of locations are written on the locations layer.  The output only depends on the seed, the preset and the world
+//! generator, so it can be diffed to review changes to world generation.
+
+use common::{
+    terrain::{BiomeKind, TerrainChunkSize},
+    vol::VolSize,
+};
+use image::{Rgb, RgbImage};
+use serde_derive::Serialize;
+use std::{
+    collections::BTreeMap,
+    env,
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    process,
+};
+use vek::*;
+use veloren_world::{
+    sim::{SimChunk, WorldSim, WORLD_FILE_VERSION},
+    WorldOpts, WorldPreset,
+};
+
+/// Width and height of the glyphs used to label maps, in pixels.
+const GLYPH_WIDTH: i32 = 3;
+const GLYPH_HEIGHT: i32 = 5;
+
+/// A tiny font for labelling maps. Each glyph is stored row by row, starting from the top left,
+/// with one bit per pixel. Letters are only stored in upper case.
+const GLYPHS: [(char, u16); 38] = [
+    ('A', 0b010_101_111_101_101),
+    ('B', 0b110_101_110_101_110),
+    ('C', 0b011_100_100_100_011),
+    ('D', 0b110_101_101_101_110),
+    ('E', 0b111_100_110_100_111),
+    ('F', 0b111_100_110_100_100),
+    ('G', 0b011_100_101_101_011),
+    ('H', 0b101_101_111_101_101),
+    ('I', 0b111_010_010_010_111),
+    ('J', 0b001_001_001_101_010),
+    ('K', 0b101_101_110_101_101),
+    ('L', 0b100_100_100_100_111),
+    ('M', 0b101_111_111_101_101),
+    ('N', 0b110_101_101_101_101),
+    ('O', 0b010_101_101_101_010),
+    ('P', 0b110_101_110_100_100),
+    ('Q', 0b010_101_101_110_011),
+    ('R', 0b110_101_110_101_101),
+    ('S', 0b011_100_010_001_110),
+    ('T', 0b111_010_010_010_010),
+    ('U', 0b101_101_101_101_111),
+    ('V', 0b101_101_101_101_010),
+    ('W', 0b101_101_111_111_101),
+    ('X', 0b101_101_010_101_101),
+    ('Y', 0b101_101_010_010_010),
+    ('Z', 0b111_001_010_100_111),
+    ('0', 0b111_101_101_101_111),
+    ('1', 0b010_110_010_010_111),
+    ('2', 0b110_001_010_100_111),
+    ('3', 0b110_001_010_001_110),
+    ('4', 0b101_101_111_001_001),
+    ('5', 0b111_100_110_001_110),
+    ('6', 0b011_100_111_101_111),
+    ('7', 0b111_001_010_010_010),
+    ('8', 0b111_101_111_101_111),
+    ('9', 0b111_101_111_001_110),
+    ('-', 0b000_000_111_000_000),
+    ('\'', 0b010_010_000_000_000),
+];
+
+#[derive(Serialize)]
+struct Summary {
+    seed: u32,
+    world_file_version: u32,
+    opts: WorldOpts,
+    min_alt: f32,
+    max_alt: f32,
+    /// Fraction of chunks above sea level.
+    land_fraction: f32,
+    river_chunks: usize,
+    lake_chunks: usize,
+    road_chunks: usize,
+    biomes: BTreeMap<String, usize>,
+    locations: Vec<LocationSummary>,
+}
+
+#[derive(Serialize)]
+struct LocationSummary {
+    name: String,
+    /// Centre of the location, in world blocks.
+    center: Vec2<i32>,
+    /// Number of chunks belonging to the location.
+    chunks: usize,
+}
+
+fn parse_preset(name: &str) -> Option<WorldPreset> {
+    match name.to_lowercase().as_str() {
+        "default" => Some(WorldPreset::Default),
+        "islands" => Some(WorldPreset::Islands),
+        "large" => Some(WorldPreset::Large),
+        "flat" => Some(WorldPreset::Flat),
+        _ => None,
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("Usage: map_export <seed> [output directory] [default|islands|large|flat]");
+    process::exit(1);
+}
+
+fn lerp_color(from: [u8; 3], to: [u8; 3], t: f32) -> Rgb<u8> {
+    let t = t.max(0.0).min(1.0);
+    Rgb([
+        Lerp::lerp(from[0] as f32, to[0] as f32, t) as u8,
+        Lerp::lerp(from[1] as f32, to[1] as f32, t) as u8,
+        Lerp::lerp(from[2] as f32, to[2] as f32, t) as u8,
+    ])
+}
+
+fn biome_color(biome: BiomeKind) -> Rgb<u8> {
+    Rgb(match biome {
+        BiomeKind::Void => [0, 0, 0],
+        BiomeKind::Ocean => [30, 60, 150],
+        BiomeKind::Lake => [60, 120, 200],
+        BiomeKind::Beach => [230, 215, 160],
+        BiomeKind::Grassland => [110, 170, 60],
+        BiomeKind::Savannah => [180, 170, 80],
+        BiomeKind::Desert => [220, 200, 120],
+        BiomeKind::Jungle => [20, 120, 20],
+        BiomeKind::Mangrove => [60, 100, 50],
+        BiomeKind::Swamp => [70, 90, 50],
+        BiomeKind::Forest => [30, 100, 30],
+        BiomeKind::Taiga => [40, 80, 60],
+        BiomeKind::Snowlands => [235, 240, 250],
+        BiomeKind::Tundra => [150, 155, 130],
+        BiomeKind::Mountain => [130, 120, 110],
+    })
+}
+
+/// Draw an image with one pixel per chunk of the world, coloured by `f`.
+fn draw_layer(sim: &WorldSim, f: impl Fn(&SimChunk) -> Rgb<u8>) -> RgbImage {
+    let size = sim.opts().size;
+    let mut img = RgbImage::new(size.x, size.y);
+    for (x, y, pixel) in img.enumerate_pixels_mut() {
+        if let Some(chunk) = sim.get(Vec2::new(x, y).map(|e| e as i32)) {
+            *pixel = f(chunk);
+        }
+    }
+    img
+}
+
+fn save_layer(img: &RgbImage, dir: &Path, name: &str) -> Result<(), Box<dyn Error>> {
+    let path = dir.join(format!("{}.png", name));
+    println!("Writing {}", path.display());
+    img.save(path)?;
+    Ok(())
+}
+
+/// Draw and save a layer in one go.
+fn write_layer(
+    sim: &WorldSim,
+    dir: &Path,
+    name: &str,
+    f: impl Fn(&SimChunk) -> Rgb<u8>,
+) -> Result<(), Box<dyn Error>> {
+    save_layer(&draw_layer(sim, f), dir, name)
+}
+
+/// Mark a point on a layer and write a label to the right of it, in white with a black outline
+/// so that it can be read on any background. Characters without a glyph are left as gaps.
+fn draw_label(img: &mut RgbImage, pos: Vec2<i32>, text: &str) {
+    let mut pixels = vec![pos];
+    for (i, c) in text.chars().enumerate() {
+        let bits = match GLYPHS.iter().find(|(g, _)| *g == c.to_ascii_uppercase()) {
+            Some((_, bits)) => *bits,
+            None => continue,
+        };
+        let origin = pos + Vec2::new(3 + i as i32 * (GLYPH_WIDTH + 1), -GLYPH_HEIGHT / 2);
+        for y in 0..GLYPH_HEIGHT {
+            for x in 0..GLYPH_WIDTH {
+                let bit = (GLYPH_HEIGHT - y) * GLYPH_WIDTH - x - 1;
+                if bits & (1 << bit) != 0 {
+                    pixels.push(origin + Vec2::new(x, y));
+                }
+            }
+        }
+    }
+
+    let mut put = |pos: Vec2<i32>, color| {
+        if pos.x >= 0 && pos.y >= 0 && pos.x < img.width() as i32 && pos.y < img.height() as i32 {
+            img.put_pixel(pos.x as u32, pos.y as u32, color);
+        }
+    };
+    let neighbours = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+    for pixel in &pixels {
+        for (x, y) in neighbours.iter() {
+            put(*pixel + Vec2::new(*x, *y), Rgb([0, 0, 0]));
+        }
+    }
+    for pixel in &pixels {
+        put(*pixel, Rgb([255, 255, 255]));
+    }
+}
+
+fn main() {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let seed = match args.get(0).map(|s| s.parse::<u32>()) {
+        Some(Ok(seed)) => seed,
+        _ => usage(),
+    };
+    let dir = args
+        .get(1)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(format!("map_{}", seed)));
+    let opts = match args.get(2).map(|s| parse_preset(s.as_str())) {
+        Some(Some(preset)) => WorldOpts::preset(preset),
+        Some(None) => usage(),
+        None => WorldOpts::default(),
+    };
+
+    if let Err(err) = opts.validate() {
+        eprintln!("Invalid world options: {:?}", err);
+        process::exit(1);
+    }
+
+    println!("Generating world with seed {}...", seed);
+    let sim = WorldSim::generate(seed, opts);
+
+    if let Err(err) = export(&sim, seed, &dir) {
+        eprintln!("Failed to export the world: {}", err);
+        process::exit(1);
+    }
+}
+
+fn export(sim: &WorldSim, seed: u32, dir: &Path) -> Result<(), Box<dyn Error>> {
+    fs::create_dir_all(dir)?;
+
+    let opts = sim.opts();
+    let size = opts.size.map(|e| e as i32);
+    let chunks = (0..size.y)
+        .map(|y| (0..size.x).map(move |x| Vec2::new(x, y)))
+        .flatten()
+        .filter_map(|pos| sim.get(pos))
+        .collect::<Vec<_>>();
+
+    let min_alt = chunks.iter().map(|c| c.alt).fold(opts.sea_level, f32::min);
+    let max_alt = chunks.iter().map(|c| c.alt).fold(opts.sea_level, f32::max);
+
+    write_layer(sim, dir, "altitude", |chunk| {
+        let v = ((chunk.alt - min_alt) / (max_alt - min_alt).max(1.0) * 255.0) as u8;
+        Rgb([v, v, v])
+    })?;
+    write_layer(sim, dir, "biome", |chunk| {
+        biome_color(chunk.get_biome(opts))
+    })?;
+    write_layer(sim, dir, "temperature", |chunk| {
+        lerp_color([40, 80, 255], [255, 60, 30], chunk.temp * 0.5 + 0.5)
+    })?;
+    write_layer(sim, dir, "humidity", |chunk| {
+        lerp_color([200, 170, 100], [20, 80, 200], chunk.humidity)
+    })?;
+    write_layer(sim, dir, "tree_density", |chunk| {
+        lerp_color([0, 0, 0], [40, 220, 40], chunk.tree_density)
+    })?;
+    let mut locations = draw_layer(sim, |chunk| {
+        if chunk.alt < opts.sea_level {
+            Rgb([20, 30, 60])
+        } else if chunk.roads != 0 {
+            Rgb([255, 255, 255])
+        } else {
+            match &chunk.location {
+                Some(loc) => {
+                    // Spread the colours of neighbouring locations apart
+                    let h = (loc.loc_idx as u32).wrapping_mul(0x9E37_79B9);
+                    Rgb([
+                        64 + (h >> 24) as u8 / 2,
+                        64 + (h >> 16) as u8 / 2,
+                        64 + (h >> 8) as u8 / 2,
+                    ])
+                }
+                None => Rgb([40, 40, 40]),
+            }
+        }
+    });
+    for loc in sim.locations() {
+        let pos = loc
+            .center()
+            .map2(Vec2::from(TerrainChunkSize::SIZE), |e, sz: u32| {
+                e / sz as i32
+            });
+        draw_label(&mut locations, pos, loc.name());
+    }
+    save_layer(&locations, dir, "locations")?;
+
+    let mut biomes = BTreeMap::new();
+    let mut location_chunks = vec![0; sim.locations().len()];
+    for chunk in chunks.iter() {
+        *biomes
+            .entry(format!("{:?}", chunk.get_biome(opts)))
+            .or_insert(0) += 1;
+        if let Some(loc) = &chunk.location {
+            location_chunks[loc.loc_idx] += 1;
+        }
+    }
+
+    let summary = Summary {
+        seed,
+        world_file_version: WORLD_FILE_VERSION,
+        opts: opts.clone(),
+        min_alt,
+        max_alt,
+        land_fraction: chunks.iter().filter(|c| c.alt >= opts.sea_level).count() as f32
+            / chunks.len() as f32,
+        river_chunks: chunks.iter().filter(|c| c.water.is_river()).count(),
+        lake_chunks: chunks.iter().filter(|c| c.water.is_lake()).count(),
+        road_chunks: chunks.iter().filter(|c| c.roads != 0).count(),
+        biomes,
+        locations: sim
+            .locations()
+            .iter()
+            .zip(location_chunks)
+            .map(|(loc, chunks)| LocationSummary {
+                name: loc.name().to_string(),
+                center: loc.center(),
+                chunks,
+            })
+            .collect(),
+    };
+
+    let path = dir.join("summary.json");
+    println!("Writing {}", path.display());
+    serde_json::to_writer_pretty(fs::File::create(path)?, &summary)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_worlds_are_exported() {
+        let dir = env::temp_dir().join(format!("veloren-map-export-{}", process::id()));
+        let opts = WorldOpts {
+            size: Vec2::broadcast(WorldOpts::MIN_SIZE),
+            ..WorldOpts::default()
+        };
+        let sim = WorldSim::generate(42, opts);
+        let exported = export(&sim, 42, &dir);
+
+        let layers = [
+            "altitude",
+            "biome",
+            "temperature",
+            "humidity",
+            "tree_density",
+            "locations",
+        ];
+        let sizes = layers
+            .iter()
+            .map(|name| image::image_dimensions(dir.join(format!("{}.png", name))))
+            .collect::<Vec<_>>();
+        let summary = fs::read(dir.join("summary.json"));
+        fs::remove_dir_all(&dir).unwrap();
+
+        exported.unwrap();
+        for size in sizes {
+            assert_eq!(size.unwrap(), (WorldOpts::MIN_SIZE, WorldOpts::MIN_SIZE));
+        }
+        let summary = serde_json::from_slice::<serde_json::Value>(&summary.unwrap()).unwrap();
+        for key in &[
+            "seed",
+            "world_file_version",
+            "opts",
+            "min_alt",
+            "max_alt",
+            "land_fraction",
+            "river_chunks",
+            "lake_chunks",
+            "road_chunks",
+            "biomes",
+            "locations",
+        ] {
+            assert!(
+                summary.get(key).is_some(),
+                "summary.json is missing {}",
+                key
+            );
+        }
+        assert_eq!(summary["seed"], 42);
+    }
+}
@@ -0,0 +1,171 @@
+use crate::all::ForestKind;
+use common::{
+    assets::{self, Asset},
+    terrain::{BiomeKind, BlockKind, Structure},
+};
+use serde_derive::Deserialize;
+use std::{fs::File, io::BufReader, sync::Arc};
+use vek::*;
+
+/// The list of structure manifests that should be placed in the world.
+#[derive(Clone, Debug, Deserialize)]
+pub struct StructureIndex {
+    pub sets: Vec<String>,
+}
+
+/// A set of `.vox` models that are placed by the same rules, e.g. all the oak trees.
+#[derive(Clone, Debug, Deserialize)]
+pub struct StructureManifest {
+    /// The kind given to blocks of the models that don't specify one themselves.
+    pub default_kind: BlockKind,
+    #[serde(default)]
+    pub spawn: SpawnRules,
+    pub variants: Vec<Variant>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Variant {
+    /// Asset specifier of the `.vox` model.
+    pub model: String,
+    /// The point of the model that gets placed on the ground.
+    pub center: (i32, i32, i32),
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct SpawnRules {
+    /// Forest kinds the set may appear in. Empty means any.
+    pub forest_kinds: Vec<ForestKind>,
+    /// Biomes the set may appear in. Empty means any.
+    pub biomes: Vec<BiomeKind>,
+    /// How likely the set is to be picked relative to the other sets allowed at the same spot.
+    pub weight: f32,
+}
+
+impl Default for SpawnRules {
+    fn default() -> Self {
+        Self {
+            forest_kinds: Vec::new(),
+            biomes: Vec::new(),
+            weight: 1.0,
+        }
+    }
+}
+
+impl SpawnRules {
+    pub fn allows(&self, forest_kind: ForestKind, biome: BiomeKind) -> bool {
+        (self.forest_kinds.is_empty() || self.forest_kinds.contains(&forest_kind))
+            && (self.biomes.is_empty() || self.biomes.contains(&biome))
+    }
+}
+
+fn parse_ron<T: serde::de::DeserializeOwned>(
+    buf_reader: BufReader<File>,
+) -> Result<T, assets::Error> {
+    ron::de::from_reader(buf_reader).map_err(|err| assets::Error::ParseError(format!("{}", err)))
+}
+
+impl Asset for StructureIndex {
+    const ENDINGS: &'static [&'static str] = &["ron"];
+    fn parse(buf_reader: BufReader<File>) -> Result<Self, assets::Error> {
+        parse_ron(buf_reader)
+    }
+}
+
+impl Asset for StructureManifest {
+    const ENDINGS: &'static [&'static str] = &["ron"];
+    fn parse(buf_reader: BufReader<File>) -> Result<Self, assets::Error> {
+        parse_ron(buf_reader)
+    }
+}
+
+/// A structure manifest with all of its models loaded and positioned.
+pub struct StructureSet {
+    pub spawn: SpawnRules,
+    pub volumes: Vec<Arc<Structure>>,
+}
+
+impl StructureSet {
+    pub fn load(specifier: &str) -> Result<Self, assets::Error> {
+        let manifest = assets::load::<StructureManifest>(specifier)?;
+
+        // Models are cloned rather than mapped in the asset cache, so that several manifests can
+        // share a model with different centres.
+        let volumes = manifest
+            .variants
+            .iter()
+            .map(|variant| {
+                let model = assets::load::<Structure>(&variant.model)?;
+                Ok(Arc::new(
+                    (*model)
+                        .clone()
+                        .with_center(Vec3::from(variant.center))
+                        .with_default_kind(manifest.default_kind),
+                ))
+            })
+            .collect::<Result<_, assets::Error>>()?;
+
+        Ok(Self {
+            spawn: manifest.spawn.clone(),
+            volumes,
+        })
+    }
+
+    /// Load every set listed in the given index.
+    pub fn load_all(index: &str) -> Result<Vec<Self>, assets::Error> {
+        assets::load::<StructureIndex>(index)?
+            .sets
+            .iter()
+            .map(|set| Self::load(set))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spawn_rules_default_to_anywhere() {
+        let manifest: StructureManifest = ron::de::from_str(
+            "(default_kind: Wood, variants: [(model: \"world.tree.oak.1\", center: (1, 2, 3))])",
+        )
+        .unwrap();
+
+        assert_eq!(manifest.default_kind, BlockKind::Wood);
+        assert_eq!(manifest.variants[0].center, (1, 2, 3));
+        assert!(manifest.spawn.allows(ForestKind::Palm, BiomeKind::Desert));
+        assert!(manifest.spawn.weight > 0.0);
+    }
+
+    #[test]
+    fn spawn_rules_restrict_placement() {
+        let spawn: SpawnRules =
+            ron::de::from_str("(forest_kinds: [Oak, Pine], biomes: [Grassland])").unwrap();
+
+        assert!(spawn.allows(ForestKind::Oak, BiomeKind::Grassland));
+        assert!(!spawn.allows(ForestKind::Palm, BiomeKind::Grassland));
+        assert!(!spawn.allows(ForestKind::Pine, BiomeKind::Desert));
+    }
+
+    #[test]
+    fn malformed_manifests_are_parse_errors() {
+        assert!(ron::de::from_str::<StructureManifest>("(default_kind: Wood)").is_err());
+        assert!(
+            ron::de::from_str::<StructureManifest>("(default_kind: Cheese, variants: [])").is_err()
+        );
+    }
+
+    #[test]
+    fn shipped_manifests_load() {
+        let sets = StructureSet::load_all("world.manifest.structures").unwrap();
+        assert!(!sets.is_empty());
+        assert!(sets.iter().all(|set| !set.volumes.is_empty()));
+
+        // Sets that aren't placed yet should still stay loadable.
+        for unplaced in &["temperate_small", "birch", "poplar", "willow"] {
+            let set = StructureSet::load(&format!("world.manifest.tree.{}", unplaced)).unwrap();
+            assert!(!set.volumes.is_empty());
+        }
+    }
+}
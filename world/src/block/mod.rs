@@ -1,9 +1,10 @@
+mod manifest;
 mod natural;
 
 use crate::{
     column::{ColumnGen, ColumnSample, StructureData},
     util::{HashCache, RandomField, Sampler, SamplerMut},
-    World, CONFIG,
+    World, WorldOpts,
 };
 use common::{
     terrain::{structure::StructureBlock, Block, BlockKind, Structure},
@@ -11,11 +12,12 @@ use common::{
     vol::{ReadVol, Vox},
 };
 use noise::NoiseFn;
-use std::ops::{Add, Div, Mul, Neg};
+use std::ops::{Add, Div, Mul};
 use vek::*;
 
 pub struct BlockGen<'a> {
     world: &'a World,
+    opts: &'a WorldOpts,
     column_cache: HashCache<Vec2<i32>, Option<ColumnSample<'a>>>,
     column_gen: ColumnGen<'a>,
 }
@@ -24,6 +26,7 @@ impl<'a> BlockGen<'a> {
     pub fn new(world: &'a World, column_gen: ColumnGen<'a>) -> Self {
         Self {
             world,
+            opts: world.sim().opts(),
             column_cache: HashCache::with_capacity(64),
             column_gen,
         }
@@ -79,6 +82,7 @@ impl<'a> BlockGen<'a> {
     pub fn get_z_cache(&mut self, wpos: Vec2<i32>) -> Option<ZCache<'a>> {
         let BlockGen {
             world,
+            opts,
             column_cache,
             column_gen,
         } = self;
@@ -124,14 +128,62 @@ impl<'a> BlockGen<'a> {
 
         Some(ZCache {
             wpos,
+            opts: *opts,
             sample,
             structures,
         })
     }
 
+    /// Pick the kind of stone at a point `depth` blocks below the surface.  Stone is laid down in
+    /// broad strata, with thin veins of ore running through it that grow richer with depth.
+    fn get_stone(world: &World, wposf: Vec3<f64>, depth: f32, col: Rgb<u8>) -> Block {
+        let ore_nz = &world.sim().gen_ctx.ore_nz;
+
+        // Veins follow the surface where the noise crosses zero.
+        let vein = ore_nz.get((wposf.div(Vec3::new(32.0, 32.0, 16.0))).into_array()) as f32;
+        if vein.abs() < 0.025 {
+            // Each stretch of vein holds a single kind of ore.
+            let richness = (ore_nz.get((wposf.div(96.0) + 500.0).into_array()) as f32)
+                .mul(0.5)
+                .add(0.5)
+                .mul((depth / 256.0).min(1.0));
+            let ore = if richness > 0.45 {
+                Some((BlockKind::Gold, Rgb::new(230, 190, 50)))
+            } else if richness > 0.3 {
+                Some((BlockKind::Iron, Rgb::new(150, 95, 80)))
+            } else if richness > 0.15 {
+                Some((BlockKind::Copper, Rgb::new(190, 110, 60)))
+            } else if depth > 8.0 {
+                Some((BlockKind::Coal, Rgb::new(45, 45, 50)))
+            } else {
+                None
+            };
+            if let Some((kind, col)) = ore {
+                return Block::new(kind, col);
+            }
+        }
+
+        let strata =
+            ore_nz.get((wposf.div(Vec3::new(300.0, 300.0, 40.0)) - 500.0).into_array()) as f32;
+        if strata > 0.35 {
+            Block::new(
+                BlockKind::Granite,
+                Lerp::lerp(col, Rgb::new(150, 130, 135), 0.6),
+            )
+        } else if strata < -0.35 {
+            Block::new(
+                BlockKind::Sandstone,
+                Lerp::lerp(col, Rgb::new(215, 185, 135), 0.6),
+            )
+        } else {
+            Block::new(BlockKind::Stone, col)
+        }
+    }
+
     pub fn get_with_z_cache(&mut self, wpos: Vec3<i32>, z_cache: Option<&ZCache>) -> Option<Block> {
         let BlockGen {
             world,
+            opts,
             column_cache,
             column_gen,
         } = self;
@@ -146,8 +198,7 @@ impl<'a> BlockGen<'a> {
             //tree_density,
             //forest_kind,
             //close_structures,
-            cave_xy,
-            cave_alt,
+            caves,
             marble,
             marble_small,
             rock,
@@ -165,7 +216,7 @@ impl<'a> BlockGen<'a> {
         let (definitely_underground, height, water_height) =
             if (wposf.z as f32) < alt - 64.0 * chaos {
                 // Shortcut warping
-                (true, alt, CONFIG.sea_level /*water_level*/)
+                (true, alt, water_level)
             } else {
                 // Apply warping
                 let warp = (world
@@ -206,11 +257,7 @@ impl<'a> BlockGen<'a> {
                     (alt + warp).max(cliff_height)
                 };
 
-                (
-                    false,
-                    height,
-                    /*(water_level + warp).max(*/ CONFIG.sea_level, /*)*/
-                )
+                (false, height, water_level)
             };
 
         // Sample blocks
@@ -241,7 +288,7 @@ impl<'a> BlockGen<'a> {
             if (wposf.z as f32) > alt - 32.0 * chaos {
                 Some(Block::new(BlockKind::Normal, col))
             } else {
-                Some(Block::new(BlockKind::Dense, col))
+                Some(Self::get_stone(world, wposf, height - wposf.z as f32, col))
             }
         } else if (wposf.z as f32) < height {
             let col = Lerp::lerp(
@@ -257,7 +304,7 @@ impl<'a> BlockGen<'a> {
                 saturate_srgb(col, 0.45).map(|e| (e * 255.0) as u8),
             ))
         } else if (wposf.z as f32) < height + 0.9
-            && temp < CONFIG.desert_temp
+            && temp < opts.desert_temp
             && (wposf.z as f32 > water_height + 3.0)
             && marble > 0.68
             && marble_small > 0.65
@@ -288,7 +335,7 @@ impl<'a> BlockGen<'a> {
                 Rgb::broadcast(0),
             ))
         } else if (wposf.z as f32) < height + 0.9
-            && temp > CONFIG.desert_temp
+            && temp > opts.desert_temp
             && (marble * 4423.5).fract() < 0.0005
         {
             Some(Block::new(
@@ -303,21 +350,17 @@ impl<'a> BlockGen<'a> {
             None
         };
 
-        // Caves
-        let block = block.and_then(|block| {
-            // Underground
-            let cave = cave_xy.powf(2.0)
-                * (wposf.z as f32 - cave_alt)
-                    .div(40.0)
-                    .powf(4.0)
-                    .neg()
-                    .add(1.0)
-                > 0.9993;
-
-            if cave {
-                None
+        // Caves (carved out as air rather than left empty, so that they aren't flooded)
+        let block = block.map(|block| {
+            let z = wposf.z as f32;
+            if caves
+                .iter()
+                .filter_map(|span| *span)
+                .any(|(bottom, top)| z >= bottom && z < top)
+            {
+                air
             } else {
-                Some(block)
+                block
             }
         });
 
@@ -345,7 +388,7 @@ impl<'a> BlockGen<'a> {
         // Water
         let block = block.or_else(|| {
             if (wposf.z as f32) < water_height {
-                // Ocean
+                // Ocean, lakes and rivers
                 Some(water)
             } else {
                 None
@@ -367,17 +410,20 @@ impl<'a> BlockGen<'a> {
 
 pub struct ZCache<'a> {
     wpos: Vec2<i32>,
+    opts: &'a WorldOpts,
     sample: ColumnSample<'a>,
     structures: [Option<(StructureInfo, ColumnSample<'a>)>; 9],
 }
 
 impl<'a> ZCache<'a> {
     pub fn get_z_limits(&self) -> (f32, f32) {
-        let cave_depth = if self.sample.cave_xy.abs() > 0.9 {
-            (self.sample.alt - self.sample.cave_alt + 8.0).max(0.0)
-        } else {
-            0.0
-        };
+        let cave_depth = self
+            .sample
+            .caves
+            .iter()
+            .filter_map(|span| *span)
+            .map(|(bottom, _)| self.sample.alt - bottom + 4.0)
+            .fold(0.0f32, |a, b| a.max(b));
 
         let min = self.sample.alt - (self.sample.chaos * 48.0 + cave_depth) - 4.0;
 
@@ -404,7 +450,7 @@ impl<'a> ZCache<'a> {
         let min = min + structure_min;
         let max = (self.sample.alt + cliff + structure_max + warp + 8.0)
             .max(self.sample.water_level)
-            .max(CONFIG.sea_level + 2.0);
+            .max(self.opts.sea_level + 2.0);
 
         (min, max)
     }
@@ -0,0 +1,284 @@
+use crate::{sim::WorldSim, util::Sampler};
+use common::{terrain::TerrainChunkSize, vol::VolSize};
+use noise::NoiseFn;
+use std::ops::Div;
+use vek::*;
+
+/// Vertical distance between levels of the cave network.  The shallowest level lies this far
+/// below the surface.
+const LEVEL_DEPTH: f32 = 36.0;
+const LEVELS: u32 = 3;
+const TUNNEL_RADIUS: f32 = 4.0;
+const SHAFT_RADIUS: f32 = 3.0;
+/// Floors are flattened by this factor relative to ceilings so that caves are walkable.
+const FLOOR_FLATTEN: f32 = 0.5;
+
+/// The vertical spans carved out of a single column by caves, as `(bottom, top)` pairs.
+pub type CaveSpans = [Option<(f32, f32)>; 4];
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum CaveNodeKind {
+    /// A meeting point of tunnels.
+    Junction,
+    /// A large open cavern.
+    Chamber,
+    /// A junction with a shaft leading up to the surface.
+    Entrance,
+    /// A junction with a shaft dropping down to a small chamber on the level below.
+    Drop,
+}
+
+/// A node in the cave network.  One node may exist for each cell of `GenCtx::cave_gen`, and
+/// tunnels run between the nodes of neighbouring cells.
+#[derive(Copy, Clone, Debug)]
+pub struct CaveNode {
+    pub pos: Vec3<f32>,
+    pub radius: f32,
+    pub seed: u32,
+    pub kind: CaveNodeKind,
+    /// Altitude of the surface directly above the node.
+    pub surface_alt: f32,
+}
+
+impl CaveNode {
+    /// Where things standing on the floor of this node should be placed.
+    pub fn floor_pos(&self) -> Vec3<f32> {
+        let floor = match self.kind {
+            CaveNodeKind::Drop => self.pos.z - LEVEL_DEPTH - TUNNEL_RADIUS * FLOOR_FLATTEN,
+            _ => self.pos.z - self.radius * FLOOR_FLATTEN,
+        };
+        Vec3::new(self.pos.x, self.pos.y, floor + 1.0)
+    }
+
+    /// Where the shaft of an entrance breaks through the surface.
+    pub fn entrance_pos(&self) -> Vec3<f32> {
+        Vec3::new(self.pos.x, self.pos.y, self.surface_alt)
+    }
+}
+
+/// Decide what cave node, if any, sits at a point produced by `GenCtx::cave_gen`.
+fn get_node(sim: &WorldSim, (pos, seed): (Vec2<i32>, u32)) -> Option<CaveNode> {
+    let chunk_pos = pos.map2(Vec2::from(TerrainChunkSize::SIZE), |e, sz: u32| {
+        e / sz as i32
+    });
+    let chunk = sim.get(chunk_pos)?;
+
+    // Keep caves away from the sea and the edges of the world.
+    if seed % 8 == 0
+        || chunk.alt < sim.opts().sea_level + 16.0
+        || chunk_pos
+            .map2(sim.opts().world_size(), |e, sz| e < 2 || e >= sz as i32 - 2)
+            .reduce_or()
+    {
+        return None;
+    }
+
+    let surface_alt = sim.get_interpolated(pos, |chunk| chunk.alt)?;
+    let level = (seed >> 4) % LEVELS;
+
+    // Entrances need dry land that isn't part of a town.
+    let can_open = chunk.water.kind.is_none() && chunk.spawn_rate > 0.5;
+    let kind = match (seed >> 8) % 8 {
+        0 | 1 => CaveNodeKind::Chamber,
+        2 if can_open => CaveNodeKind::Entrance,
+        3 if level + 1 < LEVELS => CaveNodeKind::Drop,
+        _ => CaveNodeKind::Junction,
+    };
+
+    Some(CaveNode {
+        pos: Vec3::new(
+            pos.x as f32,
+            pos.y as f32,
+            surface_alt - LEVEL_DEPTH * (level + 1) as f32,
+        ),
+        radius: match kind {
+            CaveNodeKind::Chamber => 10.0 + ((seed >> 12) % 8) as f32,
+            _ => TUNNEL_RADIUS + 1.0,
+        },
+        seed,
+        kind,
+        surface_alt,
+    })
+}
+
+/// Find the cave nodes for the 3x3 cells of `GenCtx::cave_gen` around `wpos`.
+pub fn get_nodes(sim: &WorldSim, wpos: Vec2<i32>) -> [Option<CaveNode>; 9] {
+    let mut nodes = [None; 9];
+    for (i, point) in sim.gen_ctx.cave_gen.get(wpos).iter().enumerate() {
+        nodes[i] = get_node(sim, *point);
+    }
+    nodes
+}
+
+/// Whether a tunnel runs between two neighbouring nodes.
+fn has_tunnel(a: &CaveNode, b: &CaveNode) -> bool {
+    (a.seed ^ b.seed.rotate_left(16)) % 3 != 0
+}
+
+/// Vertical span of a flattened sphere (or the cross-section of a tunnel) of the given radius,
+/// centred at `z`, seen from `dist` away horizontally.
+fn carve(z: f32, radius: f32, dist: f32) -> Option<(f32, f32)> {
+    if dist < radius {
+        let half_height = (radius * radius - dist * dist).sqrt();
+        Some((z - half_height * FLOOR_FLATTEN, z + half_height))
+    } else {
+        None
+    }
+}
+
+/// Work out which parts of the column at `wposf` are carved out by caves.  `alt` is the surface
+/// altitude of the column, which entrance shafts cut through.
+pub fn sample_column(
+    sim: &WorldSim,
+    nodes: &[Option<CaveNode>; 9],
+    wposf: Vec2<f32>,
+    alt: f32,
+) -> CaveSpans {
+    let mut spans = [None; 4];
+    let mut push = |span: Option<(f32, f32)>| {
+        if let Some((bottom, top)) = span {
+            match spans.iter().position(|s| s.is_none()) {
+                Some(i) => spans[i] = Some((bottom, top)),
+                // Out of room, so merge with the last span instead
+                None => spans[3] = spans[3].map(|(b, t): (f32, f32)| (b.min(bottom), t.max(top))),
+            }
+        }
+    };
+
+    // Rough up the walls, floors and ceilings a little.
+    let wposd = wposf.map(|e| e as f64);
+    let roughness = 1.0 + sim.gen_ctx.cave_0_nz.get(wposd.div(12.0).into_array()) as f32 * 0.3;
+    let wobble = sim.gen_ctx.cave_1_nz.get(wposd.div(24.0).into_array()) as f32 * 2.0;
+
+    for (i, node) in nodes.iter().enumerate() {
+        let node = match node {
+            Some(node) => node,
+            None => continue,
+        };
+        let dist = Vec2::from(node.pos).distance(wposf);
+
+        push(carve(node.pos.z + wobble, node.radius * roughness, dist));
+
+        match node.kind {
+            CaveNodeKind::Entrance if dist < SHAFT_RADIUS * roughness => {
+                push(Some((node.pos.z, alt.max(node.surface_alt) + 8.0)))
+            }
+            CaveNodeKind::Drop => {
+                if dist < SHAFT_RADIUS * roughness {
+                    push(Some((node.pos.z - LEVEL_DEPTH, node.pos.z)));
+                }
+                push(carve(
+                    node.pos.z - LEVEL_DEPTH + wobble,
+                    TUNNEL_RADIUS * 2.0 * roughness,
+                    dist,
+                ));
+            }
+            _ => {}
+        }
+
+        // Tunnels to the neighbours in the +x and +y directions.  Every tunnel belongs to exactly
+        // one node, so each is carved once.
+        let (x, y) = (i / 3, i % 3);
+        let neighbours = [
+            if x < 2 { nodes[i + 3] } else { None },
+            if y < 2 { nodes[i + 1] } else { None },
+        ];
+        for other in neighbours.iter().filter_map(|n| n.as_ref()) {
+            if !has_tunnel(node, other) {
+                continue;
+            }
+
+            let start = Vec2::from(node.pos);
+            let seg = Vec2::from(other.pos) - start;
+            let t = ((wposf - start).dot(seg) / seg.magnitude_squared())
+                .max(0.0)
+                .min(1.0);
+            let z = Lerp::lerp(node.pos.z, other.pos.z, t) + wobble;
+
+            push(carve(
+                z,
+                TUNNEL_RADIUS * roughness,
+                (start + seg * t).distance(wposf),
+            ));
+        }
+    }
+
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::WorldOpts;
+
+    #[test]
+    fn carving_flattens_floors() {
+        assert_eq!(carve(100.0, 4.0, 0.0), Some((98.0, 104.0)));
+        assert_eq!(carve(100.0, 4.0, 4.0), None);
+
+        let (near_bottom, near_top) = carve(100.0, 4.0, 1.0).unwrap();
+        let (far_bottom, far_top) = carve(100.0, 4.0, 3.0).unwrap();
+        assert!(far_bottom > near_bottom && far_top < near_top);
+    }
+
+    #[test]
+    fn drops_lead_to_the_level_below() {
+        let node = |kind| CaveNode {
+            pos: Vec3::new(0.0, 0.0, 100.0),
+            radius: TUNNEL_RADIUS + 1.0,
+            seed: 0,
+            kind,
+            surface_alt: 200.0,
+        };
+        let junction = node(CaveNodeKind::Junction).floor_pos();
+        let drop = node(CaveNodeKind::Drop).floor_pos();
+        assert!(junction.z < 100.0);
+        assert!(drop.z < 100.0 - LEVEL_DEPTH);
+        assert_eq!(node(CaveNodeKind::Entrance).entrance_pos().z, 200.0);
+    }
+
+    #[test]
+    fn nodes_are_carved_out() {
+        let sim = WorldSim::generate(
+            1337,
+            WorldOpts {
+                size: Vec2::broadcast(WorldOpts::MIN_SIZE),
+                ..WorldOpts::default()
+            },
+        );
+        let world_size = sim
+            .opts()
+            .size
+            .map2(Vec2::from(TerrainChunkSize::SIZE), |e, sz: u32| {
+                (e * sz) as i32
+            });
+
+        let mut carved = 0;
+        for x in (0..world_size.x).step_by(256) {
+            for y in (0..world_size.y).step_by(256) {
+                let nodes = get_nodes(&sim, Vec2::new(x, y));
+                for node in nodes.iter().filter_map(|node| node.as_ref()) {
+                    let wposf = Vec2::from(node.pos);
+                    let alt = sim
+                        .get_interpolated(wposf.map(|e| e as i32), |c| c.alt)
+                        .unwrap();
+                    let spans = sample_column(&sim, &nodes, wposf, alt);
+                    let open_at = |z: f32| {
+                        spans
+                            .iter()
+                            .filter_map(|span| *span)
+                            .any(|(bottom, top)| bottom <= z && z <= top)
+                    };
+
+                    assert!(node.pos.z < node.surface_alt - LEVEL_DEPTH + 1.0);
+                    assert!(open_at(node.pos.z + 1.0));
+                    if node.kind == CaveNodeKind::Entrance {
+                        assert!(open_at(alt));
+                    }
+                    carved += 1;
+                }
+            }
+        }
+        assert!(carved > 0);
+    }
+}
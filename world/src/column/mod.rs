@@ -1,13 +1,14 @@
 use crate::{
     all::ForestKind,
     block::StructureMeta,
-    sim::{LocationInfo, SimChunk},
+    cave::{self, CaveSpans},
+    sim::{LocationInfo, SimChunk, WaterKind, ROAD_DIRS},
     util::{RandomPerm, Sampler, UnitChooser},
-    World, CONFIG,
+    World, WorldOpts,
 };
 use common::{
     assets,
-    terrain::{BlockKind, Structure, TerrainChunkSize},
+    terrain::{BiomeKind, BlockKind, Structure, TerrainChunkSize},
     vol::VolSize,
 };
 use lazy_static::lazy_static;
@@ -21,10 +22,17 @@ use vek::*;
 
 pub struct ColumnGen<'a> {
     world: &'a World,
+    opts: &'a WorldOpts,
 }
 
 static UNIT_CHOOSER: UnitChooser = UnitChooser::new(0x700F4EC7);
 static DUNGEON_RAND: RandomPerm = RandomPerm::new(0x42782335);
+static HOUSE_RAND: RandomPerm = RandomPerm::new(0x1D4A5C3B);
+
+/// Half the width of a road.
+const ROAD_WIDTH: f32 = 5.0;
+/// How far buildings keep back from the middle of a road.
+const ROAD_CLEARANCE: f32 = 20.0;
 
 lazy_static! {
     pub static ref DUNGEONS: Vec<Arc<Structure>> = vec![
@@ -52,11 +60,34 @@ lazy_static! {
             .with_default_kind(BlockKind::Dense))
         .unwrap(),
     ];
+    pub static ref HOUSES: Vec<Arc<Structure>> = vec![
+        house_asset("world.structure.human.house_1"),
+        house_asset("world.structure.human.house_2"),
+        house_asset("world.structure.human.blacksmith"),
+        house_asset("world.structure.human.stables_1"),
+        house_asset("world.structure.human.mage_tower"),
+    ];
+    pub static ref TOWN_HALLS: Vec<Arc<Structure>> = vec![
+        house_asset("world.structure.human.town_hall"),
+        house_asset("world.structure.human.town_hall_spire"),
+    ];
+}
+
+fn house_asset(path: &str) -> Arc<Structure> {
+    assets::load_map(path, |s: Structure| s.with_footprint_center(1))
+        .expect("Failed to load building asset")
 }
 
 impl<'a> ColumnGen<'a> {
     pub fn new(world: &'a World) -> Self {
-        Self { world }
+        Self {
+            world,
+            opts: world.sim().opts(),
+        }
+    }
+
+    pub fn opts(&self) -> &'a WorldOpts {
+        self.opts
     }
 
     fn get_local_structure(&self, wpos: Vec2<i32>) -> Option<StructureData> {
@@ -77,8 +108,8 @@ impl<'a> ColumnGen<'a> {
         let chunk = self.world.sim().get(chunk_pos)?;
 
         if seed % 5 == 2
-            && chunk.temp > CONFIG.desert_temp
-            && chunk.alt > CONFIG.sea_level + 5.0
+            && chunk.temp > self.opts.desert_temp
+            && chunk.alt > self.opts.sea_level + 5.0
             && chunk.chaos <= 0.35
         {
             Some(StructureData {
@@ -100,6 +131,118 @@ impl<'a> ColumnGen<'a> {
         }
     }
 
+    /// Find the river segment (running from a river chunk to the chunk it drains into) that is
+    /// closest to `wposf`, measured from the edge of the channel.
+    fn get_river(&self, chunk_pos: Vec2<i32>, wposf: Vec2<f32>) -> Option<RiverSample> {
+        let sim = self.world.sim();
+        let chunk_sz = Vec2::from(TerrainChunkSize::SIZE).map(|e: u32| e as f32);
+
+        (-1..3)
+            .map(|i| (-1..3).map(move |j| chunk_pos + Vec2::new(i, j)))
+            .flatten()
+            .filter_map(|pos| {
+                let chunk = sim.get(pos)?;
+                let (width, depth) = match chunk.water.kind {
+                    Some(WaterKind::River { width, depth }) => (width, depth),
+                    _ => return None,
+                };
+                let downhill_pos = chunk.water.downhill?;
+                let downhill = sim.get(downhill_pos)?;
+
+                let start = pos.map(|e| e as f32) * chunk_sz;
+                let end = downhill_pos.map(|e| e as f32) * chunk_sz;
+                let seg = end - start;
+                let t = ((wposf - start).dot(seg) / seg.magnitude_squared())
+                    .max(0.0)
+                    .min(1.0);
+
+                Some(RiverSample {
+                    dist: (start + seg * t).distance(wposf),
+                    width,
+                    depth,
+                    water_alt: Lerp::lerp(chunk.water.water_alt, downhill.water.water_alt, t),
+                })
+            })
+            .min_by(|a, b| {
+                (a.dist - a.width * 0.5)
+                    .partial_cmp(&(b.dist - b.width * 0.5))
+                    .unwrap()
+            })
+    }
+
+    /// Place a settlement building at `wpos` if it falls on a building lot.  The structure point
+    /// nearest to the centre of the settlement always gets the town hall.
+    fn get_settlement_structure(&self, wpos: Vec2<i32>, seed: u32) -> Option<StructureData> {
+        let sim = self.world.sim();
+        let chunk_pos = wpos.map2(Vec2::from(TerrainChunkSize::SIZE), |e, sz: u32| {
+            e / sz as i32
+        });
+        let chunk = sim.get(chunk_pos)?;
+        let loc = &sim.locations[chunk.location.as_ref()?.loc_idx];
+
+        if !loc.settlement_area().contains_point(wpos)
+            || chunk.water.kind.is_some()
+            || chunk.near_cliffs
+        {
+            return None;
+        }
+
+        let is_town_hall = sim
+            .gen_ctx
+            .structure_gen
+            .get(loc.center)
+            .iter()
+            .min_by_key(|(pos, _)| pos.distance_squared(loc.center))
+            .map(|(pos, _)| *pos == wpos)
+            .unwrap_or(false);
+
+        let volume = if is_town_hall {
+            &TOWN_HALLS[HOUSE_RAND.get(seed) as usize % TOWN_HALLS.len()]
+        } else if self.get_road_dist(wpos.map(|e| e as f32)) > ROAD_CLEARANCE {
+            let building = loc.settlement().get_building(loc.center, wpos)?;
+            &HOUSES[HOUSE_RAND.get(building.seed) as usize % HOUSES.len()]
+        } else {
+            return None;
+        };
+
+        Some(StructureData {
+            pos: wpos,
+            seed,
+            meta: Some(StructureMeta::Volume {
+                units: UNIT_CHOOSER.get(seed),
+                volume,
+            }),
+        })
+    }
+
+    /// Distance from `wposf` to the middle of the nearest road.
+    fn get_road_dist(&self, wposf: Vec2<f32>) -> f32 {
+        let sim = self.world.sim();
+        let chunk_sz = Vec2::from(TerrainChunkSize::SIZE).map(|e: u32| e as f32);
+        let chunk_pos = wposf.map2(chunk_sz, |e, sz| (e / sz).floor() as i32);
+
+        (-1..2)
+            .map(|i| (-1..2).map(move |j| chunk_pos + Vec2::new(i, j)))
+            .flatten()
+            .filter_map(|pos| Some((pos, sim.get(pos)?.roads)))
+            .map(|(pos, roads)| {
+                let start = (pos.map(|e| e as f32) + 0.5) * chunk_sz;
+                ROAD_DIRS
+                    .iter()
+                    .enumerate()
+                    .filter(move |(i, _)| roads & (1 << i) != 0)
+                    .map(move |(_, dir)| {
+                        let seg = dir.map(|e| e as f32) * chunk_sz;
+                        let t = ((wposf - start).dot(seg) / seg.magnitude_squared())
+                            .max(0.0)
+                            .min(1.0);
+                        (start + seg * t).distance(wposf)
+                    })
+            })
+            .flatten()
+            .fold(f32::INFINITY, |a, b| a.min(b))
+    }
+
     fn gen_close_structures(&self, wpos: Vec2<i32>) -> [Option<StructureData>; 9] {
         let mut metas = [None; 9];
         self.world
@@ -111,11 +254,14 @@ impl<'a> ColumnGen<'a> {
             .copied()
             .enumerate()
             .for_each(|(i, (pos, seed))| {
-                metas[i] = self.get_local_structure(pos).or(Some(StructureData {
-                    pos,
-                    seed,
-                    meta: None,
-                }));
+                metas[i] = self
+                    .get_settlement_structure(pos, seed)
+                    .or_else(|| self.get_local_structure(pos))
+                    .or(Some(StructureData {
+                        pos,
+                        seed,
+                        meta: None,
+                    }));
             });
         metas
     }
@@ -132,6 +278,7 @@ impl<'a> Sampler for ColumnGen<'a> {
         });
 
         let sim = self.world.sim();
+        let opts = self.opts;
 
         let turb = Vec2::new(
             sim.gen_ctx.turb_x_nz.get((wposf.div(48.0)).into_array()) as f32,
@@ -142,7 +289,6 @@ impl<'a> Sampler for ColumnGen<'a> {
         let alt_base = sim.get_interpolated(wpos, |chunk| chunk.alt_base)?;
         let chaos = sim.get_interpolated(wpos, |chunk| chunk.chaos)?;
         let temp = sim.get_interpolated(wpos, |chunk| chunk.temp)?;
-        let dryness = sim.get_interpolated(wpos, |chunk| chunk.dryness)?;
         let humidity = sim.get_interpolated(wpos, |chunk| chunk.humidity)?;
         let rockiness = sim.get_interpolated(wpos, |chunk| chunk.rockiness)?;
         let tree_density = sim.get_interpolated(wpos, |chunk| chunk.tree_density)?;
@@ -150,19 +296,6 @@ impl<'a> Sampler for ColumnGen<'a> {
 
         let sim_chunk = sim.get(chunk_pos)?;
 
-        const RIVER_PROPORTION: f32 = 0.025;
-
-        /*
-        let river = dryness
-            .abs()
-            .neg()
-            .add(RIVER_PROPORTION)
-            .div(RIVER_PROPORTION)
-            .max(0.0)
-            .mul((1.0 - (chaos - 0.15) * 20.0).max(0.0).min(1.0));
-        */
-        let river = 0.0;
-
         let cliff_hill = (sim
             .gen_ctx
             .small_nz
@@ -181,15 +314,40 @@ impl<'a> Sampler for ColumnGen<'a> {
         let is_cliffs = sim_chunk.is_cliffs;
         let near_cliffs = sim_chunk.near_cliffs;
 
-        let alt = riverless_alt
-            - (1.0 - river)
-                .mul(f32::consts::PI)
-                .cos()
-                .add(1.0)
-                .mul(0.5)
-                .mul(24.0);
+        // Rivers carve a channel with a rounded bed, and the banks slope gently down towards the
+        // water's edge.
+        const RIVER_BANK: f32 = 8.0;
+        let river_sample = self.get_river(chunk_pos, wposf_turb.map(|e| e as f32));
+        let (alt, river, river_alt) = match river_sample {
+            Some(r) if r.dist < r.width * 0.5 => {
+                let river = 1.0 - r.dist / (r.width * 0.5);
+                let bed = r.water_alt - river.mul(f32::consts::FRAC_PI_2).sin().mul(r.depth);
+                (riverless_alt.min(bed), river, Some(r.water_alt))
+            }
+            Some(r) if r.dist < r.width * 0.5 + RIVER_BANK => {
+                let bank = (r.dist - r.width * 0.5) / RIVER_BANK;
+                (
+                    Lerp::lerp(riverless_alt.min(r.water_alt), riverless_alt, bank),
+                    0.0,
+                    None,
+                )
+            }
+            _ => (riverless_alt, 0.0, None),
+        };
 
-        let water_level = riverless_alt - 4.0 - 5.0 * chaos;
+        // Lakes fill the depressions found by the hydrology pass up to their spill level.
+        let lake_alt = (0..2)
+            .map(|i| (0..2).map(move |j| chunk_pos + Vec2::new(i, j)))
+            .flatten()
+            .filter_map(|pos| sim.get(pos))
+            .filter(|chunk| chunk.water.is_lake())
+            .map(|chunk| chunk.water.water_alt)
+            .fold(None, |a: Option<f32>, x| a.map(|a| a.max(x)).or(Some(x)));
+
+        let water_level = opts
+            .sea_level
+            .max(river_alt.unwrap_or(opts.sea_level))
+            .max(lake_alt.unwrap_or(opts.sea_level));
 
         let rock = (sim.gen_ctx.small_nz.get(
             Vec3::new(wposf.x, wposf.y, alt as f64)
@@ -265,13 +423,13 @@ impl<'a> Sampler for ColumnGen<'a> {
             Rgb::lerp(
                 dead_tundra,
                 sand,
-                temp.sub(CONFIG.snow_temp)
-                    .div(CONFIG.desert_temp.sub(CONFIG.snow_temp))
+                temp.sub(opts.snow_temp)
+                    .div(opts.desert_temp.sub(opts.snow_temp))
                     .mul(0.5),
             ),
             cliff,
-            alt.sub(CONFIG.mountain_scale * 0.25)
-                .div(CONFIG.mountain_scale * 0.125),
+            alt.sub(opts.mountain_scale * 0.25)
+                .div(opts.mountain_scale * 0.125),
         );
         // From desert to forest humidity, we go from tundra to dirt to grass to moss to sand,
         // depending on temperature.
@@ -284,31 +442,31 @@ impl<'a> Sampler for ColumnGen<'a> {
                             tundra,
                             // snow_temp to 0
                             dirt,
-                            temp.sub(CONFIG.snow_temp)
-                                .div(CONFIG.snow_temp.neg())
+                            temp.sub(opts.snow_temp)
+                                .div(opts.snow_temp.neg())
                                 /*.sub((marble - 0.5) * 0.05)
                                 .mul(256.0)*/
                                 .mul(1.0),
                         ),
                         // 0 to tropical_temp
                         grass,
-                        temp.div(CONFIG.tropical_temp).mul(4.0),
+                        temp.div(opts.tropical_temp).mul(4.0),
                     ),
                     // tropical_temp to desert_temp
                     moss,
-                    temp.sub(CONFIG.tropical_temp)
-                        .div(CONFIG.desert_temp.sub(CONFIG.tropical_temp))
+                    temp.sub(opts.tropical_temp)
+                        .div(opts.desert_temp.sub(opts.tropical_temp))
                         .mul(1.0),
                 ),
                 // above desert_temp
                 sand,
-                temp.sub(CONFIG.desert_temp)
-                    .div(1.0 - CONFIG.desert_temp)
+                temp.sub(opts.desert_temp)
+                    .div(1.0 - opts.desert_temp)
                     .mul(4.0),
             ),
             humidity
-                .sub(CONFIG.desert_hum)
-                .div(CONFIG.forest_hum.sub(CONFIG.desert_hum))
+                .sub(opts.desert_hum)
+                .div(opts.forest_hum.sub(opts.desert_hum))
                 .mul(1.0),
         );
         // From forest to jungle humidity, we go from snow to dark grass to grass to tropics to sand
@@ -321,23 +479,23 @@ impl<'a> Sampler for ColumnGen<'a> {
                         snow_moss,
                         // 0 to tropical_temp
                         grass,
-                        temp.div(CONFIG.tropical_temp).mul(4.0),
+                        temp.div(opts.tropical_temp).mul(4.0),
                     ),
                     // tropical_temp to desert_temp
                     tropical,
-                    temp.sub(CONFIG.tropical_temp)
-                        .div(CONFIG.desert_temp.sub(CONFIG.tropical_temp))
+                    temp.sub(opts.tropical_temp)
+                        .div(opts.desert_temp.sub(opts.tropical_temp))
                         .mul(1.0),
                 ),
                 // above desert_temp
                 sand,
-                temp.sub(CONFIG.desert_temp)
-                    .div(1.0 - CONFIG.desert_temp)
+                temp.sub(opts.desert_temp)
+                    .div(1.0 - opts.desert_temp)
                     .mul(4.0),
             ),
             humidity
-                .sub(CONFIG.forest_hum)
-                .div(CONFIG.jungle_hum.sub(CONFIG.forest_hum))
+                .sub(opts.forest_hum)
+                .div(opts.jungle_hum.sub(opts.forest_hum))
                 .mul(1.0),
         );
         // From jungle humidity upwards, we go from snow to grass to rainforest to tropics to sand.
@@ -349,63 +507,37 @@ impl<'a> Sampler for ColumnGen<'a> {
                         snow_moss,
                         // 0 to tropical_temp
                         rainforest,
-                        temp.div(CONFIG.tropical_temp).mul(4.0),
+                        temp.div(opts.tropical_temp).mul(4.0),
                     ),
                     // tropical_temp to desert_temp
                     tropical,
-                    temp.sub(CONFIG.tropical_temp)
-                        .div(CONFIG.desert_temp.sub(CONFIG.tropical_temp))
+                    temp.sub(opts.tropical_temp)
+                        .div(opts.desert_temp.sub(opts.tropical_temp))
                         .mul(4.0),
                 ),
                 // above desert_temp
                 sand,
-                temp.sub(CONFIG.desert_temp)
-                    .div(1.0 - CONFIG.desert_temp)
+                temp.sub(opts.desert_temp)
+                    .div(1.0 - opts.desert_temp)
                     .mul(4.0),
             ),
-            humidity.sub(CONFIG.jungle_hum).mul(1.0),
+            humidity.sub(opts.jungle_hum).mul(1.0),
         );
 
         // Snow covering
         let ground = Rgb::lerp(
             snow,
             ground,
-            temp.sub(CONFIG.snow_temp)
-                .max(-humidity.sub(CONFIG.desert_hum))
+            temp.sub(opts.snow_temp)
+                .max(-humidity.sub(opts.desert_hum))
                 .mul(16.0)
                 .add((marble_small - 0.5) * 0.5),
         );
 
-        // Work out if we're on a path or near a town
-        let dist_to_path = match &sim_chunk.location {
-            Some(loc) => {
-                let this_loc = &sim.locations[loc.loc_idx];
-                this_loc
-                    .neighbours
-                    .iter()
-                    .map(|j| {
-                        let other_loc = &sim.locations[*j];
-
-                        // Find the two location centers
-                        let near_0 = this_loc.center.map(|e| e as f32);
-                        let near_1 = other_loc.center.map(|e| e as f32);
-
-                        // Calculate distance to path between them
-                        (0.0 + (near_1.y - near_0.y) * wposf_turb.x as f32
-                            - (near_1.x - near_0.x) * wposf_turb.y as f32
-                            + near_1.x * near_0.y
-                            - near_0.x * near_1.y)
-                            .abs()
-                            .div(near_0.distance(near_1))
-                    })
-                    .filter(|x| x.is_finite())
-                    .min_by(|a, b| a.partial_cmp(b).unwrap())
-                    .unwrap_or(f32::INFINITY)
-            }
-            None => f32::INFINITY,
-        };
+        // Work out if we're on a road
+        let dist_to_path = self.get_road_dist(wposf_turb.map(|e| e as f32));
 
-        let on_path = dist_to_path < 5.0 && !sim_chunk.near_cliffs; // || near_0.distance(wposf_turb.map(|e| e as f32)) < 150.0;
+        let on_path = dist_to_path < ROAD_WIDTH && !sim_chunk.near_cliffs;
 
         let (alt, ground) = if on_path {
             (alt - 1.0, dirt)
@@ -413,54 +545,13 @@ impl<'a> Sampler for ColumnGen<'a> {
             (alt, ground)
         };
 
-        // Cities
-        // TODO: In a later MR
-        let building = match &sim_chunk.location {
-            Some(loc) => {
-                let loc = &sim.locations[loc.loc_idx];
-                let rpos = wposf.map2(loc.center, |a, b| a as f32 - b as f32) / 256.0 + 0.5;
-
-                if rpos.map(|e| e >= 0.0 && e < 1.0).reduce_and() {
-                    (loc.settlement
-                        .get_at(rpos)
-                        .map(|b| b.seed % 20 + 10)
-                        .unwrap_or(0)) as f32
-                } else {
-                    0.0
-                }
-            }
-            None => 0.0,
-        };
-
-        let alt = alt + building;
-
         // Caves
-        let cave_at = |wposf: Vec2<f64>| {
-            (sim.gen_ctx.cave_0_nz.get(
-                Vec3::new(wposf.x, wposf.y, alt as f64 * 8.0)
-                    .div(800.0)
-                    .into_array(),
-            ) as f32)
-                .powf(2.0)
-                .neg()
-                .add(1.0)
-                .mul((1.15 - chaos).min(1.0))
-        };
-        let cave_xy = cave_at(wposf);
-        let cave_alt = alt - 24.0
-            + (sim
-                .gen_ctx
-                .cave_1_nz
-                .get(Vec2::new(wposf.x, wposf.y).div(48.0).into_array()) as f32)
-                * 8.0
-            + (sim
-                .gen_ctx
-                .cave_1_nz
-                .get(Vec2::new(wposf.x, wposf.y).div(500.0).into_array()) as f32)
-                .add(1.0)
-                .mul(0.5)
-                .powf(15.0)
-                .mul(150.0);
+        let caves = cave::sample_column(
+            sim,
+            &cave::get_nodes(sim, wpos),
+            wposf.map(|e| e as f32),
+            alt,
+        );
 
         Some(ColumnSample {
             alt,
@@ -476,27 +567,27 @@ impl<'a> Sampler for ColumnGen<'a> {
                     Rgb::lerp(
                         cliff,
                         snow,
-                        (alt - CONFIG.sea_level
-                            - 0.4 * CONFIG.mountain_scale
+                        (alt - opts.sea_level
+                            - 0.4 * opts.mountain_scale
                             - alt_base
                             - temp * 96.0
                             - marble * 24.0)
                             / 12.0,
                     ),
-                    (alt - CONFIG.sea_level - 0.25 * CONFIG.mountain_scale + marble * 128.0)
-                        / (0.25 * CONFIG.mountain_scale),
+                    (alt - opts.sea_level - 0.25 * opts.mountain_scale + marble * 128.0)
+                        / (0.25 * opts.mountain_scale),
                 ),
                 // Beach
-                ((alt - CONFIG.sea_level - 1.0) / 2.0)
+                ((alt - opts.sea_level - 1.0) / 2.0)
                     .min(1.0 - river * 2.0)
                     .max(0.0),
             ),
             sub_surface_color: dirt,
             tree_density,
             forest_kind: sim_chunk.forest_kind,
+            biome: sim_chunk.get_biome(opts),
             close_structures: self.gen_close_structures(wpos),
-            cave_xy,
-            cave_alt,
+            caves,
             marble,
             marble_small,
             rock,
@@ -521,9 +612,9 @@ pub struct ColumnSample<'a> {
     pub sub_surface_color: Rgb<f32>,
     pub tree_density: f32,
     pub forest_kind: ForestKind,
+    pub biome: BiomeKind,
     pub close_structures: [Option<StructureData>; 9],
-    pub cave_xy: f32,
-    pub cave_alt: f32,
+    pub caves: CaveSpans,
     pub marble: f32,
     pub marble_small: f32,
     pub rock: f32,
@@ -536,6 +627,13 @@ pub struct ColumnSample<'a> {
     pub location: Option<&'a LocationInfo>,
 }
 
+struct RiverSample {
+    dist: f32,
+    width: f32,
+    depth: f32,
+    water_alt: f32,
+}
+
 #[derive(Copy, Clone)]
 pub struct StructureData {
     pub pos: Vec2<i32>,
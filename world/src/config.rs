@@ -1,6 +1,18 @@
-pub struct Config {
+use crate::Error;
+use serde_derive::{Deserialize, Serialize};
+use vek::*;
+
+/// Parameters that control the shape of a generated world.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorldOpts {
+    /// Size of the world, in chunks.
+    pub size: Vec2<u32>,
     pub sea_level: f32,
     pub mountain_scale: f32,
+    /// Lowers (or, if negative, raises) the land relative to the sea, as a fraction of
+    /// `mountain_scale`.  Higher values produce smaller continents and more islands.
+    pub ocean_bias: f32,
     pub snow_temp: f32,
     pub tropical_temp: f32,
     pub desert_temp: f32,
@@ -9,13 +21,203 @@ pub struct Config {
     pub jungle_hum: f32,
 }
 
-pub const CONFIG: Config = Config {
-    sea_level: 140.0,
-    mountain_scale: 1000.0,
-    snow_temp: -0.6,
-    tropical_temp: 0.2,
-    desert_temp: 0.6,
-    desert_hum: 0.15,
-    forest_hum: 0.5,
-    jungle_hum: 0.85,
-};
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum WorldPreset {
+    Default,
+    /// Scattered islands in a large ocean.
+    Islands,
+    /// Four times the area of the default world.
+    Large,
+    /// Gentle hills instead of mountains.
+    Flat,
+}
+
+impl Default for WorldPreset {
+    fn default() -> Self {
+        WorldPreset::Default
+    }
+}
+
+impl Default for WorldOpts {
+    fn default() -> Self {
+        Self {
+            size: Vec2::new(1024, 1024),
+            sea_level: 140.0,
+            mountain_scale: 1000.0,
+            ocean_bias: 0.0,
+            snow_temp: -0.6,
+            tropical_temp: 0.2,
+            desert_temp: 0.6,
+            desert_hum: 0.15,
+            forest_hum: 0.5,
+            jungle_hum: 0.85,
+        }
+    }
+}
+
+impl WorldOpts {
+    /// The smallest world that can be generated, in chunks along each axis.
+    pub const MIN_SIZE: u32 = 64;
+    /// The largest world that can be generated, in chunks along each axis.
+    pub const MAX_SIZE: u32 = 4096;
+
+    pub fn preset(preset: WorldPreset) -> Self {
+        match preset {
+            WorldPreset::Default => Self::default(),
+            WorldPreset::Islands => Self {
+                ocean_bias: 0.15,
+                ..Self::default()
+            },
+            WorldPreset::Large => Self {
+                size: Vec2::new(2048, 2048),
+                ..Self::default()
+            },
+            WorldPreset::Flat => Self {
+                mountain_scale: 350.0,
+                ..Self::default()
+            },
+        }
+    }
+
+    /// Check that the options describe a world that can actually be generated.
+    pub fn validate(&self) -> Result<(), Error> {
+        let fail = |msg: String| Err(Error::Other(msg));
+
+        if self
+            .size
+            .map(|e| e < Self::MIN_SIZE || e > Self::MAX_SIZE)
+            .reduce_or()
+        {
+            return fail(format!(
+                "World size {:?} must be between {} and {} chunks along each axis",
+                self.size,
+                Self::MIN_SIZE,
+                Self::MAX_SIZE
+            ));
+        }
+        // Written so that NaNs fail every check.
+        let within = |x: f32, min: f32, max: f32| x >= min && x <= max;
+        let ascending = |xs: &[f32]| xs.windows(2).all(|w| w[0] < w[1]);
+
+        if !within(self.sea_level, 0.0, 2048.0) {
+            return fail(format!("Sea level {} must be in [0, 2048]", self.sea_level));
+        }
+        if !within(self.mountain_scale, 1.0, 4096.0) {
+            return fail(format!(
+                "Mountain scale {} must be in [1, 4096]",
+                self.mountain_scale
+            ));
+        }
+        if !within(self.ocean_bias, -0.5, 0.5) {
+            return fail(format!(
+                "Ocean bias {} must be in [-0.5, 0.5]",
+                self.ocean_bias
+            ));
+        }
+        if !ascending(&[
+            -1.0,
+            self.snow_temp,
+            self.tropical_temp,
+            self.desert_temp,
+            1.0,
+        ]) {
+            return fail(
+                "Temperatures must satisfy -1 < snow_temp < tropical_temp < desert_temp < 1"
+                    .to_owned(),
+            );
+        }
+        if !ascending(&[0.0, self.desert_hum, self.forest_hum, self.jungle_hum, 1.0]) {
+            return fail(
+                "Humidities must satisfy 0 < desert_hum < forest_hum < jungle_hum < 1".to_owned(),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Size of the world, in chunks.
+    pub fn world_size(&self) -> Vec2<usize> {
+        self.size.map(|e| e as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_are_valid() {
+        for preset in &[
+            WorldPreset::Default,
+            WorldPreset::Islands,
+            WorldPreset::Large,
+            WorldPreset::Flat,
+        ] {
+            assert!(WorldOpts::preset(*preset).validate().is_ok());
+        }
+    }
+
+    #[test]
+    fn size_must_be_in_range() {
+        let with_size = |x, y| WorldOpts {
+            size: Vec2::new(x, y),
+            ..WorldOpts::default()
+        };
+
+        assert!(with_size(WorldOpts::MIN_SIZE, WorldOpts::MAX_SIZE)
+            .validate()
+            .is_ok());
+        assert!(with_size(WorldOpts::MIN_SIZE - 1, 1024).validate().is_err());
+        assert!(with_size(1024, WorldOpts::MAX_SIZE + 1).validate().is_err());
+    }
+
+    #[test]
+    fn nans_are_rejected() {
+        assert!(WorldOpts {
+            sea_level: std::f32::NAN,
+            ..WorldOpts::default()
+        }
+        .validate()
+        .is_err());
+        assert!(WorldOpts {
+            desert_temp: std::f32::NAN,
+            ..WorldOpts::default()
+        }
+        .validate()
+        .is_err());
+        assert!(WorldOpts {
+            forest_hum: std::f32::NAN,
+            ..WorldOpts::default()
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn climate_thresholds_must_be_ordered() {
+        assert!(WorldOpts {
+            snow_temp: 0.3,
+            ..WorldOpts::default()
+        }
+        .validate()
+        .is_err());
+        assert!(WorldOpts {
+            desert_temp: 1.0,
+            ..WorldOpts::default()
+        }
+        .validate()
+        .is_err());
+        assert!(WorldOpts {
+            jungle_hum: 0.4,
+            ..WorldOpts::default()
+        }
+        .validate()
+        .is_err());
+        assert!(WorldOpts {
+            desert_hum: 0.0,
+            ..WorldOpts::default()
+        }
+        .validate()
+        .is_err());
+    }
+}
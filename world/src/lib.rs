@@ -8,13 +8,14 @@
 
 mod all;
 mod block;
+mod cave;
 mod column;
 pub mod config;
 pub mod sim;
 pub mod util;
 
 // Reexports
-pub use crate::config::CONFIG;
+pub use crate::config::{WorldOpts, WorldPreset};
 
 use crate::{
     block::BlockGen,
@@ -26,7 +27,7 @@ use common::{
     vol::{ReadVol, VolSize, Vox, WriteVol},
 };
 use rand::Rng;
-use std::time::Duration;
+use std::{path::Path, time::Duration};
 use vek::*;
 
 #[derive(Debug)]
@@ -39,12 +40,27 @@ pub struct World {
 }
 
 impl World {
-    pub fn generate(seed: u32) -> Self {
+    pub fn generate(seed: u32, opts: WorldOpts) -> Self {
         Self {
-            sim: sim::WorldSim::generate(seed),
+            sim: sim::WorldSim::generate(seed, opts),
         }
     }
 
+    /// Load a world previously written with `World::save`.  See `WorldSim::load`.
+    pub fn load(
+        path: impl AsRef<Path>,
+        seed: u32,
+        opts: WorldOpts,
+    ) -> Result<Self, sim::CacheError> {
+        Ok(Self {
+            sim: sim::WorldSim::load(path, seed, opts)?,
+        })
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), sim::CacheError> {
+        self.sim.save(path)
+    }
+
     pub fn sim(&self) -> &sim::WorldSim {
         &self.sim
     }
@@ -81,7 +97,7 @@ impl World {
             None => {
                 return (
                     TerrainChunk::new(
-                        CONFIG.sea_level as i32,
+                        self.sim.opts().sea_level as i32,
                         water,
                         air,
                         TerrainChunkMeta::void(),
@@ -91,7 +107,11 @@ impl World {
             }
         };
 
-        let meta = TerrainChunkMeta::new(sim_chunk.get_name(&self.sim), sim_chunk.get_biome());
+        let meta = TerrainChunkMeta::new(
+            sim_chunk.get_name(&self.sim),
+            self.sim.get_settlement_name(chunk_pos),
+            sim_chunk.get_biome(self.sim.opts()),
+        );
         let mut sampler = self.sample_blocks();
 
         let chunk_block_pos = Vec3::from(chunk_pos) * TerrainChunkSize::SIZE.map(|e| e as i32);
@@ -136,6 +156,28 @@ impl World {
             (chunk_block_pos + lpos).map(|e| e as f32) + 0.5
         };
 
+        // Cave nodes that lie within this chunk
+        let chunk_area = Aabr {
+            min: Vec2::from(chunk_block_pos),
+            max: Vec2::from(chunk_block_pos) + chunk_size2d.map(|e: u32| e as i32 - 1),
+        };
+        let cave_nodes = cave::get_nodes(&self.sim, chunk_area.center())
+            .iter()
+            .filter_map(|node| *node)
+            .filter(|node| chunk_area.contains_point(Vec2::from(node.pos).map(|e: f32| e as i32)))
+            .collect::<Vec<_>>();
+
+        // Find the first empty space at or just above a position within the chunk
+        let settle = |wpos: Vec3<f32>| {
+            let mut lpos = wpos.map(|e| e.floor() as i32) - chunk_block_pos - Vec3::unit_z() * 4;
+
+            while chunk.get(lpos).map(|vox| !vox.is_empty()).unwrap_or(false) {
+                lpos.z += 1;
+            }
+
+            (chunk_block_pos + lpos).map(|e| e as f32) + 0.5
+        };
+
         const SPAWN_RATE: f32 = 0.1;
         const BOSS_RATE: f32 = 0.03;
         let supplement = ChunkSupplement {
@@ -147,6 +189,19 @@ impl World {
             } else {
                 Vec::new()
             },
+            cave_entrances: cave_nodes
+                .iter()
+                .filter(|node| node.kind == cave::CaveNodeKind::Entrance)
+                .map(|node| node.entrance_pos())
+                .collect(),
+            cave_chambers: cave_nodes
+                .iter()
+                .filter(|node| match node.kind {
+                    cave::CaveNodeKind::Chamber | cave::CaveNodeKind::Drop => true,
+                    _ => false,
+                })
+                .map(|node| settle(node.floor_pos()))
+                .collect(),
         };
 
         (chunk, supplement)
@@ -160,10 +215,18 @@ pub struct NpcInfo {
 
 pub struct ChunkSupplement {
     pub npcs: Vec<NpcInfo>,
+    /// Where cave shafts break through the surface.
+    pub cave_entrances: Vec<Vec3<f32>>,
+    /// Floors of cave chambers, where cave dwellers and loot can be placed.
+    pub cave_chambers: Vec<Vec3<f32>>,
 }
 
 impl Default for ChunkSupplement {
     fn default() -> Self {
-        Self { npcs: Vec::new() }
+        Self {
+            npcs: Vec::new(),
+            cave_entrances: Vec::new(),
+            cave_chambers: Vec::new(),
+        }
     }
 }
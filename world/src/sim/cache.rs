@@ -0,0 +1,181 @@
+use super::{GenCtx, Location, SimChunk, WorldSim};
+use crate::{config::WorldOpts, util::seed_expan};
+use rand::SeedableRng;
+use rand_chacha::ChaChaRng;
+use serde_derive::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter},
+    path::Path,
+};
+
+/// Version of the world file format.  This must be bumped whenever world generation or any of the
+/// types stored in the file change, so that stale caches are regenerated rather than loaded.
+pub const WORLD_FILE_VERSION: u32 = 3;
+
+/// Written at the start of every world file, so that we can decide whether a cache is usable
+/// without reading the (much larger) body.
+#[derive(Serialize, Deserialize)]
+struct Header {
+    version: u32,
+    /// The seed after the generation context has been created from it (i.e: `WorldSim::seed`).
+    seed: u32,
+    opts: WorldOpts,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Body {
+    chunks: Vec<SimChunk>,
+    locations: Vec<Location>,
+    /// Position of the world's RNG in its stream, split into high and low halves.
+    rng_pos: (u64, u64),
+}
+
+#[derive(Debug)]
+pub enum CacheError {
+    Io(io::Error),
+    Format(bincode::Error),
+    /// The file was written by a different version of the world generator.
+    VersionMismatch(u32),
+    /// The file holds a world generated from a different seed.
+    SeedMismatch,
+    /// The file holds a world generated with different options.
+    OptsMismatch,
+}
+
+impl From<io::Error> for CacheError {
+    fn from(err: io::Error) -> Self {
+        CacheError::Io(err)
+    }
+}
+
+impl From<bincode::Error> for CacheError {
+    fn from(err: bincode::Error) -> Self {
+        CacheError::Format(err)
+    }
+}
+
+impl WorldSim {
+    /// Write the simulated world to `path` so that it can be loaded with `WorldSim::load` instead
+    /// of being generated again.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), CacheError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        let rng_pos = self.rng.get_word_pos();
+
+        bincode::serialize_into(
+            &mut writer,
+            &Header {
+                version: WORLD_FILE_VERSION,
+                seed: self.seed,
+                opts: self.opts.clone(),
+            },
+        )?;
+        // Serialize borrowed data to avoid cloning the whole world.
+        bincode::serialize_into(
+            &mut writer,
+            &(
+                &self.chunks,
+                &self.locations,
+                ((rng_pos >> 64) as u64, rng_pos as u64),
+            ),
+        )?;
+
+        Ok(())
+    }
+
+    /// Load a world previously written with `WorldSim::save`.  Fails if the file was written by a
+    /// different version of the world generator or the world was generated from a different seed
+    /// or with different options.
+    pub fn load(
+        path: impl AsRef<Path>,
+        mut seed: u32,
+        opts: WorldOpts,
+    ) -> Result<Self, CacheError> {
+        let mut reader = BufReader::new(File::open(path)?);
+
+        // The noise generators aren't stored, since they can be recreated cheaply from the seed.
+        let gen_ctx = GenCtx::new(&mut seed);
+
+        let header: Header = bincode::deserialize_from(&mut reader)?;
+        if header.version != WORLD_FILE_VERSION {
+            return Err(CacheError::VersionMismatch(header.version));
+        }
+        if header.seed != seed {
+            return Err(CacheError::SeedMismatch);
+        }
+        if header.opts != opts {
+            return Err(CacheError::OptsMismatch);
+        }
+
+        let body: Body = bincode::deserialize_from(&mut reader)?;
+        let mut rng = ChaChaRng::from_seed(seed_expan::rng_state(seed));
+        rng.set_word_pos(((body.rng_pos.0 as u128) << 64) | body.rng_pos.1 as u128);
+
+        Ok(Self {
+            seed,
+            chunks: body.chunks,
+            locations: body.locations,
+            opts,
+            gen_ctx,
+            rng,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, fs};
+    use vek::*;
+
+    fn small_world() -> WorldOpts {
+        WorldOpts {
+            size: Vec2::broadcast(WorldOpts::MIN_SIZE),
+            ..WorldOpts::default()
+        }
+    }
+
+    #[test]
+    fn saved_worlds_load_unchanged() {
+        let path = env::temp_dir().join(format!("veloren-world-{}.bin", std::process::id()));
+        let mut sim = WorldSim::generate(42, small_world());
+        // Advance the RNG so that restoring its position is actually tested.
+        sim.rng.set_word_pos(12345);
+        sim.save(&path).unwrap();
+
+        let loaded = WorldSim::load(&path, 42, small_world());
+        let seed_mismatch = WorldSim::load(&path, 43, small_world());
+        let opts_mismatch = WorldSim::load(
+            &path,
+            42,
+            WorldOpts {
+                sea_level: 100.0,
+                ..small_world()
+            },
+        );
+        fs::remove_file(&path).unwrap();
+
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded.seed, sim.seed);
+        assert_eq!(loaded.opts, sim.opts);
+        assert_eq!(loaded.rng.get_word_pos(), sim.rng.get_word_pos());
+        // Neither chunks nor locations can be compared directly, but their encodings can.
+        assert_eq!(
+            bincode::serialize(&loaded.chunks).unwrap(),
+            bincode::serialize(&sim.chunks).unwrap()
+        );
+        assert_eq!(
+            bincode::serialize(&loaded.locations).unwrap(),
+            bincode::serialize(&sim.locations).unwrap()
+        );
+
+        assert!(match seed_mismatch {
+            Err(CacheError::SeedMismatch) => true,
+            _ => false,
+        });
+        assert!(match opts_mismatch {
+            Err(CacheError::OptsMismatch) => true,
+            _ => false,
+        });
+    }
+}
@@ -0,0 +1,337 @@
+use super::{
+    util::{uniform_idx_as_vec2, vec2_as_uniform_idx},
+    SimChunk,
+};
+use crate::config::WorldOpts;
+use common::{terrain::TerrainChunkSize, vol::VolSize};
+use serde_derive::{Deserialize, Serialize};
+use std::{cmp::Ordering, collections::BinaryHeap, f32};
+use vek::*;
+
+/// Minimum accumulated flux (measured in chunks' worth of rainfall) before a chunk is considered
+/// to carry a river.
+const RIVER_MIN_FLUX: f32 = 24.0;
+/// Minimum depth of standing water in a depression before it is considered a lake.
+const LAKE_MIN_DEPTH: f32 = 2.0;
+/// Scales how quickly flowing water carves into the terrain (stream power law with m = 0.5,
+/// n = 1).
+const EROSION_FACTOR: f32 = 2.5;
+/// The most any single chunk may be lowered by erosion.
+const MAX_EROSION: f32 = 48.0;
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum WaterKind {
+    Ocean,
+    Lake,
+    River { width: f32, depth: f32 },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WaterInfo {
+    /// The chunk that water on this chunk drains into, if any (oceans are sinks).
+    pub downhill: Option<Vec2<i32>>,
+    /// Total rainfall and groundwater collected by this chunk and everything upstream of it.
+    pub flux: f32,
+    /// Altitude of the water surface (only meaningful if `kind` is not `None`).
+    pub water_alt: f32,
+    pub kind: Option<WaterKind>,
+}
+
+impl Default for WaterInfo {
+    fn default() -> Self {
+        Self {
+            downhill: None,
+            flux: 0.0,
+            water_alt: 0.0,
+            kind: None,
+        }
+    }
+}
+
+impl WaterInfo {
+    pub fn is_river(&self) -> bool {
+        match self.kind {
+            Some(WaterKind::River { .. }) => true,
+            _ => false,
+        }
+    }
+
+    pub fn is_lake(&self) -> bool {
+        self.kind == Some(WaterKind::Lake)
+    }
+}
+
+/// An entry in the priority-flood queue.  Ordered so that `BinaryHeap` pops the *lowest* filled
+/// altitude first, with ties broken by index so that the result is deterministic.
+#[derive(Copy, Clone, PartialEq)]
+struct FloodCell {
+    alt: f32,
+    posi: usize,
+}
+
+impl Eq for FloodCell {}
+
+impl Ord for FloodCell {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .alt
+            .partial_cmp(&self.alt)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| other.posi.cmp(&self.posi))
+    }
+}
+
+impl PartialOrd for FloodCell {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn neighbors(size: Vec2<usize>, posi: usize) -> impl Iterator<Item = usize> {
+    let pos = uniform_idx_as_vec2(size, posi);
+    (-1..=1)
+        .map(move |x| (-1..=1).map(move |y| Vec2::new(x, y)))
+        .flatten()
+        .filter(|offs| *offs != Vec2::zero())
+        .map(move |offs| pos + offs)
+        .filter(|pos| pos.map2(size, |e, sz| e >= 0 && e < sz as i32).reduce_and())
+        .map(move |pos| vec2_as_uniform_idx(size, pos))
+}
+
+/// Run the hydrology pass over a freshly generated set of chunks.
+///
+/// 1. Depressions are filled using a priority flood from the ocean, which gives us the level
+///    that standing water would settle at everywhere on the map.
+/// 2. Each chunk drains into its steepest downhill neighbour on the filled surface (or, across
+///    flat lake surfaces, towards the chunk the flood reached it from).
+/// 3. Rainfall (driven by humidity) and groundwater springs are accumulated downstream.
+/// 4. River chunks are eroded according to their flux and slope, and classified as rivers or
+///    lakes.
+pub(super) fn simulate(chunks: &mut [SimChunk], opts: &WorldOpts) {
+    let size = opts.world_size();
+    let sea_level = opts.sea_level;
+    let chunk_count = size.x * size.y;
+
+    // Priority flood.  `order` is the order in which chunks are popped, which is non-decreasing
+    // in filled altitude, so walking it backwards always visits upstream chunks first.
+    let mut filled = vec![f32::INFINITY; chunk_count];
+    let mut parent = vec![None; chunk_count];
+    let mut order = Vec::with_capacity(chunk_count);
+    let mut queue = BinaryHeap::new();
+
+    for posi in 0..chunk_count {
+        let pos = uniform_idx_as_vec2(size, posi);
+        let on_edge =
+            pos.x == 0 || pos.y == 0 || pos.x == size.x as i32 - 1 || pos.y == size.y as i32 - 1;
+        if chunks[posi].alt < sea_level || on_edge {
+            filled[posi] = chunks[posi].alt.max(sea_level);
+            queue.push(FloodCell {
+                alt: filled[posi],
+                posi,
+            });
+        }
+    }
+
+    let mut visited = vec![false; chunk_count];
+    while let Some(FloodCell { alt, posi }) = queue.pop() {
+        if visited[posi] {
+            continue;
+        }
+        visited[posi] = true;
+        order.push(posi);
+
+        for nposi in neighbors(size, posi) {
+            let nalt = chunks[nposi].alt.max(alt);
+            if !visited[nposi] && nalt < filled[nposi] {
+                filled[nposi] = nalt;
+                parent[nposi] = Some(posi);
+                queue.push(FloodCell {
+                    alt: nalt,
+                    posi: nposi,
+                });
+            }
+        }
+    }
+
+    // Flow directions.
+    let downhill = (0..chunk_count)
+        .map(|posi| {
+            if chunks[posi].alt < sea_level {
+                return None;
+            }
+            let pos = uniform_idx_as_vec2(size, posi).map(|e| e as f32);
+            neighbors(size, posi)
+                .filter(|nposi| filled[*nposi] < filled[posi])
+                .map(|nposi| {
+                    let dist = uniform_idx_as_vec2(size, nposi)
+                        .map(|e| e as f32)
+                        .distance(pos);
+                    (nposi, (filled[posi] - filled[nposi]) / dist)
+                })
+                .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+                .map(|(nposi, _)| nposi)
+                .or(parent[posi])
+        })
+        .collect::<Vec<_>>();
+
+    // Flow accumulation.  Humid chunks receive more rain, and chunks with shallow groundwater
+    // (negative dryness) feed springs.
+    let mut flux = chunks
+        .iter()
+        .map(|chunk| chunk.humidity.max(0.0) + (-chunk.dryness).max(0.0) * 2.0)
+        .collect::<Vec<_>>();
+    for &posi in order.iter().rev() {
+        if let Some(dposi) = downhill[posi] {
+            flux[dposi] += flux[posi];
+        }
+    }
+
+    // Lakes are decided before erosion so that carved river channels aren't mistaken for
+    // depressions.
+    let lake = (0..chunk_count)
+        .map(|posi| {
+            chunks[posi].alt >= sea_level && filled[posi] - chunks[posi].alt >= LAKE_MIN_DEPTH
+        })
+        .collect::<Vec<_>>();
+
+    // Erosion.  Walking downstream-first means each chunk's outlet has already been eroded, so we
+    // can keep channels monotonic by never carving below the outlet.
+    let chunk_len = TerrainChunkSize::SIZE.x as f32;
+    for &posi in order.iter() {
+        let chunk_alt = chunks[posi].alt;
+        if chunk_alt < sea_level || lake[posi] || flux[posi] < RIVER_MIN_FLUX {
+            continue;
+        }
+        if let Some(dposi) = downhill[posi] {
+            let dist = uniform_idx_as_vec2(size, posi)
+                .map(|e| e as f32)
+                .distance(uniform_idx_as_vec2(size, dposi).map(|e| e as f32))
+                * chunk_len;
+            let downhill_alt = if lake[dposi] {
+                filled[dposi]
+            } else {
+                chunks[dposi].alt.max(sea_level)
+            };
+            let slope = ((chunk_alt - downhill_alt) / dist).max(0.0);
+            let erosion = (EROSION_FACTOR * flux[posi].sqrt() * slope).min(MAX_EROSION);
+
+            chunks[posi].alt = (chunk_alt - erosion).max(downhill_alt);
+        }
+    }
+
+    // Classify the water on each chunk.
+    for posi in 0..chunk_count {
+        let chunk = &mut chunks[posi];
+        let kind = if chunk.alt < sea_level {
+            Some(WaterKind::Ocean)
+        } else if lake[posi] {
+            Some(WaterKind::Lake)
+        } else if flux[posi] >= RIVER_MIN_FLUX {
+            Some(WaterKind::River {
+                width: (flux[posi].sqrt() * 1.5).min(chunk_len * 0.75),
+                depth: (flux[posi].sqrt() * 0.5).min(12.0) + 1.0,
+            })
+        } else {
+            None
+        };
+
+        chunk.water = WaterInfo {
+            downhill: downhill[posi].map(|dposi| uniform_idx_as_vec2(size, dposi)),
+            flux: flux[posi],
+            water_alt: match kind {
+                Some(WaterKind::Ocean) => sea_level,
+                Some(WaterKind::Lake) => filled[posi],
+                Some(WaterKind::River { .. }) => chunk.alt,
+                None => chunk.alt,
+            },
+            kind,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::all::ForestKind;
+
+    const SIZE: u32 = 16;
+
+    fn opts() -> WorldOpts {
+        WorldOpts {
+            size: Vec2::broadcast(SIZE),
+            ..WorldOpts::default()
+        }
+    }
+
+    /// A square world whose altitude, relative to sea level, is given by `height`.
+    fn terrain(height: impl Fn(Vec2<i32>) -> f32) -> Vec<SimChunk> {
+        let opts = opts();
+        (0..(SIZE * SIZE) as usize)
+            .map(|posi| SimChunk {
+                chaos: 0.0,
+                alt_base: 0.0,
+                alt: opts.sea_level + height(uniform_idx_as_vec2(opts.world_size(), posi)),
+                temp: 0.0,
+                dryness: 0.0,
+                // Enough rain for the lower end of a slope to carry a river
+                humidity: 2.0,
+                rockiness: 0.0,
+                is_cliffs: false,
+                near_cliffs: false,
+                tree_density: 0.0,
+                forest_kind: ForestKind::Oak,
+                spawn_rate: 0.0,
+                location: None,
+                water: WaterInfo::default(),
+                roads: 0,
+            })
+            .collect()
+    }
+
+    fn chunk(chunks: &[SimChunk], pos: Vec2<i32>) -> &SimChunk {
+        &chunks[vec2_as_uniform_idx(opts().world_size(), pos)]
+    }
+
+    #[test]
+    fn rivers_flow_down_to_the_sea() {
+        // A slope rising to the east from the sea on the western edge
+        let mut chunks = terrain(|pos| pos.x as f32 * 20.0 - 10.0);
+        simulate(&mut chunks, &opts());
+
+        assert_eq!(
+            chunk(&chunks, Vec2::new(0, 8)).water.kind,
+            Some(WaterKind::Ocean)
+        );
+        let mouth = chunk(&chunks, Vec2::new(1, 8));
+        let source = chunk(&chunks, Vec2::new(14, 8));
+        assert!(mouth.water.is_river());
+        assert!(!source.water.is_river());
+        assert!(mouth.water.flux > source.water.flux);
+        assert_eq!(
+            chunk(&chunks, Vec2::new(5, 8)).water.downhill,
+            Some(Vec2::new(4, 8))
+        );
+
+        // Erosion never carves a chunk below the chunk it drains into
+        for chunk in chunks
+            .iter()
+            .filter(|chunk| chunk.water.kind != Some(WaterKind::Ocean))
+        {
+            let downhill = chunk.water.downhill.unwrap();
+            let outlet = &chunks[vec2_as_uniform_idx(opts().world_size(), downhill)];
+            assert!(chunk.alt >= outlet.alt.max(opts().sea_level));
+        }
+    }
+
+    #[test]
+    fn depressions_fill_with_lakes() {
+        let pit = Vec2::new(8, 8);
+        let mut chunks = terrain(|pos| if pos == pit { 80.0 } else { 100.0 });
+        simulate(&mut chunks, &opts());
+
+        let lake = chunk(&chunks, pit);
+        assert!(lake.water.is_lake());
+        assert!((lake.water.water_alt - (opts().sea_level + 100.0)).abs() < 0.01);
+        assert!(!chunk(&chunks, pit + Vec2::new(1, 0)).water.is_lake());
+    }
+}
@@ -1,10 +1,11 @@
-use super::Settlement;
+use super::{settlement::SETTLEMENT_SIZE, Settlement};
 use hashbrown::HashSet;
 use rand::seq::SliceRandom;
 use rand::Rng;
+use serde_derive::{Deserialize, Serialize};
 use vek::*;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Location {
     pub(crate) name: String,
     pub(crate) center: Vec2<i32>,
@@ -28,12 +29,29 @@ impl Location {
         &self.name
     }
 
+    /// The centre of the location, in world blocks.
+    pub fn center(&self) -> Vec2<i32> {
+        self.center
+    }
+
     pub fn kingdom(&self) -> Option<&Kingdom> {
         self.kingdom.as_ref()
     }
+
+    pub fn settlement(&self) -> &Settlement {
+        &self.settlement
+    }
+
+    /// The area, in world blocks, covered by this location's settlement.
+    pub fn settlement_area(&self) -> Aabr<i32> {
+        Aabr {
+            min: self.center - SETTLEMENT_SIZE / 2,
+            max: self.center + SETTLEMENT_SIZE / 2,
+        }
+    }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Kingdom {
     region_name: String,
 }
@@ -0,0 +1,103 @@
+use super::{WaterKind, WorldSim};
+use common::{
+    msg::{MapLocation, WorldMap},
+    terrain::{BiomeKind, TerrainChunkSize},
+    vol::VolSize,
+};
+use vek::*;
+
+impl WorldSim {
+    /// Draw a low resolution overview of the world for clients to display, no more than
+    /// `max_dim` pixels along either side.
+    pub fn generate_map(&self, max_dim: u32) -> WorldMap {
+        let size = self.opts.size;
+        // Never use more than one pixel per chunk.
+        let dimensions = if size.reduce_max() > max_dim {
+            size.map(|e| (e * max_dim / size.reduce_max()).max(1))
+        } else {
+            size
+        };
+
+        let chunks = (0..dimensions.y)
+            .map(|y| (0..dimensions.x).map(move |x| Vec2::new(x, y)))
+            .flatten()
+            .map(|px| {
+                // The first row of the map is the northern edge of the world.
+                let pos = Vec2::new(px.x, dimensions.y - 1 - px.y);
+                let chunk_pos = pos.map(|e| e as f32 + 0.5) * size.map(|e| e as f32)
+                    / dimensions.map(|e| e as f32);
+                self.get(chunk_pos.map(|e| e as i32))
+            })
+            .collect::<Vec<_>>();
+
+        let (min_alt, max_alt) = chunks
+            .iter()
+            .filter_map(|chunk| chunk.map(|chunk| chunk.alt))
+            .fold(
+                (self.opts.sea_level, self.opts.sea_level),
+                |(min, max), alt| (min.min(alt), max.max(alt)),
+            );
+
+        let sea_level = self.opts.sea_level;
+        let mountain_scale = self.opts.mountain_scale;
+        let colors = chunks
+            .iter()
+            .map(|chunk| match chunk {
+                Some(chunk) => match chunk.water.kind {
+                    Some(WaterKind::Ocean) => Lerp::lerp(
+                        Rgb::new(50.0, 90.0, 170.0),
+                        Rgb::new(15.0, 30.0, 80.0),
+                        (sea_level - chunk.alt) / (mountain_scale * 0.25),
+                    ),
+                    Some(_) => Rgb::new(70.0, 130.0, 200.0),
+                    None if chunk.roads != 0 => Rgb::new(170.0, 140.0, 100.0),
+                    None => {
+                        let base = match chunk.get_biome(&self.opts) {
+                            BiomeKind::Forest => Rgb::new(45.0, 100.0, 40.0),
+                            BiomeKind::Taiga => Rgb::new(40.0, 80.0, 55.0),
+                            BiomeKind::Jungle => Rgb::new(25.0, 110.0, 30.0),
+                            BiomeKind::Mangrove => Rgb::new(50.0, 90.0, 45.0),
+                            BiomeKind::Savannah => Rgb::new(170.0, 165.0, 80.0),
+                            BiomeKind::Desert => Rgb::new(210.0, 190.0, 120.0),
+                            BiomeKind::Beach => Rgb::new(220.0, 210.0, 160.0),
+                            BiomeKind::Snowlands => Rgb::new(235.0, 240.0, 245.0),
+                            BiomeKind::Tundra => Rgb::new(150.0, 155.0, 130.0),
+                            BiomeKind::Mountain => Rgb::new(130.0, 120.0, 110.0),
+                            BiomeKind::Swamp => Rgb::new(80.0, 95.0, 55.0),
+                            _ => Rgb::new(100.0, 150.0, 60.0),
+                        };
+                        // Higher ground is drawn lighter
+                        let height = ((chunk.alt - sea_level) / mountain_scale).max(0.0).min(1.0);
+                        base * (0.75 + height * 0.5)
+                    }
+                },
+                None => Rgb::zero(),
+            })
+            .map(|col: Rgb<f32>| col.map(|e| e.max(0.0).min(255.0) as u8))
+            .collect();
+
+        let altitudes = chunks
+            .iter()
+            .map(|chunk| {
+                chunk
+                    .map(|chunk| (chunk.alt - min_alt) / (max_alt - min_alt).max(1.0) * 255.0)
+                    .unwrap_or(0.0) as u8
+            })
+            .collect();
+
+        WorldMap {
+            dimensions,
+            world_size: size.map2(Vec2::from(TerrainChunkSize::SIZE), |e, sz: u32| e * sz),
+            colors,
+            altitudes,
+            locations: self
+                .locations
+                .iter()
+                .map(|loc| MapLocation {
+                    name: loc.name().to_string(),
+                    pos: loc.center(),
+                })
+                .collect(),
+        }
+    }
+}
@@ -1,18 +1,25 @@
+mod cache;
+mod hydrology;
 mod location;
+mod map;
+mod road;
 mod settlement;
 mod util;
 
 // Reexports
+pub use self::cache::{CacheError, WORLD_FILE_VERSION};
+pub use self::hydrology::{WaterInfo, WaterKind};
 pub use self::location::Location;
-pub use self::settlement::Settlement;
+pub use self::road::ROAD_DIRS;
+pub use self::settlement::{Settlement, SETTLEMENT_SIZE};
 use self::util::{
     cdf_irwin_hall, uniform_idx_as_vec2, uniform_noise, vec2_as_uniform_idx, InverseCdf,
 };
 
 use crate::{
     all::ForestKind,
+    config::WorldOpts,
     util::{seed_expan, Sampler, StructureGen2d},
-    CONFIG,
 };
 use common::{
     terrain::{BiomeKind, TerrainChunkSize},
@@ -23,22 +30,21 @@ use noise::{
 };
 use rand::{Rng, SeedableRng};
 use rand_chacha::ChaChaRng;
+use serde_derive::{Deserialize, Serialize};
 use std::{
     f32,
     ops::{Add, Div, Mul, Neg, Sub},
 };
 use vek::*;
 
-pub const WORLD_SIZE: Vec2<usize> = Vec2 { x: 1024, y: 1024 };
-
 /// Calculates the smallest distance along an axis (x, y) from an edge of
-/// the world.  This value is maximal at WORLD_SIZE / 2 and minimized at the extremes
-/// (0 or WORLD_SIZE on one or more axes).  It then divides the quantity by cell_size,
+/// the world.  This value is maximal at size / 2 and minimized at the extremes
+/// (0 or size on one or more axes).  It then divides the quantity by cell_size,
 /// so the final result is 1 when we are not in a cell along the edge of the world, and
 /// ranges between 0 and 1 otherwise (lower when the chunk is closer to the edge).
-fn map_edge_factor(posi: usize) -> f32 {
-    uniform_idx_as_vec2(posi)
-        .map2(WORLD_SIZE.map(|e| e as i32), |e, sz| {
+fn map_edge_factor(size: Vec2<usize>, posi: usize) -> f32 {
+    uniform_idx_as_vec2(size, posi)
+        .map2(size.map(|e| e as i32), |e, sz| {
             (sz / 2 - (e - sz / 2).abs()) as f32 / 16.0
         })
         .reduce_partial_min()
@@ -65,7 +71,7 @@ pub(crate) struct GenCtx {
     pub alt_nz: HybridMulti,
     pub hill_nz: SuperSimplex,
     pub temp_nz: SuperSimplex,
-    // Fresh groundwater (currently has no effect, but should influence humidity)
+    // Fresh groundwater (feeds springs during the hydrology pass)
     pub dry_nz: BasicMulti,
     // Humidity noise
     pub humid_nz: Billow,
@@ -78,30 +84,35 @@ pub(crate) struct GenCtx {
 
     pub cave_0_nz: SuperSimplex,
     pub cave_1_nz: SuperSimplex,
+    // Stone strata and ore veins
+    pub ore_nz: SuperSimplex,
 
     pub structure_gen: StructureGen2d,
     pub region_gen: StructureGen2d,
     pub cliff_gen: StructureGen2d,
+    pub cave_gen: StructureGen2d,
 }
 
 pub struct WorldSim {
     pub seed: u32,
     pub(crate) chunks: Vec<SimChunk>,
     pub(crate) locations: Vec<Location>,
+    pub(crate) opts: WorldOpts,
 
     pub(crate) gen_ctx: GenCtx,
     pub rng: ChaChaRng,
 }
 
-impl WorldSim {
-    pub fn generate(mut seed: u32) -> Self {
-        let mut seed = &mut seed;
+impl GenCtx {
+    /// Create the noise generators for a world.  `seed` is advanced once per generator, so
+    /// the same starting seed always produces the same context.
+    fn new(seed: &mut u32) -> Self {
         let mut gen_seed = || {
             *seed = seed_expan::diffuse(*seed);
             *seed
         };
 
-        let mut gen_ctx = GenCtx {
+        Self {
             turb_x_nz: SuperSimplex::new().set_seed(gen_seed()),
             turb_y_nz: SuperSimplex::new().set_seed(gen_seed()),
             chaos_nz: RidgedMulti::new().set_octaves(7).set_seed(gen_seed()),
@@ -133,12 +144,21 @@ impl WorldSim {
                 // .set_octaves(6)
                 // .set_persistence(0.5)
                 .set_seed(gen_seed()),
-        };
+            cave_gen: StructureGen2d::new(gen_seed(), 128, 40),
+            ore_nz: SuperSimplex::new().set_seed(gen_seed()),
+        }
+    }
+}
+
+impl WorldSim {
+    pub fn generate(mut seed: u32, opts: WorldOpts) -> Self {
+        let mut gen_ctx = GenCtx::new(&mut seed);
+        let size = opts.world_size();
 
-        // "Base" of the chunk, to be multiplied by CONFIG.mountain_scale (multiplied value is
-        // from -0.25 * (CONFIG.mountain_scale * 1.1) to 0.25 * (CONFIG.mountain_scale * 0.9),
+        // "Base" of the chunk, to be multiplied by opts.mountain_scale (multiplied value is
+        // from -0.25 * (opts.mountain_scale * 1.1) to 0.25 * (opts.mountain_scale * 0.9),
         // but value here is from -0.275 to 0.225).
-        let alt_base = uniform_noise(|_, wposf| {
+        let alt_base = uniform_noise(size, |_, wposf| {
             Some(
                 (gen_ctx.alt_nz.get((wposf.div(12_000.0)).into_array()) as f32)
                     .sub(0.1)
@@ -148,7 +168,7 @@ impl WorldSim {
 
         // chaos produces a value in [0.1, 1.24].  It is a meta-level factor intended to reflect how
         // "chaotic" the region is--how much weird stuff is going on on this terrain.
-        let chaos = uniform_noise(|posi, wposf| {
+        let chaos = uniform_noise(size, |posi, wposf| {
             // From 0 to 1.6, but the distribution before the max is from -1 and 1, so there is a
             // 50% chance that hill will end up at 0.
             let hill = (0.0
@@ -184,9 +204,9 @@ impl WorldSim {
         });
 
         // We ignore sea level because we actually want to be relative to sea level here and want
-        // things in CONFIG.mountain_scale units, but otherwise this is a correct altitude
+        // things in opts.mountain_scale units, but otherwise this is a correct altitude
         // calculation.  Note that this is using the "unadjusted" temperature.
-        let alt = uniform_noise(|posi, wposf| {
+        let alt = uniform_noise(size, |posi, wposf| {
             // This is the extension upwards from the base added to some extra noise from -1 to 1.
             // The extra noise is multiplied by alt_main (the mountain part of the extension)
             // clamped to [0.25, 1], and made 60% larger (so the extra noise is between [-1.6, 1.6],
@@ -199,7 +219,7 @@ impl WorldSim {
             // Then, we add 1 and divide by 2 to get a value between 0.3 and 1.8.
             let alt_main = {
                 // Extension upwards from the base.  A positive number from 0 to 1 curved to be
-                // maximal at 0.  Also to be multiplied by CONFIG.mountain_scale.
+                // maximal at 0.  Also to be multiplied by opts.mountain_scale.
                 let alt_main = (gen_ctx.alt_nz.get((wposf.div(2_000.0)).into_array()) as f32)
                     .abs()
                     .powf(1.45);
@@ -214,21 +234,24 @@ impl WorldSim {
 
             // Now we can compute the final altitude using chaos.
             // We multiply by chaos clamped to [0.1, 1.24] to get a value between 0.03 and 2.232 for
-            // alt_pre, then multiply by CONFIG.mountain_scale and add to the base and sea level to
+            // alt_pre, then multiply by opts.mountain_scale and add to the base and sea level to
             // get an adjusted value, then multiply the whole thing by map_edge_factor
-            // (TODO: compute final bounds).
-            Some((alt_base[posi].1 + alt_main.mul(chaos[posi].1)).mul(map_edge_factor(posi)))
+            // (TODO: compute final bounds).  The ocean bias sinks or raises the land as a whole.
+            Some(
+                (alt_base[posi].1 + alt_main.mul(chaos[posi].1) - opts.ocean_bias)
+                    .mul(map_edge_factor(size, posi)),
+            )
         });
 
         // Check whether any tiles around this tile are not water (since Lerp will ensure that they
         // are included).
         let pure_water = |posi| {
-            let pos = uniform_idx_as_vec2(posi);
+            let pos = uniform_idx_as_vec2(size, posi);
             for x in pos.x - 1..=pos.x + 1 {
                 for y in pos.y - 1..=pos.y + 1 {
-                    if x >= 0 && y >= 0 && x < WORLD_SIZE.x as i32 && y < WORLD_SIZE.y as i32 {
-                        let posi = vec2_as_uniform_idx(Vec2::new(x, y));
-                        if alt[posi].1.mul(CONFIG.mountain_scale) > 0.0 {
+                    if x >= 0 && y >= 0 && x < size.x as i32 && y < size.y as i32 {
+                        let posi = vec2_as_uniform_idx(size, Vec2::new(x, y));
+                        if alt[posi].1.mul(opts.mountain_scale) > 0.0 {
                             return false;
                         }
                     }
@@ -238,7 +261,7 @@ impl WorldSim {
         };
 
         // A version of alt that is uniform over *non-seawater* (or land-adjacent seawater) chunks.
-        let alt_no_seawater = uniform_noise(|posi, wposf| {
+        let alt_no_seawater = uniform_noise(size, |posi, wposf| {
             if pure_water(posi) {
                 None
             } else {
@@ -247,7 +270,7 @@ impl WorldSim {
         });
 
         // -1 to 1.
-        let temp_base = uniform_noise(|posi, wposf| {
+        let temp_base = uniform_noise(size, |posi, wposf| {
             if pure_water(posi) {
                 None
             } else {
@@ -256,7 +279,7 @@ impl WorldSim {
         });
 
         // 0 to 1, hopefully.
-        let humid_base = uniform_noise(|posi, wposf| {
+        let humid_base = uniform_noise(size, |posi, wposf| {
             // Check whether any tiles around this tile are water.
             if pure_water(posi) {
                 None
@@ -279,16 +302,20 @@ impl WorldSim {
         };
 
         let mut chunks = Vec::new();
-        for i in 0..WORLD_SIZE.x * WORLD_SIZE.y {
-            chunks.push(SimChunk::generate(i, &mut gen_ctx, &gen_cdf));
+        for i in 0..size.x * size.y {
+            chunks.push(SimChunk::generate(i, &mut gen_ctx, &gen_cdf, &opts));
         }
 
+        // Route rainfall across the map, carving rivers and filling lakes.
+        hydrology::simulate(&mut chunks, &opts);
+
         let mut this = Self {
-            seed: *seed,
+            seed,
             chunks,
             locations: Vec::new(),
+            opts,
             gen_ctx,
-            rng: ChaChaRng::from_seed(seed_expan::rng_state(*seed)),
+            rng: ChaChaRng::from_seed(seed_expan::rng_state(seed)),
         };
 
         this.seed_elements();
@@ -301,7 +328,8 @@ impl WorldSim {
         let mut rng = self.rng.clone();
 
         let cell_size = 16;
-        let grid_size = WORLD_SIZE / cell_size;
+        let size = self.opts.world_size();
+        let grid_size = size / cell_size;
         let loc_count = 100;
 
         let mut loc_grid = vec![None; grid_size.product()];
@@ -340,6 +368,19 @@ impl WorldSim {
             });
         }
 
+        // Connect neighbouring locations with roads
+        let chunk_size = Vec2::from(TerrainChunkSize::SIZE).map(|e: u32| e as i32);
+        for i in 0..locations.len() {
+            for &j in locations[i].neighbours.iter().filter(|j| **j > i) {
+                let start = locations[i].center.map2(chunk_size, |e, sz| e / sz);
+                let end = locations[j].center.map2(chunk_size, |e, sz| e / sz);
+
+                if let Some(path) = road::find_path(&self.chunks, size, start, end) {
+                    road::lay_road(&mut self.chunks, size, &path);
+                }
+            }
+        }
+
         // Simulate invasion!
         let invasion_cycles = 25;
         for _ in 0..invasion_cycles {
@@ -360,8 +401,8 @@ impl WorldSim {
 
         // Place the locations onto the world
         let gen = StructureGen2d::new(self.seed, cell_size as u32, cell_size as u32 / 2);
-        for i in 0..WORLD_SIZE.x {
-            for j in 0..WORLD_SIZE.y {
+        for i in 0..size.x {
+            for j in 0..size.y {
                 let chunk_pos = Vec2::new(i as i32, j as i32);
                 let block_pos = Vec2::new(
                     chunk_pos.x * TerrainChunkSize::SIZE.x as i32,
@@ -393,15 +434,15 @@ impl WorldSim {
                     .unwrap_or(None)
                     .map(|loc_idx| LocationInfo { loc_idx, near });
 
-                let town_size = 200;
                 let in_town = self
                     .get(chunk_pos)
                     .unwrap()
                     .location
                     .as_ref()
                     .map(|l| {
-                        locations[l.loc_idx].center.distance_squared(block_pos)
-                            < town_size * town_size
+                        locations[l.loc_idx]
+                            .settlement_area()
+                            .contains_point(block_pos)
                     })
                     .unwrap_or(false);
                 if in_town {
@@ -414,12 +455,21 @@ impl WorldSim {
         self.locations = locations;
     }
 
+    pub fn locations(&self) -> &[Location] {
+        &self.locations
+    }
+
+    /// The options that this world was generated with.
+    pub fn opts(&self) -> &WorldOpts {
+        &self.opts
+    }
+
     pub fn get(&self, chunk_pos: Vec2<i32>) -> Option<&SimChunk> {
         if chunk_pos
-            .map2(WORLD_SIZE, |e, sz| e >= 0 && e < sz as i32)
+            .map2(self.opts.world_size(), |e, sz| e >= 0 && e < sz as i32)
             .reduce_and()
         {
-            Some(&self.chunks[vec2_as_uniform_idx(chunk_pos)])
+            Some(&self.chunks[vec2_as_uniform_idx(self.opts.world_size(), chunk_pos)])
         } else {
             None
         }
@@ -427,10 +477,25 @@ impl WorldSim {
 
     pub fn get_mut(&mut self, chunk_pos: Vec2<i32>) -> Option<&mut SimChunk> {
         if chunk_pos
-            .map2(WORLD_SIZE, |e, sz| e >= 0 && e < sz as i32)
+            .map2(self.opts.world_size(), |e, sz| e >= 0 && e < sz as i32)
             .reduce_and()
         {
-            Some(&mut self.chunks[vec2_as_uniform_idx(chunk_pos)])
+            let posi = vec2_as_uniform_idx(self.opts.world_size(), chunk_pos);
+            Some(&mut self.chunks[posi])
+        } else {
+            None
+        }
+    }
+
+    /// The name of the settlement that the chunk at `chunk_pos` is part of, if any.
+    pub fn get_settlement_name(&self, chunk_pos: Vec2<i32>) -> Option<String> {
+        let loc = &self.locations[self.get(chunk_pos)?.location.as_ref()?.loc_idx];
+        let block_pos = chunk_pos.map2(Vec2::from(TerrainChunkSize::SIZE), |e, sz: u32| {
+            e * sz as i32 + sz as i32 / 2
+        });
+
+        if loc.settlement_area().contains_point(block_pos) {
+            Some(loc.name().to_string())
         } else {
             None
         }
@@ -486,6 +551,7 @@ impl WorldSim {
     }
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct SimChunk {
     pub chaos: f32,
     pub alt_base: f32,
@@ -500,9 +566,12 @@ pub struct SimChunk {
     pub forest_kind: ForestKind,
     pub spawn_rate: f32,
     pub location: Option<LocationInfo>,
+    pub water: WaterInfo,
+    /// Bitmask of the directions (see `ROAD_DIRS`) in which roads leave this chunk.
+    pub roads: u8,
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Serialize, Deserialize)]
 pub struct RegionInfo {
     pub chunk_pos: Vec2<i32>,
     pub block_pos: Vec2<i32>,
@@ -510,19 +579,20 @@ pub struct RegionInfo {
     pub seed: u32,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct LocationInfo {
     pub loc_idx: usize,
     pub near: Vec<RegionInfo>,
 }
 
 impl SimChunk {
-    fn generate(posi: usize, gen_ctx: &mut GenCtx, gen_cdf: &GenCdf) -> Self {
-        let pos = uniform_idx_as_vec2(posi);
+    fn generate(posi: usize, gen_ctx: &mut GenCtx, gen_cdf: &GenCdf, opts: &WorldOpts) -> Self {
+        let pos = uniform_idx_as_vec2(opts.world_size(), posi);
         let wposf = (pos * TerrainChunkSize::SIZE.map(|e| e as i32)).map(|e| e as f64);
 
-        // FIXME: Currently unused, but should represent fresh groundwater level.
-        // Should be correlated a little with humidity, somewhat negatively with altitude,
+        // Fresh groundwater level: negative values mean shallow groundwater, which feeds springs
+        // during the hydrology pass.
+        // FIXME: Should be correlated a little with humidity, somewhat negatively with altitude,
         // and very negatively with difference in temperature from zero.
         let dryness = gen_ctx.dry_nz.get(
             (wposf
@@ -538,7 +608,7 @@ impl SimChunk {
         ) as f32;
 
         let (_, alt_base) = gen_cdf.alt_base[posi];
-        let map_edge_factor = map_edge_factor(posi);
+        let map_edge_factor = map_edge_factor(opts.world_size(), posi);
         let (_, chaos) = gen_cdf.chaos[posi];
         let (humid_uniform, _) = gen_cdf.humid_base[posi];
         let (_, alt_pre) = gen_cdf.alt[posi];
@@ -559,11 +629,11 @@ impl SimChunk {
             .sub(0.5)
             .mul(2.0);
 
-        let alt_base = alt_base.mul(CONFIG.mountain_scale);
-        let alt = CONFIG
+        let alt_base = alt_base.mul(opts.mountain_scale);
+        let alt = opts
             .sea_level
             .mul(map_edge_factor)
-            .add(alt_pre.mul(CONFIG.mountain_scale));
+            .add(alt_pre.mul(opts.mountain_scale));
 
         let cliff = gen_ctx.cliff_nz.get((wposf.div(2048.0)).into_array()) as f32 + chaos * 0.2;
 
@@ -575,7 +645,7 @@ impl SimChunk {
         let logistic_cdf = |x: f32| x.div(logistic_2_base).tanh().mul(0.5).add(0.5);
 
         // No trees in the ocean or with zero humidity (currently)
-        let tree_density = if alt <= CONFIG.sea_level + 5.0 {
+        let tree_density = if alt <= opts.sea_level + 5.0 {
             0.0
         } else {
             let tree_density = (gen_ctx.tree_nz.get((wposf.div(1024.0)).into_array()) as f32)
@@ -614,49 +684,49 @@ impl SimChunk {
                 .max(0.0),
             is_cliffs: cliff > 0.5
                 && dryness > 0.05
-                && alt > CONFIG.sea_level + 5.0
+                && alt > opts.sea_level + 5.0
                 && dryness.abs() > 0.075,
             near_cliffs: cliff > 0.25,
             tree_density,
             forest_kind: if temp > 0.0 {
-                if temp > CONFIG.desert_temp {
-                    if humidity > CONFIG.jungle_hum {
+                if temp > opts.desert_temp {
+                    if humidity > opts.jungle_hum {
                         // Forests in desert temperatures with extremely high humidity
                         // should probably be different from palm trees, but we use them
                         // for now.
                         ForestKind::Palm
-                    } else if humidity > CONFIG.forest_hum {
+                    } else if humidity > opts.forest_hum {
                         ForestKind::Palm
-                    } else if humidity > CONFIG.desert_hum {
+                    } else if humidity > opts.desert_hum {
                         // Low but not desert humidity, so we should really have some other
                         // terrain...
                         ForestKind::Savannah
                     } else {
                         ForestKind::Savannah
                     }
-                } else if temp > CONFIG.tropical_temp {
-                    if humidity > CONFIG.jungle_hum {
+                } else if temp > opts.tropical_temp {
+                    if humidity > opts.jungle_hum {
                         ForestKind::Mangrove
-                    } else if humidity > CONFIG.forest_hum {
+                    } else if humidity > opts.forest_hum {
                         // NOTE: Probably the wrong kind of tree for this climate.
                         ForestKind::Oak
-                    } else if humidity > CONFIG.desert_hum {
+                    } else if humidity > opts.desert_hum {
                         // Low but not desert... need something besides savannah.
                         ForestKind::Savannah
                     } else {
                         ForestKind::Savannah
                     }
                 } else {
-                    if humidity > CONFIG.jungle_hum {
+                    if humidity > opts.jungle_hum {
                         // Temperate climate with jungle humidity...
                         // https://en.wikipedia.org/wiki/Humid_subtropical_climates are often
                         // densely wooded and full of water.  Semitropical rainforests, basically.
                         // For now we just treet them like other rainforests.
                         ForestKind::Oak
-                    } else if humidity > CONFIG.forest_hum {
+                    } else if humidity > opts.forest_hum {
                         // Moderate climate, moderate humidity.
                         ForestKind::Oak
-                    } else if humidity > CONFIG.desert_hum {
+                    } else if humidity > opts.desert_hum {
                         // With moderate temperature and low humidity, we should probably see
                         // something different from savannah, but oh well...
                         ForestKind::Savannah
@@ -667,9 +737,9 @@ impl SimChunk {
             } else {
                 // For now we don't take humidity into account for cold climates (but we really
                 // should!) except that we make sure we only have snow pines when there is snow.
-                if temp <= CONFIG.snow_temp && humidity > CONFIG.forest_hum {
+                if temp <= opts.snow_temp && humidity > opts.forest_hum {
                     ForestKind::SnowPine
-                } else if humidity > CONFIG.desert_hum {
+                } else if humidity > opts.desert_hum {
                     ForestKind::Pine
                 } else {
                     // Should really have something like tundra.
@@ -678,6 +748,8 @@ impl SimChunk {
             },
             spawn_rate: 1.0,
             location: None,
+            water: WaterInfo::default(),
+            roads: 0,
         }
     }
 
@@ -693,16 +765,63 @@ impl SimChunk {
         }
     }
 
-    pub fn get_biome(&self) -> BiomeKind {
-        if self.alt < CONFIG.sea_level {
-            BiomeKind::Ocean
+    /// Classify the chunk into a biome, using the same climate thresholds that decide which kind
+    /// of trees grow in it.
+    pub fn get_biome(&self, opts: &WorldOpts) -> BiomeKind {
+        if self.alt < opts.sea_level {
+            return BiomeKind::Ocean;
+        } else if self.water.is_lake() {
+            return BiomeKind::Lake;
         } else if self.chaos > 0.6 {
-            BiomeKind::Mountain
-        } else if self.temp > CONFIG.desert_temp {
-            BiomeKind::Desert
-        } else if self.temp < CONFIG.snow_temp {
-            BiomeKind::Snowlands
-        } else if self.tree_density > 0.65 {
+            return BiomeKind::Mountain;
+        }
+
+        // Wetlands and beaches only form on flat land close to the sea.
+        let lowland = self.alt < opts.sea_level + 12.0 && self.chaos < 0.2;
+        let (temp, hum) = (self.temp, self.humidity);
+        if temp <= opts.snow_temp {
+            if hum > opts.forest_hum {
+                BiomeKind::Snowlands
+            } else if self.tree_density > 0.5 {
+                BiomeKind::Taiga
+            } else {
+                BiomeKind::Tundra
+            }
+        } else if temp <= 0.0 {
+            if self.tree_density > 0.5 {
+                BiomeKind::Taiga
+            } else if hum > opts.desert_hum {
+                BiomeKind::Grassland
+            } else {
+                BiomeKind::Tundra
+            }
+        } else if lowland && hum > opts.jungle_hum {
+            if temp > opts.tropical_temp {
+                BiomeKind::Mangrove
+            } else {
+                BiomeKind::Swamp
+            }
+        } else if lowland && hum <= opts.forest_hum {
+            BiomeKind::Beach
+        } else if temp > opts.desert_temp {
+            if hum > opts.jungle_hum {
+                BiomeKind::Jungle
+            } else if hum > opts.desert_hum {
+                BiomeKind::Savannah
+            } else {
+                BiomeKind::Desert
+            }
+        } else if temp > opts.tropical_temp {
+            if hum > opts.jungle_hum {
+                BiomeKind::Jungle
+            } else if self.tree_density > 0.5 {
+                BiomeKind::Forest
+            } else if hum > opts.desert_hum {
+                BiomeKind::Savannah
+            } else {
+                BiomeKind::Desert
+            }
+        } else if self.tree_density > 0.5 {
             BiomeKind::Forest
         } else {
             BiomeKind::Grassland
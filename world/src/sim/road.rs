@@ -0,0 +1,157 @@
+use super::{
+    util::{uniform_idx_as_vec2, vec2_as_uniform_idx},
+    SimChunk, WaterKind,
+};
+use common::{terrain::TerrainChunkSize, vol::VolSize};
+use hashbrown::HashMap;
+use std::{cmp::Ordering, collections::BinaryHeap};
+use vek::*;
+
+/// Offsets to each of a chunk's neighbours.  Bit `i` of `SimChunk::roads` is set if a road leaves
+/// the chunk towards `ROAD_DIRS[i]`.  The opposite of direction `i` is always `7 - i`.
+pub const ROAD_DIRS: [Vec2<i32>; 8] = [
+    Vec2 { x: -1, y: -1 },
+    Vec2 { x: 0, y: -1 },
+    Vec2 { x: 1, y: -1 },
+    Vec2 { x: -1, y: 0 },
+    Vec2 { x: 1, y: 0 },
+    Vec2 { x: -1, y: 1 },
+    Vec2 { x: 0, y: 1 },
+    Vec2 { x: 1, y: 1 },
+];
+
+/// Give up on a road if finding it takes more than this many chunk expansions.
+const MAX_ROAD_SEARCH: usize = 1 << 18;
+
+#[derive(Copy, Clone, PartialEq)]
+struct PathNode {
+    cost: f32,
+    posi: usize,
+}
+
+impl Eq for PathNode {}
+
+impl Ord for PathNode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .cost
+            .partial_cmp(&self.cost)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| other.posi.cmp(&self.posi))
+    }
+}
+
+impl PartialOrd for PathNode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// The cost of laying road from one chunk to a neighbour `dist` chunks away, or `None` if a road
+/// can't be built there.  Roads prefer gentle slopes and calm terrain, and will only cross rivers
+/// when they have to.
+fn step_cost(from: &SimChunk, to: &SimChunk, dist: f32) -> Option<f32> {
+    match to.water.kind {
+        Some(WaterKind::Ocean) | Some(WaterKind::Lake) => None,
+        kind => {
+            let slope = (to.alt - from.alt).abs() / (dist * TerrainChunkSize::SIZE.x as f32);
+            let bridge = match kind {
+                Some(WaterKind::River { .. }) => 8.0,
+                _ => 0.0,
+            };
+
+            Some(dist * (1.0 + slope * 16.0 + to.chaos * 2.0) + bridge)
+        }
+    }
+}
+
+/// Find the cheapest road between two chunks using A*, returning every chunk along the way.
+pub(super) fn find_path(
+    chunks: &[SimChunk],
+    size: Vec2<usize>,
+    start: Vec2<i32>,
+    end: Vec2<i32>,
+) -> Option<Vec<Vec2<i32>>> {
+    let in_bounds = |pos: Vec2<i32>| pos.map2(size, |e, sz| e >= 0 && e < sz as i32).reduce_and();
+    if !in_bounds(start) || !in_bounds(end) {
+        return None;
+    }
+
+    let heuristic = |pos: Vec2<i32>| pos.map(|e| e as f32).distance(end.map(|e| e as f32));
+    let (starti, endi) = (
+        vec2_as_uniform_idx(size, start),
+        vec2_as_uniform_idx(size, end),
+    );
+
+    let mut costs = HashMap::new();
+    let mut came_from = HashMap::new();
+    let mut queue = BinaryHeap::new();
+
+    costs.insert(starti, 0.0f32);
+    queue.push(PathNode {
+        cost: heuristic(start),
+        posi: starti,
+    });
+
+    let mut searched = 0;
+    while let Some(PathNode { posi, .. }) = queue.pop() {
+        if posi == endi {
+            let mut path = vec![end];
+            let mut posi = endi;
+            while let Some(&prev) = came_from.get(&posi) {
+                path.push(uniform_idx_as_vec2(size, prev));
+                posi = prev;
+            }
+            path.reverse();
+            return Some(path);
+        }
+
+        searched += 1;
+        if searched > MAX_ROAD_SEARCH {
+            return None;
+        }
+
+        let pos = uniform_idx_as_vec2(size, posi);
+        let cost = costs[&posi];
+        for dir in ROAD_DIRS.iter() {
+            let npos = pos + *dir;
+            if !in_bounds(npos) {
+                continue;
+            }
+            let nposi = vec2_as_uniform_idx(size, npos);
+            let dist = dir.map(|e| e as f32).magnitude();
+
+            // The destination itself is always reachable, even if it's in the water.
+            let step = if nposi == endi {
+                Some(dist)
+            } else {
+                step_cost(&chunks[posi], &chunks[nposi], dist)
+            };
+
+            if let Some(step) = step {
+                let ncost = cost + step;
+                if costs.get(&nposi).map(|c| ncost < *c).unwrap_or(true) {
+                    costs.insert(nposi, ncost);
+                    came_from.insert(nposi, posi);
+                    queue.push(PathNode {
+                        cost: ncost + heuristic(npos),
+                        posi: nposi,
+                    });
+                }
+            }
+        }
+    }
+
+    None
+}
+
+/// Mark a path returned by `find_path` on the chunks it passes through.
+pub(super) fn lay_road(chunks: &mut [SimChunk], size: Vec2<usize>, path: &[Vec2<i32>]) {
+    for step in path.windows(2) {
+        let dir = step[1] - step[0];
+        if let Some(i) = ROAD_DIRS.iter().position(|d| *d == dir) {
+            chunks[vec2_as_uniform_idx(size, step[0])].roads |= 1 << i;
+            chunks[vec2_as_uniform_idx(size, step[1])].roads |= 1 << (7 - i);
+        }
+    }
+}
@@ -1,7 +1,11 @@
 use rand::Rng;
+use serde_derive::{Deserialize, Serialize};
 use vek::*;
 
-#[derive(Clone, Debug)]
+/// Width, in blocks, of the square area that a settlement occupies around its location's centre.
+pub const SETTLEMENT_SIZE: i32 = 256;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Settlement {
     lot: Lot,
 }
@@ -16,14 +20,25 @@ impl Settlement {
     pub fn get_at(&self, pos: Vec2<f32>) -> Option<&Building> {
         self.lot.get_at(pos)
     }
+
+    /// Find the building whose lot covers `wpos`, for a settlement centred on `center`.
+    pub fn get_building(&self, center: Vec2<i32>, wpos: Vec2<i32>) -> Option<&Building> {
+        let rpos = (wpos - center).map(|e| e as f32) / SETTLEMENT_SIZE as f32 + 0.5;
+
+        if rpos.map(|e| e >= 0.0 && e < 1.0).reduce_and() {
+            self.get_at(rpos)
+        } else {
+            None
+        }
+    }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Building {
     pub seed: u32,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 enum Lot {
     None,
     One(Building),
@@ -1,4 +1,3 @@
-use super::WORLD_SIZE;
 use common::{terrain::TerrainChunkSize, vol::VolSize};
 use vek::*;
 
@@ -90,24 +89,25 @@ pub fn cdf_irwin_hall<const N: usize>(weights: &[f32; N], samples: [f32; N]) ->
 /// every chunk in the game).  Second component is the cached value of the noise function that
 /// generated the index.
 ///
-/// NOTE: Length should always be WORLD_SIZE.x * WORLD_SIZE.y.
+/// NOTE: Length should always be size.x * size.y, for the size of the world.
 pub type InverseCdf = Box<[(f32, f32)]>;
 
 /// Computes the position Vec2 of a SimChunk from an index, where the index was generated by
-/// uniform_noise.
-pub fn uniform_idx_as_vec2(idx: usize) -> Vec2<i32> {
-    Vec2::new((idx % WORLD_SIZE.x) as i32, (idx / WORLD_SIZE.x) as i32)
+/// uniform_noise, in a world that is `size` chunks across.
+pub fn uniform_idx_as_vec2(size: Vec2<usize>, idx: usize) -> Vec2<i32> {
+    Vec2::new((idx % size.x) as i32, (idx / size.x) as i32)
 }
 
 /// Computes the index of a Vec2 of a SimChunk from a position, where the index is generated by
-/// uniform_noise.  NOTE: Both components of idx should be in-bounds!
-pub fn vec2_as_uniform_idx(idx: Vec2<i32>) -> usize {
-    (idx.y as usize * WORLD_SIZE.x + idx.x as usize) as usize
+/// uniform_noise, in a world that is `size` chunks across.  NOTE: Both components of idx should
+/// be in-bounds!
+pub fn vec2_as_uniform_idx(size: Vec2<usize>, idx: Vec2<i32>) -> usize {
+    (idx.y as usize * size.x + idx.x as usize) as usize
 }
 
 /// Compute inverse cumulative distribution function for arbitrary function f, the hard way.  We
 /// pre-generate noise values prior to worldgen, then sort them in order to determine the correct
-/// position in the sorted order.  That lets us use `(index + 1) / (WORLDSIZE.y * WORLDSIZE.x)` as
+/// position in the sorted order.  That lets us use `(index + 1) / (size.y * size.x)` as
 /// a uniformly distributed (from almost-0 to 1) regularization of the chunks.  That is, if we
 /// apply the computed "function" F⁻¹(x, y) to (x, y) and get out p, it means that approximately
 /// (100 * p)% of chunks have a lower value for F⁻¹ than p.  The main purpose of doing this is to
@@ -136,12 +136,12 @@ pub fn vec2_as_uniform_idx(idx: Vec2<i32>) -> usize {
 /// this one, and the actual noise value (we don't need to cache it, but it makes ensuring that
 /// subsequent code that needs the noise value actually uses the same one we were using here
 /// easier).
-pub fn uniform_noise(f: impl Fn(usize, Vec2<f64>) -> Option<f32>) -> InverseCdf {
-    let mut noise = (0..WORLD_SIZE.x * WORLD_SIZE.y)
+pub fn uniform_noise(size: Vec2<usize>, f: impl Fn(usize, Vec2<f64>) -> Option<f32>) -> InverseCdf {
+    let mut noise = (0..size.x * size.y)
         .filter_map(|i| {
             (f(
                 i,
-                (uniform_idx_as_vec2(i) * TerrainChunkSize::SIZE.map(|e| e as i32))
+                (uniform_idx_as_vec2(size, i) * TerrainChunkSize::SIZE.map(|e| e as i32))
                     .map(|e| e as f64),
             )
             .map(|res| (i, res)))
@@ -157,7 +157,7 @@ pub fn uniform_noise(f: impl Fn(usize, Vec2<f64>) -> Option<f32>) -> InverseCdf
     // position of the noise in the sorted vector (divided by the vector length).
     // This guarantees a uniform distribution among the samples (excluding those that returned
     // None, which will remain at zero).
-    let mut uniform_noise = vec![(0.0, 0.0); WORLD_SIZE.x * WORLD_SIZE.y].into_boxed_slice();
+    let mut uniform_noise = vec![(0.0, 0.0); size.x * size.y].into_boxed_slice();
     let total = noise.len() as f32;
     for (noise_idx, (chunk_idx, noise_val)) in noise.into_iter().enumerate() {
         uniform_noise[chunk_idx] = ((1 + noise_idx) as f32 / total, noise_val);